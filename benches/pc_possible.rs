@@ -81,7 +81,7 @@ fn bench_pc_possibles(c: &mut Criterion) {
             board: Board64::blank(),
             height: 4,
             patterns: Rc::from(Pattern::try_from(vec![
-                Fixed(BitShapes::try_from(vec![
+                Fixed(WideBitShapes::try_from(vec![
                     T, I, O, S, L, J, Z, T, I, O,
                 ]).unwrap()),
                 Wildcard,
@@ -61,7 +61,7 @@ mod tests {
             PatternElement::One(Shape::I),
             PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
         ]).expect("Failed to create a pattern");
-        binder.pattern = Rc::from(pattern);
+        binder.pattern = Rc::from(PatternSet::from(pattern));
 
         // The others are the same.
         let board = Board64::from_str("
@@ -88,7 +88,7 @@ mod tests {
         for sequence in results.accepted_shape_sequences() {
             if sequence.shapes().starts_with(&[Shape::I, Shape::S]) {
                 total += 1;
-                if let Some(true) = results.get(sequence) {
+                if let Some(true) = results.get(&sequence) {
                     succeed += 1;
                 }
             }
@@ -100,8 +100,8 @@ mod tests {
         assert_eq!(
             succeed,
             results.iter()
-                .filter(|&(sequence, _)| sequence.shapes().starts_with(&[Shape::I, Shape::S]))
-                .filter(|&(_, result)| *result == Some(true))
+                .filter(|(sequence, _)| sequence.shapes().starts_with(&[Shape::I, Shape::S]))
+                .filter(|&(_, result)| result == Some(true))
                 .count(),
         );
     }
@@ -116,7 +116,7 @@ mod tests {
         let pattern = Pattern::try_from(vec![
             PatternElement::Factorial(ShapeCounter::one_of_each()),
         ]).expect("Failed to create a pattern");
-        binder.pattern = Rc::from(pattern);
+        binder.pattern = Rc::from(PatternSet::from(pattern));
 
         let board = Board64::from_str("
             ..........
@@ -175,10 +175,10 @@ mod tests {
         ").expect("Failed to create a board");
         let height = 4;
         binder.clipped_board = ClippedBoard::try_new(board, height).expect("Failed to clip");
-        binder.pattern = Rc::from(Pattern::try_from(vec![
+        binder.pattern = Rc::from(PatternSet::from(Pattern::try_from(vec![
             PatternElement::One(Shape::I),
             PatternElement::Permutation(ShapeCounter::one_of_each(), 4),
-        ]).expect("Failed to create a pattern"));
+        ]).expect("Failed to create a pattern")));
 
         let results = binder.try_execute().expect("Failed to execute");
         assert_eq!(results.count_succeed(), 485);
@@ -0,0 +1,80 @@
+//! A command-line interface to run the commands like solution-finder.
+//!
+//! ```text
+//! bitris-commands percent --board "XX......XX/XX.....XXX/XX....XXXX/XX.....XXX" --pattern "*p7"
+//! ```
+
+use std::process::ExitCode;
+
+use thiserror::Error;
+
+use crate::options::Options;
+
+mod options;
+mod percent;
+
+const USAGE: &str = "\
+Usage: bitris-commands <COMMAND> [OPTIONS]
+
+Commands:
+  percent    Calculates the success rate of perfect clears
+
+Run `bitris-commands <COMMAND> --help` for the options of each command.";
+
+/// A collection of errors that occur when running the commands.
+#[derive(Error, Debug)]
+pub(crate) enum CommandError {
+    #[error("Unknown command '{0}'.")]
+    UnknownCommand(String),
+    #[error("Unknown option '{0}'.")]
+    UnknownOption(String),
+    #[error("The option '--{0}' requires a value.")]
+    MissingValue(String),
+    #[error("Invalid value '{1}' for '--{0}'.")]
+    InvalidValue(String, String),
+    #[error("The options '--{0}' and '--{1}' cannot be used together.")]
+    ConflictingOptions(String, String),
+    #[error("Invalid board: {0}")]
+    InvalidBoard(String),
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(#[from] bitris_commands::PatternParseError),
+    #[error("Invalid patterns: {0}")]
+    InvalidPatternSet(#[from] bitris_commands::PatternSetParseError),
+    #[error("Failed to read '{0}': {1}")]
+    FailedToRead(String, std::io::Error),
+    #[error("Invalid fumen: {0}")]
+    InvalidFumen(#[from] bitris_commands::fumen::FumenDecodeError),
+    #[error("Invalid height: {0}")]
+    InvalidHeight(#[from] bitris_commands::ClippedBoardCreationError),
+    #[error("Failed to execute: {0}")]
+    FailedToExecute(#[from] bitris_commands::pc_possible::PcPossibleExecutorBulkCreationError),
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let result = match command.as_str() {
+        "-h" | "--help" => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        "percent" => Options::parse(rest).and_then(|options| percent::run(&options)),
+        _ => Err(CommandError::UnknownCommand(command.clone())),
+    };
+
+    match result {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
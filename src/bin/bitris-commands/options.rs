@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use bitris::prelude::*;
+use bitris_commands::ClippedBoard;
+use bitris_commands::fumen;
+
+use crate::CommandError;
+
+/// Holds the options passed to a command as `--name value` or `--name=value`.
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub(crate) struct Options {
+    values: BTreeMap<String, String>,
+    help: bool,
+}
+
+impl Options {
+    pub(crate) fn parse(args: &[String]) -> Result<Self, CommandError> {
+        let mut options = Options::default();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "-h" || arg == "--help" {
+                options.help = true;
+                continue;
+            }
+
+            let Some(name) = arg.strip_prefix("--") else {
+                return Err(CommandError::UnknownOption(arg.clone()));
+            };
+
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = iter.next().ok_or_else(|| CommandError::MissingValue(name.to_string()))?;
+                    (name.to_string(), value.clone())
+                }
+            };
+            options.values.insert(name, value);
+        }
+
+        Ok(options)
+    }
+
+    #[inline]
+    pub(crate) fn is_help(&self) -> bool {
+        self.help
+    }
+
+    /// Returns `Err()` if an option not contained in the names is passed.
+    pub(crate) fn check_names(&self, names: &[&str]) -> Result<(), CommandError> {
+        match self.values.keys().find(|name| !names.contains(&name.as_str())) {
+            Some(name) => Err(CommandError::UnknownOption(format!("--{}", name))),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|value| value.as_str())
+    }
+
+    pub(crate) fn parse_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, CommandError> {
+        match self.get(name) {
+            Some(value) => value.parse().map_err(|_| invalid_value(name, value)),
+            None => Ok(default),
+        }
+    }
+
+    /// Accepts `yes`, `no`, `true` and `false`.
+    pub(crate) fn flag_or(&self, name: &str, default: bool) -> Result<bool, CommandError> {
+        match self.get(name) {
+            Some("yes" | "true") => Ok(true),
+            Some("no" | "false") => Ok(false),
+            Some(value) => Err(invalid_value(name, value)),
+            None => Ok(default),
+        }
+    }
+
+    /// Returns the board clipped by the height.
+    /// The board is fumen if it contains `v115@`, otherwise ASCII rows separated by `/` or newlines (like `XX......XX/XXX....XXX`).
+    /// The first page is used for fumen.
+    pub(crate) fn clipped_board(&self, name: &str, height: u32) -> Result<ClippedBoard, CommandError> {
+        let board = match self.get(name) {
+            Some(value) if value.contains("v115@") => {
+                let pages = fumen::decode(value)?;
+                pages.first().ok_or_else(|| CommandError::InvalidBoard(value.to_string()))?.board()
+            }
+            Some(value) => {
+                Board64::from_str(value.replace('/', "\n").as_str())
+                    .map_err(|_| CommandError::InvalidBoard(value.to_string()))?
+            }
+            None => Board64::blank(),
+        };
+        Ok(ClippedBoard::try_new(board, height)?)
+    }
+}
+
+#[inline]
+pub(crate) fn invalid_value(name: &str, value: &str) -> CommandError {
+    CommandError::InvalidValue(name.to_string(), value.to_string())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::options::Options;
+
+    #[test]
+    fn parse() {
+        let args = ["--hold", "no", "--height=3", "-h"].map(String::from);
+        let options = Options::parse(&args).unwrap();
+        assert!(options.is_help());
+        assert!(!options.flag_or("hold", true).unwrap());
+        assert_eq!(options.parse_or("height", 4u32).unwrap(), 3);
+        assert_eq!(options.parse_or("threads", 1usize).unwrap(), 1);
+        assert!(options.check_names(&["hold", "height"]).is_ok());
+        assert!(options.check_names(&["hold"]).is_err());
+
+        assert!(Options::parse(&["--board".to_string()]).is_err());
+        assert!(Options::parse(&["board".to_string()]).is_err());
+    }
+
+    #[test]
+    fn clipped_board() {
+        let args = ["--board", "XX......XX/XXX....XXX"].map(String::from);
+        let clipped_board = Options::parse(&args).unwrap().clipped_board("board", 2).unwrap();
+        assert_eq!(clipped_board.spaces(), 10);
+
+        let args = ["--board", "https://fumen.zui.jp/?v115@bhI8KeAgH"].map(String::from);
+        let clipped_board = Options::parse(&args).unwrap().clipped_board("board", 1).unwrap();
+        assert_eq!(clipped_board.spaces(), 1);
+
+        // The fumen has no pages.
+        let args = ["--board", "v115@"].map(String::from);
+        assert!(Options::parse(&args).unwrap().clipped_board("board", 1).is_err());
+    }
+}
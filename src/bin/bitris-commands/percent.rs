@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::thread;
+
+use bitris::prelude::*;
+use bitris_commands::{ClippedBoard, Pattern, PatternSet, ShapeSequence};
+use bitris_commands::pc_possible::{MAX_ENUMERABLE_SEQUENCES, PcPossibleBulkExecutor, PcPossibleBulkExecutorBinder, PcPossibleExecutorBulkCreationError, PcResults};
+use serde_json::json;
+
+use crate::CommandError;
+use crate::options::{invalid_value, Options};
+
+const USAGE: &str = "\
+Usage: bitris-commands percent [OPTIONS]
+
+Calculates the success rate of perfect clears for the sequences in the pattern.
+
+Options:
+  --board <BOARD>       ASCII rows separated by '/' (like `XX......XX/XXX....XXX`) or fumen [default: blank]
+  --height <HEIGHT>     The height of the perfect clear [default: 4]
+  --pattern <PATTERN>   The sequences (like `T,*p4`) [default: *p7]
+  --patterns-file <PATH>
+                        The file with one pattern per line and `#` comments, instead of --pattern
+  --hold <yes|no>       Allows hold [default: yes]
+  --drop <DROP>         softdrop, harddrop, or tspin (every T must clear lines with a T-spin) [default: softdrop]
+  --threads <N>         The number of threads [default: 1]
+  --tree-depth <N>      The depth of the prefixes in the tree [default: 2]
+  --format <FORMAT>     human or json [default: human]";
+
+const NAMES: [&str; 9] = ["board", "height", "pattern", "patterns-file", "hold", "drop", "threads", "tree-depth", "format"];
+
+/// The settings of `percent`.
+#[derive(Clone, Debug)]
+struct Settings {
+    clipped_board: ClippedBoard,
+    allow_move: AllowMove,
+    allows_hold: bool,
+    requires_t_spin: bool,
+}
+
+pub(crate) fn run(options: &Options) -> Result<String, CommandError> {
+    if options.is_help() {
+        return Ok(USAGE.to_string());
+    }
+
+    options.check_names(&NAMES)?;
+
+    let height = options.parse_or("height", 4u32)?;
+    let clipped_board = options.clipped_board("board", height)?;
+    let pattern_set = match options.get("patterns-file") {
+        Some(_) if options.get("pattern").is_some() => {
+            return Err(CommandError::ConflictingOptions("pattern".to_string(), "patterns-file".to_string()));
+        }
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|error| CommandError::FailedToRead(path.to_string(), error))?;
+            PatternSet::from_str(&text)?
+        }
+        None => PatternSet::from(Pattern::from_str(options.get("pattern").unwrap_or("*p7"))?),
+    };
+    let allows_hold = options.flag_or("hold", true)?;
+    let (allow_move, requires_t_spin) = match options.get("drop").unwrap_or("softdrop") {
+        "softdrop" => (AllowMove::Softdrop, false),
+        "harddrop" => (AllowMove::Harddrop, false),
+        "tspin" => (AllowMove::Softdrop, true),
+        value => return Err(invalid_value("drop", value)),
+    };
+
+    let threads = options.parse_or("threads", 1usize)?;
+    if threads == 0 {
+        return Err(invalid_value("threads", "0"));
+    }
+
+    let tree_depth = options.parse_or("tree-depth", 2usize)?;
+    let format = options.get("format").unwrap_or("human");
+    if format != "human" && format != "json" {
+        return Err(invalid_value("format", format));
+    }
+
+    let settings = Settings { clipped_board, allow_move, allows_hold, requires_t_spin };
+    let results = execute(&settings, &pattern_set, threads)?;
+
+    let report = Report::new(&results, tree_depth);
+    Ok(match format {
+        "json" => report.to_json(),
+        _ => report.to_human(),
+    })
+}
+
+fn execute(settings: &Settings, pattern_set: &PatternSet, threads: usize) -> Result<PcResults, CommandError> {
+    let execute_one = |pattern_set: PatternSet| -> Result<PcResults, PcPossibleExecutorBulkCreationError> {
+        // The binder is made in each thread because it holds `Rc`.
+        let mut binder = PcPossibleBulkExecutorBinder::srs();
+        binder.allow_move = settings.allow_move;
+        binder.clipped_board = settings.clipped_board;
+        binder.pattern = Rc::from(pattern_set);
+        binder.allows_hold = settings.allows_hold;
+        binder.requires_t_spin = settings.requires_t_spin;
+        binder.try_execute()
+    };
+
+    if threads == 1 {
+        return Ok(execute_one(pattern_set.clone())?);
+    }
+
+    // Checks the total before enumerating the sequences.
+    if pattern_set.try_len_shapes_vec().map_or(true, |size| MAX_ENUMERABLE_SEQUENCES < size) {
+        return Err(PcPossibleExecutorBulkCreationError::TooManySequences.into());
+    }
+
+    // Consecutive sequences share their heads, so each thread takes a contiguous chunk
+    // to reuse the sequences inferred with hold within it.
+    let sequences = pattern_set.to_sequences();
+    let chunk_size = sequences.len().div_ceil(threads).max(1);
+
+    let merged = thread::scope(|scope| {
+        let handles: Vec<_> = sequences.chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let move_rules = MoveRules::srs(settings.allow_move);
+                    let executor = PcPossibleBulkExecutor::try_new_with_sequences(
+                        &move_rules,
+                        settings.clipped_board,
+                        chunk,
+                        settings.allows_hold,
+                    )?.requires_t_spin(settings.requires_t_spin);
+                    Ok::<_, PcPossibleExecutorBulkCreationError>(executor.execute())
+                })
+            })
+            .collect();
+
+        handles.into_iter()
+            .map(|handle| handle.join().expect("The thread panicked"))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    // Keep the order of the patterns.
+    let mut results = PcResults::new(&sequences);
+    for (sequence, succeed) in merged.into_iter().flat_map(|chunk_results| chunk_results.into_iter()) {
+        if let Some(succeed) = succeed {
+            results.accept_if_present(&sequence, succeed);
+        }
+    }
+    Ok(results)
+}
+
+fn to_percent(succeed: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    succeed as f64 / total as f64 * 100.0
+}
+
+/// Aggregates the results for output.
+#[derive(Clone, PartialEq, Debug)]
+struct Report {
+    succeed: u64,
+    total: u64,
+
+    // Each prefix and the count of succeed and total sequences starting with it. Sorted by the prefixes.
+    tree: Vec<(ShapeSequence, u64, u64)>,
+
+    // Sorted.
+    failed: Vec<ShapeSequence>,
+}
+
+impl Report {
+    fn new(results: &PcResults, tree_depth: usize) -> Self {
+        let mut tree = BTreeMap::<ShapeSequence, (u64, u64)>::new();
+        let mut failed = Vec::<ShapeSequence>::new();
+
+        for (sequence, succeed) in results.iter() {
+            let succeed = succeed.unwrap_or(false);
+            let shapes = sequence.shapes();
+            for depth in 1..=tree_depth.min(shapes.len()) {
+                let counts = tree.entry(ShapeSequence::new(shapes[..depth].to_vec())).or_default();
+                counts.0 += succeed as u64;
+                counts.1 += 1;
+            }
+            if !succeed {
+                failed.push(sequence);
+            }
+        }
+        failed.sort();
+
+        Self {
+            succeed: results.count_succeed(),
+            total: results.count_keys() as u64,
+            tree: tree.into_iter().map(|(prefix, (succeed, total))| (prefix, succeed, total)).collect(),
+            failed,
+        }
+    }
+
+    fn to_human(&self) -> String {
+        let mut lines = Vec::<String>::new();
+
+        lines.push(format!(
+            "Success rate: {:.2}% ({}/{})", to_percent(self.succeed, self.total), self.succeed, self.total,
+        ));
+
+        if !self.tree.is_empty() {
+            lines.push(String::new());
+            lines.push("Tree:".to_string());
+            for (prefix, succeed, total) in &self.tree {
+                lines.push(format!(
+                    "{}{} -> {:.2}% ({}/{})",
+                    "  ".repeat(prefix.shapes().len() - 1), prefix, to_percent(*succeed, *total), succeed, total,
+                ));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(format!("Failed sequences ({}):", self.failed.len()));
+        for sequence in &self.failed {
+            lines.push(format!("  {}", sequence));
+        }
+
+        lines.join("\n")
+    }
+
+    fn to_json(&self) -> String {
+        let tree: Vec<_> = self.tree.iter()
+            .map(|(prefix, succeed, total)| json!({
+                "prefix": prefix.to_string(),
+                "succeed": succeed,
+                "total": total,
+                "rate": to_percent(*succeed, *total),
+            }))
+            .collect();
+
+        let failed: Vec<_> = self.failed.iter()
+            .map(|sequence| sequence.to_string())
+            .collect();
+
+        json!({
+            "succeed": self.succeed,
+            "total": self.total,
+            "rate": to_percent(self.succeed, self.total),
+            "tree": tree,
+            "failed": failed,
+        }).to_string()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+    use bitris_commands::{ClippedBoard, Pattern, PatternSet};
+
+    use crate::CommandError;
+    use crate::options::Options;
+    use crate::percent::{execute, Report, run, Settings};
+
+    #[test]
+    fn threads() {
+        let board = Board64::from_str("
+            XX......XX
+            XX......XX
+        ").unwrap();
+        let settings = Settings {
+            clipped_board: ClippedBoard::try_new(board, 2).unwrap(),
+            allow_move: AllowMove::Softdrop,
+            allows_hold: true,
+            requires_t_spin: false,
+        };
+        let pattern_set = PatternSet::from(Pattern::from_str("*p3").unwrap());
+
+        let single = Report::new(&execute(&settings, &pattern_set, 1).unwrap(), 2);
+        let multiple = Report::new(&execute(&settings, &pattern_set, 3).unwrap(), 2);
+        assert_eq!(single, multiple);
+        assert_eq!(single.total, 210);
+        assert_eq!(single.succeed + single.failed.len() as u64, 210);
+        assert!(0 < single.succeed);
+
+        // The overlapping sequences are counted once.
+        let pattern_set = PatternSet::from_str("*p3\nT,[^T]p2").unwrap();
+        let multiple = Report::new(&execute(&settings, &pattern_set, 3).unwrap(), 2);
+        assert_eq!(single, multiple);
+    }
+
+    #[test]
+    fn conflicting_patterns() {
+        let args: Vec<String> = ["--pattern", "*p7", "--patterns-file", "patterns.txt"].iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let options = Options::parse(&args).unwrap();
+        assert!(matches!(run(&options), Err(CommandError::ConflictingOptions(..))));
+    }
+}
@@ -1,8 +1,15 @@
+use std::fmt;
+use std::str::FromStr;
+
 use bitris::Shape;
 use derive_more::Constructor;
 
 use crate::internal_macros::forward_impl_try_from;
-use crate::ShapeSequence;
+use crate::{ShapeSequence, ShapesParseError};
+use crate::internals::{parse_shapes, write_shapes};
+
+/// The maximum number of shapes `BitShapes` can hold.
+const MAX_LEN: usize = 22;
 
 /// Preserves the order of shapes.
 /// Internally, it's represented in bits, making copying and comparing lightweight.
@@ -38,6 +45,124 @@ impl BitShapes {
         self.len as usize
     }
 
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the shape at the index. If the index is out of range, returns `None`.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let shapes = BitShapes::try_from(vec![Shape::T, Shape::I, Shape::O]).unwrap();
+    /// assert_eq!(shapes.get(1), Some(Shape::I));
+    /// assert_eq!(shapes.get(3), None);
+    /// ```
+    #[inline]
+    pub fn get(self, index: usize) -> Option<Shape> {
+        if self.len() <= index {
+            return None;
+        }
+        let shape_value = self.value / 7u64.pow(index as u32) % 7;
+        Some(Shape::try_from(shape_value as usize).ok().unwrap())
+    }
+
+    /// Appends the shape to the back.
+    /// Returns `Err()` if it's already full, and then it's not changed.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let mut shapes = BitShapes::empty();
+    /// shapes.push(Shape::T).unwrap();
+    /// shapes.push(Shape::I).unwrap();
+    /// assert_eq!(shapes.to_vec(), vec![Shape::T, Shape::I]);
+    ///
+    /// let mut shapes = BitShapes::try_from(vec![Shape::T].repeat(22)).unwrap();
+    /// assert_eq!(shapes.push(Shape::I), Err(BitShapesCreationError::TooManyShapes(23)));
+    /// ```
+    #[inline]
+    pub fn push(&mut self, shape: Shape) -> Result<(), BitShapesCreationError> {
+        if MAX_LEN <= self.len() {
+            return Err(BitShapesCreationError::TooManyShapes(self.len() + 1));
+        }
+        self.value += shape as u64 * 7u64.pow(self.len as u32);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the first shape and returns it. If it's empty, returns `None`.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let mut shapes = BitShapes::try_from(vec![Shape::T, Shape::I]).unwrap();
+    /// assert_eq!(shapes.pop_front(), Some(Shape::T));
+    /// assert_eq!(shapes.pop_front(), Some(Shape::I));
+    /// assert_eq!(shapes.pop_front(), None);
+    /// ```
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<Shape> {
+        let first = self.get(0)?;
+        self.value /= 7;
+        self.len -= 1;
+        Some(first)
+    }
+
+    /// Returns the shapes followed by the other shapes.
+    /// Returns `Err()` if the total exceeds the limit.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let head = BitShapes::try_from(vec![Shape::T, Shape::I]).unwrap();
+    /// let tail = BitShapes::try_from(vec![Shape::O]).unwrap();
+    /// assert_eq!(head.concat(tail).unwrap().to_vec(), vec![Shape::T, Shape::I, Shape::O]);
+    /// ```
+    #[inline]
+    pub fn concat(self, other: BitShapes) -> Result<BitShapes, BitShapesCreationError> {
+        let len = self.len() + other.len();
+        if MAX_LEN < len {
+            return Err(BitShapesCreationError::TooManyShapes(len));
+        }
+        Ok(BitShapes::new(self.value + other.value * 7u64.pow(self.len as u32), len as u8))
+    }
+
+    /// Returns the first `n` shapes. If `n` exceeds the length, returns all shapes.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let shapes = BitShapes::try_from(vec![Shape::T, Shape::I, Shape::O]).unwrap();
+    /// assert_eq!(shapes.prefix(2).to_vec(), vec![Shape::T, Shape::I]);
+    /// ```
+    #[inline]
+    pub fn prefix(self, n: usize) -> BitShapes {
+        let n = n.min(self.len());
+        BitShapes::new(self.value % 7u64.pow(n as u32), n as u8)
+    }
+
+    /// Returns the last `n` shapes. If `n` exceeds the length, returns all shapes.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let shapes = BitShapes::try_from(vec![Shape::T, Shape::I, Shape::O]).unwrap();
+    /// assert_eq!(shapes.suffix(2).to_vec(), vec![Shape::I, Shape::O]);
+    /// ```
+    #[inline]
+    pub fn suffix(self, n: usize) -> BitShapes {
+        let n = n.min(self.len());
+        BitShapes::new(self.value / 7u64.pow((self.len() - n) as u32), n as u8)
+    }
+
+    /// Returns an iterator over the shapes from the front.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let shapes = BitShapes::try_from(vec![Shape::T, Shape::I, Shape::O]).unwrap();
+    /// assert_eq!(shapes.iter().filter(|&shape| shape != Shape::I).count(), 2);
+    /// ```
+    #[inline]
+    pub fn iter(self) -> BitShapesIter {
+        BitShapesIter { shapes: self }
+    }
+
     /// ```
     /// use bitris_commands::prelude::*;
     ///
@@ -46,15 +171,72 @@ impl BitShapes {
     ///
     /// ```
     pub fn to_vec(self) -> Vec<Shape> {
-        let mut value = self.value;
-        let len = self.len();
-        let mut vec = Vec::with_capacity(len);
-        for _ in 0..len {
-            let shape_value = value % 7;
-            value /= 7;
-            vec.push(Shape::try_from(shape_value as usize).ok().unwrap());
-        }
-        vec
+        self.iter().collect()
+    }
+}
+
+/// An iterator over the shapes of `BitShapes`. It does not allocate.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct BitShapesIter {
+    shapes: BitShapes,
+}
+
+impl Iterator for BitShapesIter {
+    type Item = Shape;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.shapes.pop_front()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.shapes.len(), Some(self.shapes.len()))
+    }
+}
+
+impl ExactSizeIterator for BitShapesIter {}
+
+impl IntoIterator for BitShapes {
+    type Item = Shape;
+    type IntoIter = BitShapesIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Display for BitShapes {
+    /// Formats as the letters of the shapes (like `TIO`).
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let shapes = BitShapes::try_from(vec![Shape::T, Shape::I, Shape::O]).unwrap();
+    /// assert_eq!(shapes.to_string(), "TIO");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_shapes(f, self.iter())
+    }
+}
+
+impl FromStr for BitShapes {
+    type Err = ShapesParseError;
+
+    /// Parses the letters of the shapes (like `TIO`).
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let shapes = BitShapes::from_str("TIO").unwrap();
+    /// assert_eq!(shapes.to_vec(), vec![Shape::T, Shape::I, Shape::O]);
+    ///
+    /// assert_eq!(BitShapes::from_str(&"T".repeat(23)), Err(ShapesParseError::TooManyShapes(23)));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let shapes = parse_shapes(s)?;
+        BitShapes::try_from(shapes.as_slice())
+            .map_err(|_| ShapesParseError::TooManyShapes(shapes.len()))
     }
 }
 
@@ -68,7 +250,7 @@ impl TryFrom<&[Shape]> for BitShapes {
     type Error = BitShapesCreationError;
 
     fn try_from(shapes: &[Shape]) -> Result<Self, Self::Error> {
-        if 23 <= shapes.len() {
+        if MAX_LEN < shapes.len() {
             return Err(BitShapesCreationError::TooManyShapes(shapes.len()));
         }
 
@@ -132,4 +314,35 @@ mod tests {
         assert_eq!(shapes.len(), 22);
         assert_eq!(shapes.to_vec(), vec![Shape::T, Shape::I].repeat(11));
     }
+
+    #[test]
+    fn operations() {
+        use Shape::*;
+
+        let shapes = BitShapes::try_from(vec![Z, S, J, L, O, I, T].repeat(3)).unwrap();
+        let vec = shapes.to_vec();
+        for index in 0..21 {
+            assert_eq!(shapes.get(index), Some(vec[index]));
+        }
+        for n in 0..=21 {
+            assert_eq!(shapes.prefix(n).to_vec(), vec[..n]);
+            assert_eq!(shapes.suffix(n).to_vec(), vec[21 - n..]);
+            assert_eq!(shapes.prefix(n).concat(shapes.suffix(21 - n)), Ok(shapes));
+        }
+
+        let mut pushed = BitShapes::empty();
+        for &shape in &vec {
+            pushed.push(shape).unwrap();
+        }
+        assert_eq!(pushed, shapes);
+        assert!(pushed.push(T).is_ok());
+        assert!(pushed.push(T).is_err());
+        assert_eq!(pushed.len(), 22);
+        assert!(shapes.concat(BitShapes::try_from(vec![T, T]).unwrap()).is_err());
+
+        assert_eq!(shapes.iter().len(), 21);
+        assert_eq!(shapes.into_iter().collect_vec(), vec);
+        assert_eq!(shapes.to_string(), "ZSJLOIT".repeat(3));
+    }
 }
+
@@ -2,7 +2,7 @@ use bitris::{Board64, BoardOp};
 use thiserror::Error;
 
 /// Holds a board and height.
-#[derive(Copy, Clone, PartialEq, PartialOrd, Hash, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Hash, Debug)]
 pub struct ClippedBoard {
     board: Board64,
     height: u32,
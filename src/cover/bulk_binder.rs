@@ -0,0 +1,105 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+
+use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+use crate::cover::{CoverBulkExecutor, CoverExecutorBulkCreationError, CoverResults};
+
+/// The binder to hold and tie settings for `CoverBulkExecutor`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct CoverBulkExecutorBinder<T: RotationSystem> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub clipped_board: ClippedBoard,
+    pub targets: Rc<Vec<Board64>>,
+    pub pattern: Rc<Pattern>,
+    pub allows_hold: bool,
+}
+
+impl CoverBulkExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `CoverBulkExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        CoverBulkExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem> CoverBulkExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + [required] targets: empty. You must set this.
+    ///   + allow move: softdrop
+    ///   + board: blank
+    ///   + height: 4 lines
+    ///   + pattern: factorial of all shapes (like `*p7`)
+    ///   + allows hold: yes
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
+            targets: Rc::from(Vec::new()),
+            pattern: Rc::from(Pattern::try_from(vec![
+                PatternElement::Factorial(ShapeCounter::one_of_each()),
+            ]).unwrap()),
+            allows_hold: true,
+        }
+    }
+
+    // See `CoverBulkExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<CoverResults, CoverExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute())
+    }
+
+    fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<CoverBulkExecutor<'a, T>, CoverExecutorBulkCreationError> {
+        CoverBulkExecutor::try_new(
+            move_rules,
+            self.clipped_board,
+            self.targets.as_slice(),
+            self.pattern.as_ref(),
+            self.allows_hold,
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{Pattern, PatternElement, ShapeCounter};
+    use crate::cover::{CoverBulkExecutorBinder, CoverExecutorBulkCreationError};
+
+    #[test]
+    fn reuse() {
+        use PatternElement::*;
+
+        let mut binder = CoverBulkExecutorBinder::srs();
+        assert_eq!(binder.try_execute().unwrap_err(), CoverExecutorBulkCreationError::NoTargets);
+
+        binder.targets = Rc::from(vec![
+            Board64::from_str("
+                XX........
+                XX........
+            ").unwrap(),
+        ]);
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            Permutation(ShapeCounter::one_of_each(), 2),
+        ]).unwrap());
+
+        let results = binder.try_execute().unwrap();
+        assert_eq!(results.count_covered(0), 12); // The sequences containing O
+        assert_eq!(results.combined_rate(), 12.0 / 42.0);
+
+        binder.allows_hold = false;
+        let results = binder.try_execute().unwrap();
+        assert_eq!(results.count_covered(0), 6); // The sequences starting with O
+    }
+}
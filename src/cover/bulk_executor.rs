@@ -0,0 +1,316 @@
+use bitris::prelude::*;
+use fxhash::{FxHashMap, FxHashSet};
+use thiserror::Error;
+
+use crate::{ClippedBoard, OrderCursor, Pattern, PopOp, ShapeSequence};
+use crate::cover::CoverResults;
+
+/// Dataset for detecting the same state during cover search.
+/// Each placement only fills the cells of the target, so the board and the head of shapes determine the state.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct SearchingState {
+    board: Board64,
+    first: Option<Shape>,
+}
+
+
+/// A collection of errors that occur when making the executor.
+/// The index of the target that caused the error is attached to the target errors.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum CoverExecutorBulkCreationError {
+    #[error("No targets are specified.")]
+    NoTargets,
+    #[error("The target {0} lacks some blocks on the board.")]
+    TargetMissesBoardBlocks(usize),
+    #[error("Unexpected the count of cells to fill in the target {0}.")]
+    UnexpectedTargetSpaces(usize),
+    #[error("The target {0} exceeds the height.")]
+    TargetExceedsHeight(usize),
+    #[error("The pattern is too short to build the target {0}.")]
+    ShortPatternDimension(usize),
+    #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
+    BoardIsTooHigh,
+}
+
+/// The executor to check whether each shape sequence can build target boards.
+///
+/// A target is a final board including the blocks on the current board.
+/// Each piece must be placed only on the cells to be filled, and line clears are ignored.
+/// In other words, the target is built exactly, without extra blocks.
+///
+/// The targets use the same coordinates as the clipped board (after its filled rows are cleared).
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct CoverBulkExecutor<'a, T: RotationSystem> {
+    move_rules: &'a MoveRules<'a, T>,
+    clipped_board: ClippedBoard,
+    targets: &'a [Board64],
+    pattern: &'a Pattern,
+    allows_hold: bool,
+    spawn_position: BlPosition,
+}
+
+impl<'a, T: RotationSystem> CoverBulkExecutor<'a, T> {
+    /// Make CoverBulkExecutor.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `CoverExecutorBulkCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Shape, Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    /// use bitris_commands::cover::CoverBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).expect("Failed to clip");
+    ///
+    /// let targets = vec![
+    ///     // O on the left
+    ///     Board64::from_str("
+    ///         XX........
+    ///         XX........
+    ///     ").expect("Failed to create a target"),
+    ///     // I on the right
+    ///     Board64::from_str("
+    ///         ......XXXX
+    ///     ").expect("Failed to create a target"),
+    /// ];
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Permutation(ShapeCounter::one_of_each(), 2),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = CoverBulkExecutor::try_new(&move_rules, clipped_board, &targets, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert_eq!(results.count_keys(), 42);
+    /// assert_eq!(results.count_covered(0), 12); // The sequences containing O
+    /// assert_eq!(results.count_covered(1), 12); // The sequences containing I
+    /// assert_eq!(results.count_covered_any(), 22);
+    /// assert_eq!(results.uncovered_sequences().len(), 20);
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        targets: &'a [Board64],
+        pattern: &'a Pattern,
+        allows_hold: bool,
+    ) -> Result<Self, CoverExecutorBulkCreationError> {
+        use CoverExecutorBulkCreationError::*;
+
+        if 56 < clipped_board.height() {
+            return Err(BoardIsTooHigh);
+        }
+
+        if targets.is_empty() {
+            return Err(NoTargets);
+        }
+
+        let board = clipped_board.board_ref();
+        for (index, target) in targets.iter().enumerate() {
+            let misses = (0..10).any(|x| board.cols[x] & !target.cols[x] != 0);
+            if misses {
+                return Err(TargetMissesBoardBlocks(index));
+            }
+
+            let spaces = target.count_blocks() - board.count_blocks();
+            if spaces % 4 != 0 {
+                return Err(UnexpectedTargetSpaces(index));
+            }
+
+            if clipped_board.height() < target.well_top() {
+                return Err(TargetExceedsHeight(index));
+            }
+
+            if (pattern.min_dim_shapes() as u32) < spaces / 4 {
+                return Err(ShortPatternDimension(index));
+            }
+        }
+
+        // Spawn above the height so that it is not stuck when harddrop only.
+        let spawn_position = bl(5, clipped_board.height() as i32 + 4);
+
+        Ok(Self { move_rules, clipped_board, targets, pattern, allows_hold, spawn_position })
+    }
+
+    /// Start the search for cover in bulk.
+    /// Each shape sequence is checked against all targets.
+    pub fn execute(&self) -> CoverResults {
+        let mut results = Vec::<(ShapeSequence, Vec<bool>)>::new();
+        let mut searched = FxHashSet::<ShapeSequence>::default();
+
+        // Each target has its own visited states because the states depend on the target.
+        let mut visited_states = FxHashMap::<SearchingState, bool>::default();
+
+        for sequence in self.pattern.to_sequences() {
+            if !searched.insert(sequence.clone()) {
+                continue;
+            }
+
+            let order = sequence.to_shape_order();
+            let covered = self.targets.iter()
+                .map(|target| {
+                    visited_states.clear();
+                    let remaining = target.count_blocks() - self.clipped_board.board_ref().count_blocks();
+                    self.pop_shape(order.new_cursor(), self.clipped_board.board(), target, remaining, &mut visited_states)
+                })
+                .collect();
+
+            results.push((sequence, covered));
+        }
+
+        CoverResults::new(self.targets.len(), results)
+    }
+
+    fn pop_shape(
+        &self,
+        cursor: OrderCursor,
+        board: Board64,
+        target: &Board64,
+        remaining: u32,
+        visited_states: &mut FxHashMap<SearchingState, bool>,
+    ) -> bool {
+        if remaining == 0 {
+            return true;
+        }
+
+        if (cursor.len_unused() as u32) * 4 < remaining {
+            return false;
+        }
+
+        let state = SearchingState { board, first: cursor.first() };
+        if let Some(&found) = visited_states.get(&state) {
+            return found;
+        }
+
+        let mut found = false;
+
+        let (popped, next_cursor) = cursor.pop(PopOp::First);
+        if let Some(shape) = popped {
+            found = self.increment(shape, board, next_cursor, target, remaining, visited_states);
+        }
+
+        if !found && self.allows_hold {
+            let (popped, next_cursor) = cursor.pop(PopOp::Second);
+            if let Some(shape) = popped {
+                found = self.increment(shape, board, next_cursor, target, remaining, visited_states);
+            }
+        }
+
+        visited_states.insert(state, found);
+        found
+    }
+
+    fn increment(
+        &self,
+        shape: Shape,
+        board: Board64,
+        next_cursor: OrderCursor,
+        target: &Board64,
+        remaining: u32,
+        visited_states: &mut FxHashMap<SearchingState, bool>,
+    ) -> bool {
+        let placement = shape.with(Orientation::North).with(self.spawn_position);
+        let moves = self.move_rules.generate_minimized_moves(board, placement);
+
+        moves.into_iter().any(|placement| {
+            // Only the cells of the target can be filled.
+            if placement.locations().into_iter().any(|location| target.is_free_at(location)) {
+                return false;
+            }
+
+            let mut next_board = board;
+            for location in placement.locations() {
+                next_board.set_at(location);
+            }
+
+            self.pop_shape(next_cursor, next_board, target, remaining - 4, visited_states)
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter, ShapeSequence};
+    use crate::cover::{CoverBulkExecutor, CoverExecutorBulkCreationError};
+
+    #[test]
+    fn opener() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        let targets = vec![
+            // O and I side by side
+            Board64::from_str("
+                XX........
+                XXXXXX....
+            ").unwrap(),
+        ];
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let pattern = Pattern::try_from(vec![
+            Permutation(ShapeCounter::one_of_each(), 3),
+        ]).unwrap();
+        let executor = CoverBulkExecutor::try_new(&move_rules, clipped_board, &targets, &pattern, true).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_keys(), 210);
+
+        // O and I both must be used, and the first two are available with hold.
+        assert_eq!(results.get(&ShapeSequence::new(vec![O, I, T])), Some([true].as_slice()));
+        assert_eq!(results.get(&ShapeSequence::new(vec![O, T, I])), Some([true].as_slice()));
+        assert_eq!(results.get(&ShapeSequence::new(vec![T, S, I])), Some([false].as_slice()));
+        assert_eq!(results.count_covered(0), 30);
+
+        let executor = CoverBulkExecutor::try_new(&move_rules, clipped_board, &targets, &pattern, false).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.get(&ShapeSequence::new(vec![O, T, I])), Some([false].as_slice()));
+        assert_eq!(results.count_covered(0), 10);
+    }
+
+    #[test]
+    fn errors() {
+        use CoverExecutorBulkCreationError::*;
+
+        let board = Board64::from_str("
+            XX........
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![PatternElement::Wildcard]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let error = |targets: &[&str]| {
+            let targets: Vec<Board64> = targets.iter().map(|it| Board64::from_str(it).unwrap()).collect();
+            CoverBulkExecutor::try_new(&move_rules, clipped_board, &targets, &pattern, true).unwrap_err()
+        };
+
+        assert_eq!(error(&[]), NoTargets);
+        assert_eq!(error(&["
+            XX........
+            XXXX......
+        ", "
+            ..XXXX....
+        "]), TargetMissesBoardBlocks(1));
+        assert_eq!(error(&["
+            XXXXX.....
+        "]), UnexpectedTargetSpaces(0));
+        assert_eq!(error(&["
+            X.........
+            X.........
+            X.........
+            X.........
+            XX........
+        "]), TargetExceedsHeight(0));
+        assert_eq!(error(&["
+            XX........
+            XX........
+            XXXXXX....
+        "]), ShortPatternDimension(0));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::ShapeSequence;
+use crate::internals::SequenceKeys;
+
+/// Holds the results of cover.
+///
+/// For each shape sequence, it holds whether each target can be built.
+/// The shape sequences are kept in the order in which they were searched,
+/// and the flags of a shape sequence are stored contiguously in the order of the targets.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct CoverResults {
+    len_targets: usize,
+    keys: SequenceKeys,
+    covered: Vec<bool>,
+}
+
+impl CoverResults {
+    #[inline]
+    pub(crate) fn new(len_targets: usize, sequences: Vec<(ShapeSequence, Vec<bool>)>) -> Self {
+        debug_assert!(sequences.iter().all(|(_, covered)| covered.len() == len_targets));
+        let keys = SequenceKeys::new(sequences.iter().map(|(sequence, _)| sequence));
+        let mut covered = vec![false; keys.len() * len_targets];
+        for (sequence, flags) in &sequences {
+            let start = keys.position(sequence).unwrap() * len_targets;
+            covered[start..start + len_targets].copy_from_slice(flags);
+        }
+        Self { len_targets, keys, covered }
+    }
+
+    #[inline]
+    fn covered_at(&self, index: usize) -> &[bool] {
+        &self.covered[index * self.len_targets..(index + 1) * self.len_targets]
+    }
+
+    #[inline]
+    fn iter_covered(&self) -> impl Iterator<Item=&[bool]> + '_ {
+        (0..self.keys.len()).map(|index| self.covered_at(index))
+    }
+
+    /// Returns the count of targets.
+    #[inline]
+    pub fn len_targets(&self) -> usize {
+        self.len_targets
+    }
+
+    /// Returns whether each target can be built from the shape sequence, in the order of the targets.
+    /// If the shape sequence was not searched, returns `None`.
+    #[inline]
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<&[bool]> {
+        self.keys.position(sequence).map(|index| self.covered_at(index))
+    }
+
+    /// Returns the pair of shape sequence and result in the searched order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(ShapeSequence, &[bool])> + '_ {
+        (0..self.keys.len()).map(|index| (self.keys.sequence_at(index), self.covered_at(index)))
+    }
+
+    /// Returns the count of shape sequences that can build the target.
+    /// Panics if the index is out of the targets.
+    #[inline]
+    pub fn count_covered(&self, target_index: usize) -> u64 {
+        assert!(target_index < self.len_targets, "The target index is out of range.");
+        self.iter_covered()
+            .filter(|covered| covered[target_index])
+            .count() as u64
+    }
+
+    /// Returns the count of shape sequences that can build at least one of the targets.
+    #[inline]
+    pub fn count_covered_any(&self) -> u64 {
+        self.iter_covered()
+            .filter(|covered| covered.iter().any(|&it| it))
+            .count() as u64
+    }
+
+    /// Returns the rate of shape sequences that can build the target.
+    /// If there are no shape sequences, returns `0.0`.
+    /// Panics if the index is out of the targets.
+    #[inline]
+    pub fn rate(&self, target_index: usize) -> f64 {
+        self.to_rate(self.count_covered(target_index))
+    }
+
+    /// Returns the rate of shape sequences that can build at least one of the targets.
+    /// If there are no shape sequences, returns `0.0`.
+    #[inline]
+    pub fn combined_rate(&self) -> f64 {
+        self.to_rate(self.count_covered_any())
+    }
+
+    #[inline]
+    fn to_rate(&self, count: u64) -> f64 {
+        if self.keys.is_empty() {
+            return 0.0;
+        }
+        count as f64 / self.keys.len() as f64
+    }
+
+    /// Returns the shape sequences that cannot build any target, in the searched order.
+    #[inline]
+    pub fn uncovered_sequences(&self) -> Vec<ShapeSequence> {
+        (0..self.keys.len())
+            .filter(|&index| self.covered_at(index).iter().all(|&it| !it))
+            .map(|index| self.keys.sequence_at(index))
+            .collect()
+    }
+
+    /// Return the count of all shape sequences independent of the result.
+    #[inline]
+    pub fn count_keys(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+
+    use crate::cover::CoverResults;
+    use crate::ShapeSequence;
+
+    #[test]
+    fn rates() {
+        use Shape::*;
+
+        let results = CoverResults::new(2, vec![
+            (ShapeSequence::new(vec![T, I]), vec![true, false]),
+            (ShapeSequence::new(vec![T, O]), vec![true, true]),
+            (ShapeSequence::new(vec![S, Z]), vec![false, false]),
+            (ShapeSequence::new(vec![L, J]), vec![false, true]),
+        ]);
+
+        assert_eq!(results.count_covered(0), 2);
+        assert_eq!(results.count_covered(1), 2);
+        assert_eq!(results.count_covered_any(), 3);
+        assert_eq!(results.rate(0), 0.5);
+        assert_eq!(results.combined_rate(), 0.75);
+        assert_eq!(results.uncovered_sequences(), vec![ShapeSequence::new(vec![S, Z])]);
+        assert_eq!(results.get(&ShapeSequence::new(vec![L, J])), Some([false, true].as_slice()));
+        assert_eq!(results.get(&ShapeSequence::new(vec![L, L])), None);
+
+        assert_eq!(CoverResults::new(1, vec![]).combined_rate(), 0.0);
+    }
+}
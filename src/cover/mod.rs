@@ -0,0 +1,7 @@
+pub use bulk_binder::*;
+pub use bulk_executor::*;
+pub use cover_results::*;
+
+mod bulk_binder;
+mod bulk_executor;
+mod cover_results;
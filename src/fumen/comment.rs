@@ -0,0 +1,105 @@
+// Comments in fumen are escaped in the same way as `escape()` of JavaScript.
+// The escaped characters are stored as values in the table below.
+
+const COMMENT_TABLE_LENGTH: u32 = 95;
+
+/// The base to pack 4 characters into a value.
+pub(crate) const MAX_COMMENT_CHAR_VALUE: u32 = COMMENT_TABLE_LENGTH + 1;
+
+/// The upper limit of the escaped length.
+pub(crate) const MAX_COMMENT_LENGTH: usize = 4095;
+
+#[inline]
+pub(crate) fn to_comment_value(ch: char) -> u32 {
+    debug_assert!((' '..='~').contains(&ch));
+    ch as u32 - ' ' as u32
+}
+
+#[inline]
+pub(crate) fn from_comment_value(value: u32) -> Option<char> {
+    if value < COMMENT_TABLE_LENGTH {
+        char::from_u32(value + ' ' as u32)
+    } else {
+        None
+    }
+}
+
+/// Escapes the character like `escape()` of JavaScript.
+/// A character outside the BMP is escaped as its surrogate pair.
+fn escape_char(ch: char, escaped: &mut String) {
+    let mut buffer = [0u16; 2];
+    for &unit in ch.encode_utf16(&mut buffer).iter() {
+        match char::from_u32(unit as u32) {
+            Some(ch) if ch.is_ascii_alphanumeric() || "@*_+-./".contains(ch) => escaped.push(ch),
+            _ if unit < 0x100 => escaped.push_str(&format!("%{:02X}", unit)),
+            _ => escaped.push_str(&format!("%u{:04X}", unit)),
+        }
+    }
+}
+
+/// Escapes the string like `escape()` of JavaScript.
+pub(crate) fn escape(str: &str) -> String {
+    let mut escaped = String::new();
+    for ch in str.chars() {
+        escape_char(ch, &mut escaped);
+    }
+    escaped
+}
+
+/// Unescapes the string like `unescape()` of JavaScript.
+/// Sequences that are not escapes are left as they are.
+pub(crate) fn unescape(str: &str) -> String {
+    let chars: Vec<char> = str.chars().collect();
+    let parse = |from: usize, length: usize| -> Option<u16> {
+        let digits: String = chars.get(from..from + length)?.iter().collect();
+        if digits.chars().all(|it| it.is_ascii_hexdigit()) {
+            u16::from_str_radix(digits.as_str(), 16).ok()
+        } else {
+            None
+        }
+    };
+
+    let mut units = Vec::<u16>::new();
+    let mut index = 0;
+    while index < chars.len() {
+        if chars[index] == '%' {
+            if chars.get(index + 1) == Some(&'u') {
+                if let Some(unit) = parse(index + 2, 4) {
+                    units.push(unit);
+                    index += 6;
+                    continue;
+                }
+            } else if let Some(unit) = parse(index + 1, 2) {
+                units.push(unit);
+                index += 3;
+                continue;
+            }
+        }
+
+        let mut buffer = [0u16; 2];
+        units.extend_from_slice(chars[index].encode_utf16(&mut buffer));
+        index += 1;
+    }
+
+    String::from_utf16_lossy(units.as_slice())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fumen::comment::{escape, unescape};
+
+    #[test]
+    fn escape_like_javascript() {
+        assert_eq!(escape("abc-XYZ_09"), "abc-XYZ_09");
+        assert_eq!(escape("#Q=[](T)"), "%23Q%3D%5B%5D%28T%29");
+        assert_eq!(escape("a b"), "a%20b");
+        assert_eq!(escape("テト"), "%u30C6%u30C8");
+
+        for str in ["", "PC 1st", "#Q=[](T)IOSZ", "テトリス", "100% 🙂"] {
+            assert_eq!(unescape(escape(str).as_str()), str);
+        }
+
+        assert_eq!(unescape("%zz%u12"), "%zz%u12");
+    }
+}
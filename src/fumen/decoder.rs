@@ -0,0 +1,243 @@
+use thiserror::Error;
+
+use crate::fumen::comment::{from_comment_value, MAX_COMMENT_CHAR_VALUE, unescape};
+use crate::fumen::field::{ColoredField, decode_placement, FIELD_BLOCKS, FIELD_TOP, FIELD_WIDTH, from_fumen_piece, from_fumen_rotation, is_in_field};
+use crate::fumen::FumenPage;
+use crate::fumen::values::{ValuesError, ValuesReader};
+
+/// A collection of errors that occur when decoding fumen.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum FumenDecodeError {
+    #[error("Unsupported version. Only v115 is supported.")]
+    UnsupportedVersion,
+    #[error("Contains an invalid character '{0}'.")]
+    InvalidCharacter(char),
+    #[error("The data ended unexpectedly.")]
+    UnexpectedEnd,
+    #[error("The field data is broken.")]
+    BrokenField,
+    #[error("Contains an unsupported piece in the operation.")]
+    UnsupportedPiece,
+    #[error("The piece is out of the playfield.")]
+    PieceOutOfField,
+    #[error("The comment data is broken.")]
+    BrokenComment,
+}
+
+impl From<ValuesError> for FumenDecodeError {
+    fn from(error: ValuesError) -> Self {
+        match error {
+            ValuesError::InvalidCharacter(ch) => FumenDecodeError::InvalidCharacter(ch),
+            ValuesError::UnexpectedEnd => FumenDecodeError::UnexpectedEnd,
+        }
+    }
+}
+
+const VERSION_PREFIX: &str = "v115@";
+
+/// Decodes fumen (v115) into pages.
+/// A URL containing the fumen is also accepted, such as `https://fumen.zui.jp/?v115@...`.
+/// ```
+/// use bitris::prelude::*;
+/// use bitris_commands::fumen;
+///
+/// let pages = fumen::decode("v115@9gF8DeF8DeF8DeF8NeAgH").expect("Failed to decode");
+/// assert_eq!(pages.len(), 1);
+/// assert_eq!(pages[0].board().count_blocks(), 24);
+///
+/// let clipped_board = pages[0].to_clipped_board(4).expect("Failed to clip");
+/// assert_eq!(clipped_board.spaces(), 16);
+/// ```
+pub fn decode(fumen: &str) -> Result<Vec<FumenPage>, FumenDecodeError> {
+    use FumenDecodeError::*;
+
+    let start = fumen.find(VERSION_PREFIX).ok_or(UnsupportedVersion)?;
+    let data: String = fumen[start + VERSION_PREFIX.len()..].chars()
+        .filter(|&ch| ch != '?' && !ch.is_whitespace())
+        .collect();
+    let mut values = ValuesReader::new(data.as_str())?;
+
+    let mut pages = Vec::<FumenPage>::new();
+    let mut prev_field = ColoredField::default();
+    let mut prev_comment = String::new();
+    let mut repeat_count = 0;
+
+    while !values.is_empty() {
+        // Field
+        let mut field = prev_field;
+        if 0 < repeat_count {
+            repeat_count -= 1;
+        } else {
+            let changed = decode_field(&mut values, &mut field)?;
+            if !changed {
+                repeat_count = values.poll(1)?;
+            }
+        }
+
+        // Operation
+        let mut action = values.poll(3)?;
+        let piece_value = action % 8;
+        action /= 8;
+        let rotation = from_fumen_rotation(action % 4);
+        action /= 4;
+        let coordinate = action % FIELD_BLOCKS;
+        action /= FIELD_BLOCKS;
+        let rise = action % 2 == 1;
+        action /= 2;
+        let mirror = action % 2 == 1;
+        action /= 2;
+        let _colorize = action % 2 == 1;
+        action /= 2;
+        let has_comment = action % 2 == 1;
+        action /= 2;
+        let lock = action % 2 != 1;
+
+        let placement = match piece_value {
+            0 => None,
+            value => {
+                let shape = from_fumen_piece(value).ok_or(UnsupportedPiece)?;
+                let placement = decode_placement(shape.with(rotation), coordinate);
+                if !is_in_field(placement) {
+                    return Err(PieceOutOfField);
+                }
+                Some(placement)
+            }
+        };
+
+        // Comment
+        let comment = if has_comment {
+            let comment = decode_comment(&mut values)?;
+            prev_comment = comment.clone();
+            comment
+        } else {
+            prev_comment.clone()
+        };
+
+        pages.push(FumenPage::with_lock(field.to_board(), placement, comment, lock));
+
+        // Next field
+        if lock {
+            if let Some(placement) = placement {
+                field.fill(placement);
+            }
+            field.clear_lines();
+            if rise {
+                field.rise_garbage();
+            }
+            if mirror {
+                field.mirror();
+            }
+        }
+
+        prev_field = field;
+    }
+
+    Ok(pages)
+}
+
+// Returns `false` if the field is the same as the previous.
+fn decode_field(values: &mut ValuesReader, field: &mut ColoredField) -> Result<bool, FumenDecodeError> {
+    let mut changed = true;
+    let mut index = 0;
+    while index < FIELD_BLOCKS {
+        let value = values.poll(2)?;
+        let diff = value / FIELD_BLOCKS;
+        let count = value % FIELD_BLOCKS + 1;
+
+        if diff == 8 && count == FIELD_BLOCKS {
+            changed = false;
+        }
+
+        if 16 < diff || FIELD_BLOCKS < index + count {
+            return Err(FumenDecodeError::BrokenField);
+        }
+
+        for _ in 0..count {
+            let x = (index % FIELD_WIDTH as u32) as i32;
+            let y = FIELD_TOP - (index / FIELD_WIDTH as u32) as i32 - 1;
+            let next = field.get(x, y) as i32 + diff as i32 - 8;
+            if !(0..=8).contains(&next) {
+                return Err(FumenDecodeError::BrokenField);
+            }
+            field.set(x, y, next as u8);
+            index += 1;
+        }
+    }
+    Ok(changed)
+}
+
+fn decode_comment(values: &mut ValuesReader) -> Result<String, FumenDecodeError> {
+    let length = values.poll(2)?;
+
+    let mut escaped = String::with_capacity(length as usize);
+    let mut remaining = length;
+    while 0 < remaining {
+        let mut value = values.poll(5)?;
+        for _ in 0..remaining.min(4) {
+            let ch = from_comment_value(value % MAX_COMMENT_CHAR_VALUE).ok_or(FumenDecodeError::BrokenComment)?;
+            escaped.push(ch);
+            value /= MAX_COMMENT_CHAR_VALUE;
+        }
+        remaining = remaining.saturating_sub(4);
+    }
+
+    Ok(unescape(escaped.as_str()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::fumen::{decode, FumenDecodeError};
+
+    #[test]
+    fn empty() {
+        let pages = decode("v115@vhAAgH").unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].board(), Board64::blank());
+        assert_eq!(pages[0].placement(), None);
+        assert_eq!(pages[0].comment(), "");
+        assert!(pages[0].is_locked());
+
+        // The URL and separators are accepted.
+        let pages = decode("https://fumen.zui.jp/?v115@vhA?AgH").unwrap();
+        assert_eq!(pages.len(), 1);
+    }
+
+    #[test]
+    fn errors() {
+        use FumenDecodeError::*;
+
+        assert_eq!(decode("v110@vhAAgH").unwrap_err(), UnsupportedVersion);
+        assert_eq!(decode("v115@vhAA!H").unwrap_err(), InvalidCharacter('!'));
+        assert_eq!(decode("v115@vhAAg").unwrap_err(), UnexpectedEnd);
+    }
+
+    #[test]
+    fn board_from_field() {
+        let pages = decode("v115@9gF8DeF8DeF8DeF8NeAgH").unwrap();
+        assert_eq!(pages[0].board(), Board64::from_str("
+            XXXXXX....
+            XXXXXX....
+            XXXXXX....
+            XXXXXX....
+        ").unwrap());
+
+        let pages = decode("v115@bhI8KeAgH").unwrap();
+        assert_eq!(pages[0].board(), Board64::from_str("
+            XXXXXXXXX.
+        ").unwrap());
+    }
+
+    #[test]
+    fn pages_with_pieces() {
+        use Shape::*;
+
+        let pages = decode("v115@vhA1QJ").unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].placement(), Some(T.with(Orientation::North).with(bl(4, 0))));
+    }
+}
@@ -0,0 +1,349 @@
+use bitris::prelude::*;
+use thiserror::Error;
+
+use crate::fumen::comment::{escape, MAX_COMMENT_CHAR_VALUE, MAX_COMMENT_LENGTH, to_comment_value};
+use crate::fumen::field::{ColoredField, encode_placement, FIELD_BLOCKS, FIELD_TOP, FIELD_WIDTH, is_in_field, to_fumen_piece, to_fumen_rotation};
+use crate::fumen::FumenPage;
+use crate::fumen::values::{ENCODE_TABLE_LENGTH, ValuesWriter};
+
+/// A collection of errors that occur when encoding fumen.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum FumenEncodeError {
+    #[error("No pages to encode.")]
+    NoPages,
+    #[error("The board has blocks above the playfield. Up to 23 rows are supported.")]
+    BoardIsTooHigh,
+    #[error("The piece is out of the playfield.")]
+    PieceOutOfField,
+    #[error("The piece overlaps blocks on the board.")]
+    PieceOverlapsBoard,
+    #[error("The comment is too long. Up to 4095 characters are supported after escaping.")]
+    CommentIsTooLong,
+}
+
+/// Encodes pages into fumen (v115).
+/// Blocks on the board are gray, and the blocks of locked pieces keep their colors while they remain.
+/// ```
+/// use std::str::FromStr;
+/// use bitris::prelude::*;
+/// use bitris_commands::fumen::{self, FumenPage};
+///
+/// let board = Board64::from_str("
+///     XXXXXX....
+///     XXXXXX....
+///     XXXXXX....
+///     XXXXXX....
+/// ").expect("Failed to create a board");
+///
+/// let pages = vec![
+///     FumenPage::new(board, None, "".to_string()),
+/// ];
+/// assert_eq!(fumen::encode(&pages), Ok("v115@9gF8DeF8DeF8DeF8NeAgH".to_string()));
+/// ```
+pub fn encode(pages: &[FumenPage]) -> Result<String, FumenEncodeError> {
+    use FumenEncodeError::*;
+
+    if pages.is_empty() {
+        return Err(NoPages);
+    }
+
+    let mut values = ValuesWriter::default();
+    let mut prev_field = ColoredField::default();
+    let mut prev_comment = "";
+    let mut last_repeat_index: Option<usize> = None;
+
+    for (index, page) in pages.iter().enumerate() {
+        // Field
+        if FIELD_TOP < page.board().well_top() as i32 {
+            return Err(BoardIsTooHigh);
+        }
+
+        let mut field = ColoredField::with_colors_of(&page.board(), &prev_field);
+        let (changed, field_values) = encode_field(&prev_field, &field);
+        if changed {
+            values.extend(&field_values);
+            last_repeat_index = None;
+        } else {
+            match last_repeat_index {
+                Some(repeat_index) if values.get(repeat_index) < ENCODE_TABLE_LENGTH - 1 => {
+                    // Extend the repeat without recording the field.
+                    values.set(repeat_index, values.get(repeat_index) + 1);
+                }
+                _ => {
+                    // Record the field, and start a new repeat.
+                    values.extend(&field_values);
+                    values.push(0, 1);
+                    last_repeat_index = Some(values.len() - 1);
+                }
+            }
+        }
+
+        // Operation
+        let (piece_value, rotation, coordinate) = match page.placement() {
+            Some(placement) => {
+                if !is_in_field(placement) {
+                    return Err(PieceOutOfField);
+                }
+                (to_fumen_piece(placement.piece.shape) as u32, to_fumen_rotation(placement.piece.orientation), encode_placement(placement))
+            }
+            None => (0, 0, 0),
+        };
+
+        let has_comment = if index == 0 { !page.comment().is_empty() } else { prev_comment != page.comment() };
+
+        let mut action = if page.is_locked() { 0 } else { 1 };
+        action = action * 2 + has_comment as u32;
+        action = action * 2 + (index == 0) as u32; // colorize
+        action *= 2; // mirror
+        action *= 2; // rise
+        action = action * FIELD_BLOCKS + coordinate;
+        action = action * 4 + rotation;
+        action = action * 8 + piece_value;
+        values.push(action, 3);
+
+        // Comment
+        if has_comment {
+            encode_comment(&mut values, page.comment())?;
+        }
+        prev_comment = page.comment();
+
+        // Next field
+        if page.is_locked() {
+            if let Some(placement) = page.placement() {
+                field.fill(placement);
+            }
+            field.clear_lines();
+        }
+        prev_field = field;
+    }
+
+    Ok(format!("v115@{}", split_data(values.to_data())))
+}
+
+/// Encodes the board into fumen (v115) of a page.
+/// ```
+/// use bitris::prelude::*;
+/// use bitris_commands::fumen;
+///
+/// assert_eq!(fumen::encode_board(Board64::blank()), Ok("v115@vhAAgH".to_string()));
+/// ```
+pub fn encode_board(board: Board64) -> Result<String, FumenEncodeError> {
+    encode(&[FumenPage::new(board, None, String::new())])
+}
+
+/// Encodes the placements into fumen (v115), one page for each placement.
+/// The placements are placed in order, and filled rows are cleared after each placement,
+/// so that a solution such as PC can be replayed in viewers.
+/// ```
+/// use std::str::FromStr;
+/// use bitris::prelude::*;
+/// use bitris_commands::fumen;
+///
+/// let board = Board64::from_str("
+///     XXXXXX....
+///     XXXXXX....
+///     XXXXXX....
+///     XXXXXX....
+/// ").expect("Failed to create a board");
+///
+/// let placements = vec![
+///     Shape::I.with(Orientation::East).with(bl(9, 0)),
+///     Shape::O.with(Orientation::North).with(bl(6, 0)),
+///     Shape::O.with(Orientation::North).with(bl(6, 2)),
+///     Shape::I.with(Orientation::East).with(bl(8, 0)),
+/// ];
+///
+/// let fumen = fumen::encode_placements(board, &placements).expect("Failed to encode");
+/// let pages = fumen::decode(fumen.as_str()).expect("Failed to decode");
+/// assert_eq!(pages.len(), 4);
+/// assert_eq!(pages[1].placement(), Some(placements[1]));
+/// ```
+pub fn encode_placements(board: Board64, placements: &[BlPlacement]) -> Result<String, FumenEncodeError> {
+    if placements.is_empty() {
+        return encode_board(board);
+    }
+
+    let mut pages = Vec::with_capacity(placements.len());
+    let mut current = board;
+    for &placement in placements {
+        if !is_in_field(placement) {
+            return Err(FumenEncodeError::PieceOutOfField);
+        }
+        pages.push(FumenPage::new(current, Some(placement), String::new()));
+        placement.place_on_and_clear_lines(&mut current)
+            .map_err(|_| FumenEncodeError::PieceOverlapsBoard)?;
+    }
+
+    encode(pages.as_slice())
+}
+
+// Returns `false` if the field is the same as the previous.
+fn encode_field(prev: &ColoredField, current: &ColoredField) -> (bool, ValuesWriter) {
+    let diff_at = |index: u32| {
+        let x = (index % FIELD_WIDTH as u32) as i32;
+        let y = FIELD_TOP - (index / FIELD_WIDTH as u32) as i32 - 1;
+        (current.get(x, y) as i32 - prev.get(x, y) as i32 + 8) as u32
+    };
+
+    let mut values = ValuesWriter::default();
+
+    let mut changed = false;
+    let mut prev_diff = diff_at(0);
+    let mut counter = 0;
+    for index in 1..FIELD_BLOCKS {
+        let diff = diff_at(index);
+        if diff != prev_diff {
+            values.push(prev_diff * FIELD_BLOCKS + counter, 2);
+            counter = 0;
+            prev_diff = diff;
+            changed = true;
+        } else {
+            counter += 1;
+        }
+    }
+    values.push(prev_diff * FIELD_BLOCKS + counter, 2);
+
+    (changed || prev_diff != 8, values)
+}
+
+fn encode_comment(values: &mut ValuesWriter, comment: &str) -> Result<(), FumenEncodeError> {
+    let escaped: Vec<u32> = escape(comment).chars()
+        .map(to_comment_value)
+        .collect();
+    if MAX_COMMENT_LENGTH < escaped.len() {
+        return Err(FumenEncodeError::CommentIsTooLong);
+    }
+
+    values.push(escaped.len() as u32, 2);
+    for chunk in escaped.chunks(4) {
+        let value = chunk.iter()
+            .rev()
+            .fold(0, |acc, &it| acc * MAX_COMMENT_CHAR_VALUE + it);
+        values.push(value, 5);
+    }
+    Ok(())
+}
+
+// Viewers expect `?` to be inserted at regular intervals.
+fn split_data(data: String) -> String {
+    let chars: Vec<char> = data.chars().collect();
+    if chars.len() <= 42 {
+        return data;
+    }
+
+    let mut chunks = vec![chars[..42].iter().collect::<String>()];
+    chunks.extend(chars[42..].chunks(47).map(|chunk| chunk.iter().collect::<String>()));
+    chunks.join("?")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::fumen::{decode, encode, encode_board, encode_placements, FumenEncodeError, FumenPage};
+
+    #[test]
+    fn board() {
+        assert_eq!(encode_board(Board64::blank()), Ok("v115@vhAAgH".to_string()));
+
+        let board = Board64::from_str("
+            XXXXXXXXX.
+        ").unwrap();
+        assert_eq!(encode_board(board), Ok("v115@bhI8KeAgH".to_string()));
+
+        let mut board = Board64::blank();
+        board.set_at(xy(0, 23));
+        assert_eq!(encode_board(board), Err(FumenEncodeError::BoardIsTooHigh));
+    }
+
+    #[test]
+    fn piece() {
+        let placement = Shape::T.with(Orientation::North).with(bl(4, 0));
+        assert_eq!(
+            encode(&[FumenPage::new(Board64::blank(), Some(placement), String::new())]),
+            Ok("v115@vhA1QJ".to_string()),
+        );
+    }
+
+    #[test]
+    fn long_comment() {
+        // 1 + 6 * 682 = 4093 characters are escaped.
+        let comment = format!("a{}", "テ".repeat(682));
+        let fumen = encode(&[FumenPage::new(Board64::blank(), None, comment.clone())]).unwrap();
+        let pages = decode(fumen.as_str()).unwrap();
+        assert_eq!(pages[0].comment(), comment);
+
+        // The next `%u30C6` exceeds 4095.
+        let comment = format!("a{}", "テ".repeat(683));
+        assert_eq!(
+            encode(&[FumenPage::new(Board64::blank(), None, comment)]),
+            Err(FumenEncodeError::CommentIsTooLong),
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        use Shape::*;
+
+        let board = Board64::from_str("
+            XX........
+            XX......XX
+            XX.....XXX
+            XXX.....XX
+        ").unwrap();
+
+        let pages = vec![
+            FumenPage::new(board, Some(T.with(Orientation::South).with(bl(2, 0))), "PC 1st".to_string()),
+            FumenPage::new(Board64::from_str("
+                XX........
+                XX......XX
+                XXXXX..XXX
+                XXXX....XX
+            ").unwrap(), Some(S.with(Orientation::East).with(bl(6, 0))), "PC 1st".to_string()),
+            FumenPage::new(Board64::blank(), None, "テトリス #Q=[](I)".to_string()),
+        ];
+
+        let decoded = decode(encode(&pages).unwrap().as_str()).unwrap();
+        assert_eq!(decoded, pages);
+    }
+
+    #[test]
+    fn many_pages() {
+        // The unchanged fields are repeated more than the limit of a value.
+        let pages = vec![FumenPage::new(Board64::blank(), None, String::new()); 100];
+        let fumen = encode(&pages).unwrap();
+        assert!(fumen.contains('?'));
+        assert_eq!(decode(fumen.as_str()).unwrap(), pages);
+    }
+
+    #[test]
+    fn placements() {
+        use Shape::*;
+
+        let board = Board64::from_str("
+            XXXXXX....
+            XXXXXX....
+            XXXXXX....
+            XXXXXX....
+        ").unwrap();
+
+        let placements = vec![
+            I.with(Orientation::East).with(bl(9, 0)),
+            O.with(Orientation::North).with(bl(6, 0)),
+            O.with(Orientation::North).with(bl(6, 2)),
+            I.with(Orientation::East).with(bl(8, 0)),
+        ];
+
+        let pages = decode(encode_placements(board, &placements).unwrap().as_str()).unwrap();
+        assert_eq!(pages.iter().map(|page| page.placement().unwrap()).collect::<Vec<_>>(), placements);
+        assert_eq!(pages[0].board(), board);
+
+        assert_eq!(
+            encode_placements(board, &[O.with(Orientation::North).with(bl(5, 0))]),
+            Err(FumenEncodeError::PieceOverlapsBoard),
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use bitris::prelude::*;
+
+/// The count of rows in the playfield of fumen.
+pub(crate) const FIELD_TOP: i32 = 23;
+
+pub(crate) const FIELD_WIDTH: i32 = 10;
+
+/// The count of blocks including the garbage line below the playfield.
+pub(crate) const FIELD_BLOCKS: u32 = ((FIELD_TOP + 1) * FIELD_WIDTH) as u32;
+
+pub(crate) const EMPTY: u8 = 0;
+pub(crate) const GRAY: u8 = 8;
+
+/// The field of fumen with colors.
+/// It has the playfield (y = 0..23) and the garbage line (y = -1).
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) struct ColoredField {
+    blocks: [u8; FIELD_BLOCKS as usize],
+}
+
+impl Default for ColoredField {
+    fn default() -> Self {
+        Self { blocks: [EMPTY; FIELD_BLOCKS as usize] }
+    }
+}
+
+impl ColoredField {
+    /// Make a field from the board. Blocks are gray, but the colors of the blocks remaining from the previous field are kept.
+    /// The board must have no blocks above the playfield.
+    pub(crate) fn with_colors_of(board: &Board64, prev: &ColoredField) -> Self {
+        let mut field = ColoredField::default();
+        for y in 0..FIELD_TOP {
+            for x in 0..FIELD_WIDTH {
+                if board.is_occupied_at(xy(x, y)) {
+                    let prev_color = prev.get(x, y);
+                    field.set(x, y, if prev_color != EMPTY { prev_color } else { GRAY });
+                }
+            }
+        }
+        field
+    }
+
+    #[inline]
+    fn index(x: i32, y: i32) -> usize {
+        debug_assert!((0..FIELD_WIDTH).contains(&x) && (-1..FIELD_TOP).contains(&y));
+        ((y + 1) * FIELD_WIDTH + x) as usize
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, x: i32, y: i32) -> u8 {
+        self.blocks[Self::index(x, y)]
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, x: i32, y: i32, value: u8) {
+        self.blocks[Self::index(x, y)] = value;
+    }
+
+    /// Returns the playfield as a board. The garbage line is not included.
+    pub(crate) fn to_board(self) -> Board64 {
+        let mut board = Board64::blank();
+        for y in 0..FIELD_TOP {
+            for x in 0..FIELD_WIDTH {
+                if self.get(x, y) != EMPTY {
+                    board.set_at(xy(x, y));
+                }
+            }
+        }
+        board
+    }
+
+    pub(crate) fn fill(&mut self, placement: BlPlacement) {
+        let color = to_fumen_piece(placement.piece.shape);
+        for location in placement.locations() {
+            self.set(location.x, location.y, color);
+        }
+    }
+
+    /// Clears the filled rows in the playfield. The garbage line is not affected.
+    pub(crate) fn clear_lines(&mut self) {
+        let mut dest = 0;
+        for y in 0..FIELD_TOP {
+            let filled = (0..FIELD_WIDTH).all(|x| self.get(x, y) != EMPTY);
+            if filled {
+                continue;
+            }
+            for x in 0..FIELD_WIDTH {
+                self.set(x, dest, self.get(x, y));
+            }
+            dest += 1;
+        }
+        for y in dest..FIELD_TOP {
+            for x in 0..FIELD_WIDTH {
+                self.set(x, y, EMPTY);
+            }
+        }
+    }
+
+    /// Pushes up the playfield by the garbage line.
+    pub(crate) fn rise_garbage(&mut self) {
+        for y in (-1..FIELD_TOP - 1).rev() {
+            for x in 0..FIELD_WIDTH {
+                self.set(x, y + 1, self.get(x, y));
+            }
+        }
+        for x in 0..FIELD_WIDTH {
+            self.set(x, -1, EMPTY);
+        }
+    }
+
+    /// Mirrors the playfield horizontally. The garbage line is not affected.
+    pub(crate) fn mirror(&mut self) {
+        for y in 0..FIELD_TOP {
+            for x in 0..FIELD_WIDTH / 2 {
+                let left = self.get(x, y);
+                let right = self.get(FIELD_WIDTH - 1 - x, y);
+                self.set(x, y, right);
+                self.set(FIELD_WIDTH - 1 - x, y, left);
+            }
+        }
+    }
+}
+
+
+#[inline]
+pub(crate) fn to_fumen_piece(shape: Shape) -> u8 {
+    match shape {
+        Shape::I => 1,
+        Shape::L => 2,
+        Shape::O => 3,
+        Shape::Z => 4,
+        Shape::T => 5,
+        Shape::J => 6,
+        Shape::S => 7,
+    }
+}
+
+#[inline]
+pub(crate) fn from_fumen_piece(value: u32) -> Option<Shape> {
+    match value {
+        1 => Some(Shape::I),
+        2 => Some(Shape::L),
+        3 => Some(Shape::O),
+        4 => Some(Shape::Z),
+        5 => Some(Shape::T),
+        6 => Some(Shape::J),
+        7 => Some(Shape::S),
+        _ => None,
+    }
+}
+
+#[inline]
+pub(crate) fn to_fumen_rotation(orientation: Orientation) -> u32 {
+    match orientation {
+        Orientation::South => 0,
+        Orientation::East => 1,
+        Orientation::North => 2,
+        Orientation::West => 3,
+    }
+}
+
+#[inline]
+pub(crate) fn from_fumen_rotation(value: u32) -> Orientation {
+    match value % 4 {
+        0 => Orientation::South,
+        1 => Orientation::East,
+        2 => Orientation::North,
+        _ => Orientation::West,
+    }
+}
+
+/// Returns the offsets of blocks from the rotation center defined by fumen.
+fn center_offsets(piece: Piece) -> [(i32, i32); 4] {
+    let offsets = match piece.shape {
+        Shape::I => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+        Shape::T => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+        Shape::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+        Shape::L => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+        Shape::J => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+        Shape::S => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+        Shape::Z => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+    };
+    offsets.map(|(x, y)| match piece.orientation {
+        Orientation::North => (x, y),
+        Orientation::East => (y, -x),
+        Orientation::South => (-x, -y),
+        Orientation::West => (-y, x),
+    })
+}
+
+#[inline]
+fn min_offset(piece: Piece) -> (i32, i32) {
+    let offsets = center_offsets(piece);
+    (
+        offsets.iter().map(|it| it.0).min().unwrap(),
+        offsets.iter().map(|it| it.1).min().unwrap(),
+    )
+}
+
+// Fumen stores O, I, S and Z at a position different from the rotation center in some rotations.
+#[inline]
+fn position_fix(piece: Piece) -> (i32, i32) {
+    use Orientation::*;
+    use Shape::*;
+
+    match (piece.shape, piece.orientation) {
+        (O, West) => (1, -1),
+        (O, South) => (1, 0),
+        (O, North) => (0, -1),
+        (I, South) => (1, 0),
+        (I, West) => (0, -1),
+        (S, North) => (0, -1),
+        (S, East) => (-1, 0),
+        (Z, North) => (0, -1),
+        (Z, West) => (1, 0),
+        _ => (0, 0),
+    }
+}
+
+/// Converts the coordinate stored in fumen to the placement.
+pub(crate) fn decode_placement(piece: Piece, coordinate: u32) -> BlPlacement {
+    let (fix_x, fix_y) = position_fix(piece);
+    let center_x = (coordinate % FIELD_WIDTH as u32) as i32 + fix_x;
+    let center_y = FIELD_TOP - (coordinate / FIELD_WIDTH as u32) as i32 - 1 + fix_y;
+
+    let (min_x, min_y) = min_offset(piece);
+    piece.with(bl(center_x + min_x, center_y + min_y))
+}
+
+/// Converts the placement to the coordinate stored in fumen.
+/// The placement must be in the playfield.
+pub(crate) fn encode_placement(placement: BlPlacement) -> u32 {
+    let (min_x, min_y) = min_offset(placement.piece);
+    let (fix_x, fix_y) = position_fix(placement.piece);
+    let x = placement.position.lx - min_x - fix_x;
+    let y = placement.position.by - min_y - fix_y;
+
+    let coordinate = (FIELD_TOP - y - 1) * FIELD_WIDTH + x;
+    debug_assert!((0..FIELD_BLOCKS as i32).contains(&coordinate));
+    coordinate as u32
+}
+
+/// Returns `true` if all blocks of the placement are in the playfield.
+#[inline]
+pub(crate) fn is_in_field(placement: BlPlacement) -> bool {
+    placement.locations().into_iter()
+        .all(|location| (0..FIELD_WIDTH).contains(&location.x) && (0..FIELD_TOP).contains(&location.y))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+
+    use crate::fumen::field::{decode_placement, encode_placement};
+
+    #[test]
+    fn placement_round_trip() {
+        for shape in Shape::all_into_iter() {
+            for orientation in Orientation::all_into_iter() {
+                let placement = shape.with(orientation).with(bl(3, 5));
+                let coordinate = encode_placement(placement);
+                assert_eq!(decode_placement(placement.piece, coordinate), placement);
+            }
+        }
+    }
+
+    #[test]
+    fn placement_center() {
+        // The center of T is the middle of the three blocks.
+        let placement = decode_placement(Shape::T.with(Orientation::North), 22 * 10 + 1);
+        assert_eq!(placement, Shape::T.with(Orientation::North).with(bl(0, 0)));
+
+        // The center of I North is the second block from the left.
+        let placement = decode_placement(Shape::I.with(Orientation::North), 22 * 10 + 1);
+        assert_eq!(placement, Shape::I.with(Orientation::North).with(bl(0, 0)));
+    }
+}
@@ -0,0 +1,56 @@
+use bitris::prelude::*;
+
+use crate::{ClippedBoard, ClippedBoardCreationError};
+
+/// Represents a page of fumen.
+///
+/// The board is the playfield displayed on the page, before the placement is locked.
+/// If the placement is locked, it's placed on the board and filled rows are cleared on the next page.
+/// Colors of blocks are not kept, and the garbage line below the playfield is ignored.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct FumenPage {
+    board: Board64,
+    placement: Option<BlPlacement>,
+    comment: String,
+    lock: bool,
+}
+
+impl FumenPage {
+    /// Make a page. The placement is locked.
+    #[inline]
+    pub fn new(board: Board64, placement: Option<BlPlacement>, comment: String) -> Self {
+        Self { board, placement, comment, lock: true }
+    }
+
+    #[inline]
+    pub(crate) fn with_lock(board: Board64, placement: Option<BlPlacement>, comment: String, lock: bool) -> Self {
+        Self { board, placement, comment, lock }
+    }
+
+    #[inline]
+    pub fn board(&self) -> Board64 {
+        self.board
+    }
+
+    #[inline]
+    pub fn placement(&self) -> Option<BlPlacement> {
+        self.placement
+    }
+
+    #[inline]
+    pub fn comment(&self) -> &str {
+        self.comment.as_str()
+    }
+
+    /// Returns `true` if the placement is placed on the board of the next page.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.lock
+    }
+
+    /// Returns the board clipped at the height. See `ClippedBoard::try_new()` for more details.
+    #[inline]
+    pub fn to_clipped_board(&self, height: u32) -> Result<ClippedBoard, ClippedBoardCreationError> {
+        ClippedBoard::try_new(self.board, height)
+    }
+}
@@ -0,0 +1,10 @@
+pub use decoder::*;
+pub use encoder::*;
+pub use fumen_page::*;
+
+mod comment;
+mod decoder;
+mod encoder;
+mod field;
+mod fumen_page;
+mod values;
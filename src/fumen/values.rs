@@ -0,0 +1,115 @@
+const ENCODE_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) const ENCODE_TABLE_LENGTH: u32 = 64;
+
+/// Thrown when the data cannot be read as fumen values.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) enum ValuesError {
+    InvalidCharacter(char),
+    UnexpectedEnd,
+}
+
+/// Reads values from fumen data in little endian of base 64.
+#[derive(Clone, Debug)]
+pub(crate) struct ValuesReader {
+    values: Vec<u32>,
+    index: usize,
+}
+
+impl ValuesReader {
+    pub(crate) fn new(data: &str) -> Result<Self, ValuesError> {
+        let values = data.chars()
+            .map(|ch| {
+                ENCODE_TABLE.iter()
+                    .position(|&it| it as char == ch)
+                    .map(|it| it as u32)
+                    .ok_or(ValuesError::InvalidCharacter(ch))
+            })
+            .collect::<Result<Vec<u32>, ValuesError>>()?;
+        Ok(Self { values, index: 0 })
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.values.len() <= self.index
+    }
+
+    pub(crate) fn poll(&mut self, max_length: usize) -> Result<u32, ValuesError> {
+        if self.values.len() < self.index + max_length {
+            return Err(ValuesError::UnexpectedEnd);
+        }
+
+        let value = self.values[self.index..self.index + max_length].iter()
+            .rev()
+            .fold(0, |acc, &it| acc * ENCODE_TABLE_LENGTH + it);
+        self.index += max_length;
+        Ok(value)
+    }
+}
+
+/// Writes values as fumen data in little endian of base 64.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct ValuesWriter {
+    values: Vec<u32>,
+}
+
+impl ValuesWriter {
+    pub(crate) fn push(&mut self, value: u32, split_count: usize) {
+        let mut current = value;
+        for _ in 0..split_count {
+            self.values.push(current % ENCODE_TABLE_LENGTH);
+            current /= ENCODE_TABLE_LENGTH;
+        }
+        debug_assert_eq!(current, 0, "The value is too large to split.");
+    }
+
+    #[inline]
+    pub(crate) fn extend(&mut self, other: &ValuesWriter) {
+        self.values.extend_from_slice(other.values.as_slice());
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> u32 {
+        self.values[index]
+    }
+
+    #[inline]
+    pub(crate) fn set(&mut self, index: usize, value: u32) {
+        self.values[index] = value;
+    }
+
+    pub(crate) fn to_data(&self) -> String {
+        self.values.iter()
+            .map(|&it| ENCODE_TABLE[it as usize] as char)
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fumen::values::{ValuesError, ValuesReader, ValuesWriter};
+
+    #[test]
+    fn read_and_write() {
+        let mut writer = ValuesWriter::default();
+        writer.push(2159, 2);
+        writer.push(0, 1);
+        writer.push(30720, 3);
+        assert_eq!(writer.to_data(), "vhAAgH");
+
+        let mut reader = ValuesReader::new("vhAAgH").unwrap();
+        assert_eq!(reader.poll(2), Ok(2159));
+        assert_eq!(reader.poll(1), Ok(0));
+        assert_eq!(reader.poll(3), Ok(30720));
+        assert!(reader.is_empty());
+        assert_eq!(reader.poll(1), Err(ValuesError::UnexpectedEnd));
+
+        assert_eq!(ValuesReader::new("vh!").unwrap_err(), ValuesError::InvalidCharacter('!'));
+    }
+}
@@ -0,0 +1,22 @@
+use std::fmt;
+
+use bitris::Shape;
+
+use crate::internals::to_char;
+
+/// Represents that shape is undetermined.
+/// It's formatted as `?` if unknown, otherwise as the letter of the shape.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub enum FuzzyShape {
+    #[default] Unknown,
+    Known(Shape),
+}
+
+impl fmt::Display for FuzzyShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            FuzzyShape::Unknown => write!(f, "?"),
+            FuzzyShape::Known(shape) => write!(f, "{}", to_char(shape)),
+        }
+    }
+}
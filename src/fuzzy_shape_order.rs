@@ -0,0 +1,258 @@
+use std::fmt;
+use std::str::FromStr;
+
+use bitris::Shape;
+
+use crate::{ForEachVisitor, FuzzyShape, PopOp, ShapeOrder, ShapesParseError};
+use crate::internal_macros::forward_impl_from;
+use crate::internals::from_char;
+
+/// A collection of quantifiers for the unknown shapes.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub enum FuzzyQuantifier {
+    /// It must be satisfied for every fill of the unknown shapes.
+    #[default] Every,
+
+    /// It must be satisfied for at least one fill of the unknown shapes.
+    Some,
+}
+
+/// Preserves the reference status of the fuzzy order, in the same way as `OrderCursor`.
+/// The unknown shapes are popped as they are, so the caller decides the shapes to fill.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub(crate) struct FuzzyOrderCursor<'a> {
+    shapes: &'a [FuzzyShape],
+    head: Option<usize>,
+    tails: usize,
+}
+
+impl<'a> FuzzyOrderCursor<'a> {
+    /// Returns a popped shape and a next cursor. See `OrderCursor::pop()` for details.
+    #[inline]
+    pub(crate) fn pop(&self, op: PopOp) -> (Option<FuzzyShape>, FuzzyOrderCursor<'a>) {
+        match op {
+            PopOp::First => match self.head {
+                Some(head) => {
+                    let next = if self.tails < self.shapes.len() {
+                        FuzzyOrderCursor { shapes: self.shapes, head: Some(self.tails), tails: self.tails + 1 }
+                    } else {
+                        FuzzyOrderCursor { shapes: self.shapes, head: None, tails: self.tails }
+                    };
+                    (Some(self.shapes[head]), next)
+                }
+                None => (None, *self),
+            },
+            PopOp::Second => {
+                if self.tails < self.shapes.len() {
+                    let next = FuzzyOrderCursor { shapes: self.shapes, head: self.head, tails: self.tails + 1 };
+                    return (Some(self.shapes[self.tails]), next);
+                }
+                (None, *self)
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn first(&self) -> Option<FuzzyShape> {
+        self.head.map(|index| self.shapes[index])
+    }
+
+    /// Returns the shapes that have not been used, if all of them are known.
+    pub(crate) fn unused_known_shapes(&self) -> Option<ShapeOrder> {
+        let Some(head) = self.head else {
+            return Some(ShapeOrder::new(Vec::new()));
+        };
+        [self.shapes[head]].iter()
+            .chain(&self.shapes[self.tails..])
+            .map(|shape| match *shape {
+                FuzzyShape::Known(shape) => Some(shape),
+                FuzzyShape::Unknown => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(ShapeOrder::new)
+    }
+}
+
+/// Represents an order of shapes that includes fuzzy.
+/// "Order" means affected by the hold operation.
+/// Thus, it allows branches to be produced, indicating that they are not necessarily consumed from the head.
+/// It's formatted with `?` for unknown shapes (like `T?O`).
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub struct FuzzyShapeOrder {
+    shapes: Vec<FuzzyShape>,
+}
+
+impl FuzzyShapeOrder {
+    #[inline]
+    pub fn new(shapes: Vec<FuzzyShape>) -> Self {
+        Self { shapes }
+    }
+
+    #[inline]
+    pub fn shapes(&self) -> &[FuzzyShape] {
+        self.shapes.as_slice()
+    }
+
+    /// Returns the count of unknown shapes.
+    #[inline]
+    pub fn count_unknown(&self) -> usize {
+        self.shapes.iter().filter(|shape| **shape == FuzzyShape::Unknown).count()
+    }
+
+    /// Panics if the order is empty.
+    #[inline]
+    pub(crate) fn new_cursor(&self) -> FuzzyOrderCursor<'_> {
+        assert!(!self.shapes.is_empty());
+        FuzzyOrderCursor { shapes: &self.shapes, head: Some(0), tails: 1 }
+    }
+
+    /// Expand unknown shapes to the order assumed as the shape of each.
+    /// The count of orders is `7^k`, where `k` is the count of unknown shapes.
+    /// Panics if the order is empty.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use FuzzyShape::*;
+    /// use Shape::*;
+    ///
+    /// let order = FuzzyShapeOrder::new(vec![Known(T), Unknown]);
+    /// let orders = order.expand_as_wildcard();
+    /// assert_eq!(orders.len(), 7);
+    /// assert_eq!(orders[0], ShapeOrder::new(vec![T, T]));
+    /// assert_eq!(orders[6], ShapeOrder::new(vec![T, Z]));
+    /// ```
+    pub fn expand_as_wildcard(&self) -> Vec<ShapeOrder> {
+        struct Visitor {
+            out: Vec<ShapeOrder>,
+        }
+
+        impl ForEachVisitor<[Shape]> for Visitor {
+            fn visit(&mut self, shapes: &[Shape]) {
+                self.out.push(ShapeOrder::new(shapes.to_vec()));
+            }
+        }
+
+        let mut visitor = Visitor { out: Vec::new() };
+        self.expand_as_wildcard_walk(&mut visitor);
+        visitor.out
+    }
+
+    /// See `expand_as_wildcard()` for details.
+    pub(crate) fn expand_as_wildcard_walk(&self, visitor: &mut impl ForEachVisitor<[Shape]>) {
+        fn build(shapes: &Vec<FuzzyShape>, index: usize, buffer: &mut Vec<Shape>, visitor: &mut impl ForEachVisitor<[Shape]>) {
+            if shapes.len() <= index {
+                visitor.visit(buffer.as_slice());
+                return;
+            }
+
+            match shapes[index] {
+                FuzzyShape::Known(shape) => {
+                    buffer[index] = shape;
+                    build(shapes, index + 1, buffer, visitor);
+                }
+                FuzzyShape::Unknown => {
+                    for shape in Shape::all_into_iter() {
+                        buffer[index] = shape;
+                        build(shapes, index + 1, buffer, visitor);
+                    }
+                }
+            }
+        }
+
+        assert!(!self.shapes.is_empty());
+        let mut buffer = Vec::<Shape>::with_capacity(self.shapes.len());
+        buffer.resize(self.shapes.len(), Shape::T);
+        build(&self.shapes, 0, &mut buffer, visitor);
+    }
+}
+
+impl From<&ShapeOrder> for FuzzyShapeOrder {
+    fn from(order: &ShapeOrder) -> Self {
+        Self::new(order.shapes().iter().map(|&shape| FuzzyShape::Known(shape)).collect())
+    }
+}
+
+forward_impl_from!(FuzzyShapeOrder, from ShapeOrder);
+
+impl FromStr for FuzzyShapeOrder {
+    type Err = ShapesParseError;
+
+    /// Parses the letters of the shapes, and `?` for unknown shapes (like `TO??S`). Whitespaces are ignored.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    /// use FuzzyShape::*;
+    ///
+    /// let order = FuzzyShapeOrder::from_str("T O ? ? S").unwrap();
+    /// assert_eq!(order, FuzzyShapeOrder::new(vec![Known(Shape::T), Known(Shape::O), Unknown, Unknown, Known(Shape::S)]));
+    /// assert_eq!(order.to_string(), "TO??S");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.char_indices()
+            .filter(|(_, ch)| !ch.is_whitespace())
+            .map(|(index, ch)| match ch {
+                '?' => Ok(FuzzyShape::Unknown),
+                _ => from_char(ch).map(FuzzyShape::Known).ok_or(ShapesParseError::UnexpectedCharacter(ch, index)),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(FuzzyShapeOrder::new)
+    }
+}
+
+impl fmt::Display for FuzzyShapeOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.shapes.iter().try_for_each(|shape| write!(f, "{}", shape))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::*;
+
+    use crate::{FuzzyShape, FuzzyShapeOrder, PopOp, ShapeOrder};
+
+    #[test]
+    fn fuzzy() {
+        use Shape::*;
+        use FuzzyShape::*;
+        let fuzzy_shape_order = FuzzyShapeOrder::new(vec![Known(T), Unknown, Known(O)]);
+        let orders = fuzzy_shape_order.expand_as_wildcard();
+        assert_eq!(orders, vec![
+            ShapeOrder::new(vec![T, T, O]),
+            ShapeOrder::new(vec![T, I, O]),
+            ShapeOrder::new(vec![T, O, O]),
+            ShapeOrder::new(vec![T, L, O]),
+            ShapeOrder::new(vec![T, J, O]),
+            ShapeOrder::new(vec![T, S, O]),
+            ShapeOrder::new(vec![T, Z, O]),
+        ]);
+        assert_eq!(fuzzy_shape_order.count_unknown(), 1);
+        assert_eq!(fuzzy_shape_order.to_string(), "T?O");
+    }
+
+    #[test]
+    fn cursor() {
+        use Shape::*;
+        use FuzzyShape::*;
+        let fuzzy_shape_order = FuzzyShapeOrder::new(vec![Known(T), Unknown, Known(O)]);
+        let order = ShapeOrder::new(vec![T, I, O]);
+
+        // Pops in the same way as `OrderCursor`.
+        for ops in [[PopOp::First; 3], [PopOp::Second, PopOp::First, PopOp::First], [PopOp::Second, PopOp::Second, PopOp::First]] {
+            let mut fuzzy_cursor = fuzzy_shape_order.new_cursor();
+            let mut cursor = order.new_cursor();
+            for op in ops {
+                let (fuzzy_popped, next_fuzzy_cursor) = fuzzy_cursor.pop(op);
+                let (popped, next_cursor) = cursor.pop(op);
+                assert_eq!(fuzzy_popped.map(|shape| shape == Unknown), popped.map(|shape| shape == I));
+                assert_eq!(next_fuzzy_cursor.first().map(|shape| shape == Unknown), next_cursor.first().map(|shape| shape == I));
+                fuzzy_cursor = next_fuzzy_cursor;
+                cursor = next_cursor;
+            }
+        }
+
+        let (_, cursor) = fuzzy_shape_order.new_cursor().pop(PopOp::Second);
+        assert_eq!(cursor.unused_known_shapes(), Some(ShapeOrder::new(vec![T, O])));
+        assert_eq!(fuzzy_shape_order.new_cursor().unused_known_shapes(), None);
+    }
+}
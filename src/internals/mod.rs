@@ -1,5 +1,7 @@
-pub(crate) use fuzzy_shape::*;
-pub(crate) use fuzzy_shape_order::*;
+pub(crate) use sequence_keys::*;
+pub(crate) use shape_char::*;
+pub(crate) use t_spin::*;
 
-mod fuzzy_shape;
-mod fuzzy_shape_order;
+mod sequence_keys;
+mod shape_char;
+mod t_spin;
@@ -0,0 +1,212 @@
+use bitvec::vec::BitVec;
+
+use crate::{BitShapes, Mirror, ShapeSequence, WideBitShapes};
+
+/// The keys in the given order.
+/// They are held as `BitShapes` if all the shape sequences fit in it, otherwise as `WideBitShapes`.
+#[derive(Clone, PartialEq, Debug)]
+enum Keys {
+    Narrow(Vec<BitShapes>),
+    Wide(Vec<WideBitShapes>),
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Keys::Narrow(Vec::new())
+    }
+}
+
+impl Keys {
+    /// Panics if a shape sequence has more shapes than `WideBitShapes` can hold.
+    fn new<'a>(sequences: impl Iterator<Item=&'a ShapeSequence> + Clone) -> Self {
+        match sequences.clone().map(BitShapes::try_from).collect() {
+            Ok(keys) => Keys::Narrow(keys),
+            Err(_) => Keys::Wide(
+                sequences
+                    .map(|sequence| WideBitShapes::try_from(sequence).expect("The shape sequence is too long to hold."))
+                    .collect()
+            ),
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Keys::Narrow(keys) => keys.len(),
+            Keys::Wide(keys) => keys.len(),
+        }
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> Option<WideBitShapes> {
+        match self {
+            Keys::Narrow(keys) => keys.get(index).map(WideBitShapes::from),
+            Keys::Wide(keys) => keys.get(index).copied(),
+        }
+    }
+
+    /// Removes the duplicates except for the first ones, keeping the order.
+    /// Returns the positions of the keys sorted by the keys.
+    fn dedup(&mut self) -> Vec<u32> {
+        match self {
+            Keys::Narrow(keys) => dedup_keys(keys),
+            Keys::Wide(keys) => dedup_keys(keys),
+        }
+    }
+
+    #[inline]
+    fn position(&self, sorted: &[u32], sequence: &ShapeSequence) -> Option<usize> {
+        match self {
+            Keys::Narrow(keys) => search_position(keys, sorted, BitShapes::try_from(sequence).ok()?),
+            Keys::Wide(keys) => search_position(keys, sorted, WideBitShapes::try_from(sequence).ok()?),
+        }
+    }
+
+    /// The mirror images of distinct keys are also distinct, but their order changes.
+    /// Returns the mirrored keys and their positions sorted by the keys.
+    fn mirrored(&self) -> (Self, Vec<u32>) {
+        match self {
+            Keys::Narrow(keys) => {
+                let keys: Vec<BitShapes> = keys.iter().map(BitShapes::mirrored).collect();
+                let sorted = sort_positions(&keys);
+                (Keys::Narrow(keys), sorted)
+            }
+            Keys::Wide(keys) => {
+                let keys: Vec<WideBitShapes> = keys.iter().map(WideBitShapes::mirrored).collect();
+                let sorted = sort_positions(&keys);
+                (Keys::Wide(keys), sorted)
+            }
+        }
+    }
+}
+
+fn dedup_keys<K: Copy + Ord>(keys: &mut Vec<K>) -> Vec<u32> {
+    let mut sorted = sort_positions(keys);
+
+    // The sort is stable, so the first one in the given order comes first among the same keys.
+    let mut first: BitVec = BitVec::repeat(false, keys.len());
+    for (index, &position) in sorted.iter().enumerate() {
+        if index == 0 || keys[sorted[index - 1] as usize] != keys[position as usize] {
+            first.set(position as usize, true);
+        }
+    }
+
+    if first.count_ones() < keys.len() {
+        let mut remapped = vec![0u32; keys.len()];
+        let mut len = 0;
+        for position in first.iter_ones() {
+            keys[len] = keys[position];
+            remapped[position] = len as u32;
+            len += 1;
+        }
+        keys.truncate(len);
+        sorted.retain(|&position| first[position as usize]);
+        sorted.iter_mut().for_each(|position| *position = remapped[*position as usize]);
+    }
+    sorted
+}
+
+fn sort_positions<K: Copy + Ord>(keys: &[K]) -> Vec<u32> {
+    let len = u32::try_from(keys.len()).expect("The number of shape sequences is too large to hold.");
+    let mut sorted: Vec<u32> = (0..len).collect();
+    sorted.sort_by_key(|&position| keys[position as usize]);
+    sorted
+}
+
+#[inline]
+fn search_position<K: Copy + Ord>(keys: &[K], sorted: &[u32], key: K) -> Option<usize> {
+    sorted.binary_search_by_key(&key, |&position| keys[position as usize]).ok()
+        .map(|index| sorted[index] as usize)
+}
+
+/// Holds the shape sequences of results in the given order without duplicates.
+/// The results keep their own values by the positions of the keys.
+///
+/// Internally, the keys are stored once as `BitShapes`, or as `WideBitShapes` only if some shape sequences have more than 22 shapes.
+/// Lookups are binary searches over the key positions sorted by key, so lookups and iteration do not allocate.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub(crate) struct SequenceKeys {
+    keys: Keys,
+    // The positions of `keys` in the ascending order of the keys.
+    sorted: Vec<u32>,
+}
+
+impl SequenceKeys {
+    /// Removes the duplicates except for the first ones, keeping the order.
+    /// Panics if a shape sequence has more shapes than `WideBitShapes` can hold.
+    #[inline]
+    pub(crate) fn new<'a>(sequences: impl Iterator<Item=&'a ShapeSequence> + Clone) -> Self {
+        let mut keys = Keys::new(sequences);
+        let sorted = keys.dedup();
+        Self { keys, sorted }
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.keys.len() == 0
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> Option<WideBitShapes> {
+        self.keys.get(index)
+    }
+
+    /// Panics if the index is out of range.
+    #[inline]
+    pub(crate) fn key_at(&self, index: usize) -> WideBitShapes {
+        self.keys.get(index).expect("The index is out of range.")
+    }
+
+    /// Panics if the index is out of range.
+    #[inline]
+    pub(crate) fn sequence_at(&self, index: usize) -> ShapeSequence {
+        ShapeSequence::from(self.key_at(index))
+    }
+
+    /// Returns the position of a shape sequence in the given order.
+    #[inline]
+    pub(crate) fn position(&self, sequence: &ShapeSequence) -> Option<usize> {
+        self.keys.position(&self.sorted, sequence)
+    }
+}
+
+impl Mirror for SequenceKeys {
+    /// The keys stay in the same positions, so the values of the results can be kept as they are.
+    fn mirrored(&self) -> Self {
+        let (keys, sorted) = self.keys.mirrored();
+        Self { keys, sorted }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+
+    use crate::internals::SequenceKeys;
+    use crate::{Mirror, ShapeSequence};
+
+    #[test]
+    fn dedup_and_mirror() {
+        use Shape::*;
+        let sequences = vec![
+            ShapeSequence::new(vec!(Z, T)),
+            ShapeSequence::new(vec!(L, T)),
+            ShapeSequence::new(vec!(Z, T)),
+        ];
+        let keys = SequenceKeys::new(sequences.iter());
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys.position(&sequences[2]), Some(0));
+        assert_eq!(keys.position(&sequences[1]), Some(1));
+        assert_eq!(keys.sequence_at(1), sequences[1]);
+
+        let mirrored = keys.mirrored();
+        assert_eq!(mirrored.position(&ShapeSequence::new(vec!(J, T))), Some(1));
+        assert_eq!(mirrored.position(&sequences[1]), None);
+    }
+}
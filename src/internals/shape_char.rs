@@ -0,0 +1,73 @@
+use std::fmt;
+
+use bitris::Shape;
+
+use crate::ShapesParseError;
+
+/// Returns the letter of the shape.
+#[inline]
+pub(crate) fn to_char(shape: Shape) -> char {
+    match shape {
+        Shape::T => 'T',
+        Shape::I => 'I',
+        Shape::O => 'O',
+        Shape::L => 'L',
+        Shape::J => 'J',
+        Shape::S => 'S',
+        Shape::Z => 'Z',
+    }
+}
+
+/// Returns the shape of the letter. Lowercase letters are also accepted.
+#[inline]
+pub(crate) fn from_char(ch: char) -> Option<Shape> {
+    match ch.to_ascii_uppercase() {
+        'T' => Some(Shape::T),
+        'I' => Some(Shape::I),
+        'O' => Some(Shape::O),
+        'L' => Some(Shape::L),
+        'J' => Some(Shape::J),
+        'S' => Some(Shape::S),
+        'Z' => Some(Shape::Z),
+        _ => None,
+    }
+}
+
+/// Returns the shapes of the letters. Whitespaces are ignored.
+pub(crate) fn parse_shapes(text: &str) -> Result<Vec<Shape>, ShapesParseError> {
+    text.char_indices()
+        .filter(|(_, ch)| !ch.is_whitespace())
+        .map(|(index, ch)| from_char(ch).ok_or(ShapesParseError::UnexpectedCharacter(ch, index)))
+        .collect()
+}
+
+/// Writes the letters of the shapes.
+pub(crate) fn write_shapes(f: &mut fmt::Formatter<'_>, shapes: impl IntoIterator<Item=Shape>) -> fmt::Result {
+    shapes.into_iter().try_for_each(|shape| write!(f, "{}", to_char(shape)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::Shape;
+
+    use crate::internals::{from_char, parse_shapes, to_char};
+    use crate::ShapesParseError;
+
+    #[test]
+    fn round_trip() {
+        for shape in Shape::all_into_iter() {
+            assert_eq!(from_char(to_char(shape)), Some(shape));
+            assert_eq!(from_char(to_char(shape).to_ascii_lowercase()), Some(shape));
+        }
+        assert_eq!(from_char('X'), None);
+    }
+
+    #[test]
+    fn parse() {
+        use Shape::*;
+        assert_eq!(parse_shapes(" ti o\n"), Ok(vec![T, I, O]));
+        assert_eq!(parse_shapes(""), Ok(vec![]));
+        assert_eq!(parse_shapes("TX"), Err(ShapesParseError::UnexpectedCharacter('X', 1)));
+    }
+}
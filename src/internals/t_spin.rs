@@ -0,0 +1,283 @@
+use bitris::prelude::*;
+use itertools::Itertools;
+
+use crate::spin::TSpinType;
+
+#[inline]
+fn is_occupied_or_outside(board: &Board64, x: i32, y: i32) -> bool {
+    !(0..10).contains(&x) || !(0..64).contains(&y) || !board.is_free_at(xy(x, y))
+}
+
+#[inline]
+fn can_place(board: &Board64, placement: BlPlacement) -> bool {
+    placement.locations().into_iter()
+        .all(|location| !is_occupied_or_outside(board, location.x, location.y))
+}
+
+/// Returns the block adjacent to the other three blocks. For T, it's the center of rotation.
+fn center_of(locations: &[Location]) -> Option<Location> {
+    locations.iter()
+        .find(|center| {
+            locations.iter()
+                .filter(|location| (location.x - center.x).abs() + (location.y - center.y).abs() == 1)
+                .count() == 3
+        })
+        .copied()
+}
+
+#[inline]
+fn rotate(orientation: Orientation, rotation: Rotation) -> Orientation {
+    use Orientation::*;
+    match (orientation, rotation) {
+        (North, Rotation::Cw) | (South, Rotation::Ccw) => East,
+        (East, Rotation::Cw) | (West, Rotation::Ccw) => South,
+        (South, Rotation::Cw) | (North, Rotation::Ccw) => West,
+        (West, Rotation::Cw) | (East, Rotation::Ccw) => North,
+    }
+}
+
+/// Returns the T placement whose center is at the location.
+fn t_with_center(orientation: Orientation, center: Location) -> BlPlacement {
+    let origin = Shape::T.with(orientation).with(bl(0, 0));
+    let offset = center_of(&origin.locations()).expect("T always has the center.");
+    origin.piece.with(bl(center.x - offset.x, center.y - offset.y))
+}
+
+/// Rotates the T with the first kick that fits, in the same way as the rotation system.
+fn rotate_t(rotation_system: &impl RotationSystem, board: &Board64, from: BlPlacement, rotation: Rotation) -> Option<BlPlacement> {
+    let center = center_of(&from.locations()).expect("T always has the center.");
+    let orientation = rotate(from.piece.orientation, rotation);
+    rotation_system.iter_kicks(from.piece, rotation)
+        .map(|kick| t_with_center(orientation, xy(center.x + kick.offset.dx, center.y + kick.offset.dy)))
+        .find(|&placement| can_place(board, placement))
+}
+
+/// Returns `true` if the T can be placed at the goal with a rotation as the last move.
+/// The states before the rotation are the placements generated by the move rules from the spawn,
+/// and one rotation with the kicks of the rotation system must lead from one of them to the goal.
+/// Harddrop locks the T as soon as it lands, so it cannot rotate into the goal.
+/// It does not matter whether the goal can also be reached by other moves.
+pub(crate) fn can_reach_by_rotation<T: RotationSystem>(
+    move_rules: &MoveRules<T>,
+    board: &Board64,
+    spawn: BlPlacement,
+    goal: BlPlacement,
+) -> bool {
+    debug_assert_eq!(spawn.piece.shape, Shape::T);
+    debug_assert_eq!(goal.piece.shape, Shape::T);
+
+    if move_rules.allow_move != AllowMove::Softdrop {
+        return false;
+    }
+
+    for before in move_rules.generate_minimized_moves(*board, spawn) {
+        if before.piece.orientation == goal.piece.orientation {
+            continue;
+        }
+
+        for rotation in [Rotation::Cw, Rotation::Ccw] {
+            if rotate_t(move_rules.rotation_system, board, before, rotation) == Some(goal) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Judges the T-spin type by the 3-corner rule.
+/// Returns `None` if it's not a T-spin.
+pub(crate) fn judge_spin_type(board: &Board64, placement: BlPlacement) -> Option<TSpinType> {
+    debug_assert_eq!(placement.piece.shape, Shape::T);
+
+    let locations = placement.locations().into_iter().collect_vec();
+    let neighbors_of = |center: &Location| {
+        locations.iter()
+            .filter(|location| (location.x - center.x).abs() + (location.y - center.y).abs() == 1)
+            .map(|location| (location.x - center.x, location.y - center.y))
+            .collect_vec()
+    };
+
+    let center = center_of(&locations)?;
+    let neighbors = neighbors_of(&center);
+
+    // The nose is the only block without a block on the opposite side.
+    let (nose_x, nose_y) = *neighbors.iter()
+        .find(|(dx, dy)| !neighbors.contains(&(-dx, -dy)))?;
+
+    let corners = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+    let occupied = corners.into_iter()
+        .filter(|(dx, dy)| is_occupied_or_outside(board, center.x + dx, center.y + dy))
+        .count();
+    if occupied < 3 {
+        return None;
+    }
+
+    // The two corners on the nose side.
+    let (perpendicular_x, perpendicular_y) = (nose_y, nose_x);
+    let front_occupied = [1, -1].into_iter()
+        .filter(|sign| {
+            is_occupied_or_outside(
+                board,
+                center.x + nose_x + perpendicular_x * sign,
+                center.y + nose_y + perpendicular_y * sign,
+            )
+        })
+        .count();
+
+    Some(if front_occupied == 2 { TSpinType::Regular } else { TSpinType::Mini })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::internals::{can_reach_by_rotation, judge_spin_type};
+    use crate::spin::TSpinType;
+
+    #[test]
+    fn judge() {
+        use Shape::*;
+
+        let board = Board64::from_str("
+            XXXX......
+            XXX...XXXX
+            XXXX.XXXXX
+        ").unwrap();
+
+        // Double
+        assert_eq!(
+            judge_spin_type(&board, T.with(Orientation::South).with(bl(3, 0))),
+            Some(TSpinType::Regular),
+        );
+
+        // Mini
+        assert_eq!(
+            judge_spin_type(&board, T.with(Orientation::North).with(bl(3, 1))),
+            Some(TSpinType::Mini),
+        );
+
+        // Not a T-spin
+        assert_eq!(
+            judge_spin_type(&board, T.with(Orientation::North).with(bl(5, 3))),
+            None,
+        );
+    }
+
+    #[test]
+    fn reach_by_rotation() {
+        use Shape::*;
+
+        let board = Board64::from_str("
+            XXXX......
+            XXX...XXXX
+            XXXX.XXXXX
+        ").unwrap();
+        let spawn = T.with(Orientation::North).with(bl(4, 7));
+        let goal = T.with(Orientation::South).with(bl(3, 0));
+
+        // Soft drop into the slot as East, then rotate to South.
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        assert!(can_reach_by_rotation(&move_rules, &board, spawn, goal));
+
+        // Harddrop locks the T on landing, so it cannot rotate into the slot.
+        let move_rules = MoveRules::srs(AllowMove::Harddrop);
+        assert!(!can_reach_by_rotation(&move_rules, &board, spawn, goal));
+
+        // Only reachable by a drop.
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let goal = T.with(Orientation::North).with(bl(6, 2));
+        assert!(!can_reach_by_rotation(&move_rules, &board, spawn, goal));
+    }
+
+    #[test]
+    fn reach_tst() {
+        use Shape::*;
+
+        // The T slides under the overhang, and kicks down with the last test of SRS.
+        let board = Board64::from_str("
+            XX........
+            X.........
+            X.XXXXXXXX
+            X..XXXXXXX
+            X.XXXXXXXX
+        ").unwrap();
+        let spawn = T.with(Orientation::North).with(bl(4, 7));
+        let goal = T.with(Orientation::East).with(bl(1, 0));
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        assert!(can_reach_by_rotation(&move_rules, &board, spawn, goal));
+        assert_eq!(judge_spin_type(&board, goal), Some(TSpinType::Regular));
+
+        let move_rules = MoveRules::srs(AllowMove::Harddrop);
+        assert!(!can_reach_by_rotation(&move_rules, &board, spawn, goal));
+
+        // Without the overhang, the first test of SRS succeeds and the T stays above.
+        let board = Board64::from_str("
+            X.........
+            X.........
+            X.XXXXXXXX
+            X..XXXXXXX
+            X.XXXXXXXX
+        ").unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        assert!(!can_reach_by_rotation(&move_rules, &board, spawn, goal));
+    }
+
+    #[test]
+    fn reach_tsd_fin() {
+        use Shape::*;
+
+        // Same kick as TST, but the bottom line is not cleared.
+        let board = Board64::from_str("
+            XX........
+            X.........
+            X.XXXXXXXX
+            X..XXXXXXX
+            X.XXXXXXX.
+        ").unwrap();
+        let spawn = T.with(Orientation::North).with(bl(4, 7));
+        let goal = T.with(Orientation::East).with(bl(1, 0));
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        assert!(can_reach_by_rotation(&move_rules, &board, spawn, goal));
+
+        let mut after = board;
+        assert_eq!(goal.place_on_and_clear_lines(&mut after).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn reach_tsd_neo() {
+        use Shape::*;
+
+        // The T slides in as South, and kicks down with the fourth test of SRS.
+        let board = Board64::from_str("
+            .X........
+            ..........
+            X.........
+            X..XXXXXXX
+            X.XXXXXXXX
+        ").unwrap();
+        let spawn = T.with(Orientation::North).with(bl(4, 7));
+        let goal = T.with(Orientation::East).with(bl(1, 0));
+
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        assert!(can_reach_by_rotation(&move_rules, &board, spawn, goal));
+
+        let mut after = board;
+        assert_eq!(goal.place_on_and_clear_lines(&mut after).unwrap().count(), 2);
+
+        // Without the overhang, the first test of SRS succeeds and the T stays above.
+        let board = Board64::from_str("
+            ..........
+            ..........
+            X.........
+            X..XXXXXXX
+            X.XXXXXXXX
+        ").unwrap();
+        assert!(!can_reach_by_rotation(&move_rules, &board, spawn, goal));
+    }
+}
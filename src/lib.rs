@@ -2,11 +2,19 @@ extern crate core;
 
 pub use bit_shapes::*;
 pub use clipped_board::*;
+pub use fuzzy_shape::*;
+pub use fuzzy_shape_order::*;
+pub use mirror::*;
+pub use sequence_filter::*;
 pub use shape_sequence::*;
+pub use pattern_set::*;
 pub use patterns::*;
+pub use randomizer::*;
 pub use shape_order::*;
 pub use shape_counter::*;
+pub use shapes_parse_error::*;
 pub use traits::*;
+pub use wide_bit_shapes::*;
 
 #[doc(hidden)]
 pub mod prelude {
@@ -15,23 +23,45 @@ pub mod prelude {
     pub use crate::{
         bit_shapes::*,
         clipped_board::*,
+        fuzzy_shape::*,
+        fuzzy_shape_order::*,
+        mirror::*,
+        sequence_filter::*,
         shape_sequence::*,
+        pattern_set::*,
         patterns::*,
+        randomizer::*,
         shape_order::*,
         shape_counter::*,
+        shapes_parse_error::*,
         traits::*,
+        wide_bit_shapes::*,
     };
 }
 
+pub mod cover;
+pub mod fumen;
 pub mod pc_possible;
+pub mod reachable;
+pub mod ren;
+pub mod setup;
+pub mod spin;
 
 mod bit_shapes;
 mod clipped_board;
+mod fuzzy_shape;
+mod fuzzy_shape_order;
+mod mirror;
+mod sequence_filter;
 mod shape_sequence;
+mod pattern_set;
 mod patterns;
+mod randomizer;
 mod shape_order;
 mod shape_counter;
+mod shapes_parse_error;
 mod traits;
+mod wide_bit_shapes;
 
 mod internal_macros;
 mod internals;
@@ -0,0 +1,188 @@
+use bitris::Shape;
+
+use crate::{BitShapes, ClippedBoard, FuzzyShape, FuzzyShapeOrder, Pattern, PatternElement, PatternSet, ShapeCounter, ShapeOrder, ShapeSequence, WideBitShapes};
+
+/// Flips horizontally. The shapes are swapped with their mirror images (L and J, S and Z), and the others stay as they are.
+/// Applying it twice returns the original.
+/// ```
+/// use std::str::FromStr;
+/// use bitris_commands::prelude::*;
+/// use Shape::*;
+///
+/// assert_eq!(L.mirrored(), J);
+/// assert_eq!(T.mirrored(), T);
+/// assert_eq!(ShapeSequence::from_str("TLSZ").unwrap().mirrored(), ShapeSequence::from_str("TJZS").unwrap());
+/// assert_eq!(Pattern::from_str("L,[SZO]p2").unwrap().mirrored(), Pattern::from_str("J,[SZO]p2").unwrap());
+///
+/// let board = Board64::from_str("
+///     XX........
+///     XXX.......
+/// ").unwrap();
+/// let mirrored = Board64::from_str("
+///     ........XX
+///     .......XXX
+/// ").unwrap();
+/// assert_eq!(ClippedBoard::try_new(board, 2).unwrap().mirrored(), ClippedBoard::try_new(mirrored, 2).unwrap());
+/// ```
+pub trait Mirror {
+    fn mirrored(&self) -> Self;
+}
+
+impl Mirror for Shape {
+    #[inline]
+    fn mirrored(&self) -> Self {
+        match *self {
+            Shape::L => Shape::J,
+            Shape::J => Shape::L,
+            Shape::S => Shape::Z,
+            Shape::Z => Shape::S,
+            shape => shape,
+        }
+    }
+}
+
+impl Mirror for ShapeSequence {
+    fn mirrored(&self) -> Self {
+        ShapeSequence::new(self.shapes().iter().map(Shape::mirrored).collect())
+    }
+}
+
+impl Mirror for ShapeOrder {
+    fn mirrored(&self) -> Self {
+        ShapeOrder::new(self.shapes().iter().map(Shape::mirrored).collect())
+    }
+}
+
+impl Mirror for BitShapes {
+    fn mirrored(&self) -> Self {
+        let mut shapes = BitShapes::empty();
+        for shape in self.iter() {
+            shapes.push(shape.mirrored()).expect("The length is not changed.");
+        }
+        shapes
+    }
+}
+
+impl Mirror for WideBitShapes {
+    fn mirrored(&self) -> Self {
+        let shapes = self.to_vec().iter().map(Shape::mirrored).collect::<Vec<_>>();
+        WideBitShapes::try_from(shapes.as_slice()).expect("The length is not changed.")
+    }
+}
+
+impl Mirror for ShapeCounter {
+    #[inline]
+    fn mirrored(&self) -> Self {
+        let mut counters = [0; 7];
+        for shape in Shape::all_into_iter() {
+            counters[shape.mirrored() as usize] = self[shape];
+        }
+        ShapeCounter::new(counters)
+    }
+}
+
+impl Mirror for FuzzyShape {
+    #[inline]
+    fn mirrored(&self) -> Self {
+        match *self {
+            FuzzyShape::Unknown => FuzzyShape::Unknown,
+            FuzzyShape::Known(shape) => FuzzyShape::Known(shape.mirrored()),
+        }
+    }
+}
+
+impl Mirror for FuzzyShapeOrder {
+    fn mirrored(&self) -> Self {
+        FuzzyShapeOrder::new(self.shapes().iter().map(FuzzyShape::mirrored).collect())
+    }
+}
+
+impl Mirror for PatternElement {
+    /// The sequences are the mirror images of the original, but their order may differ.
+    /// Only for combinations, the shapes in each sequence are sorted again (e.g. the mirror image of `LS` in `[LS]c2` is `ZJ`, but it's held as `JZ`).
+    fn mirrored(&self) -> Self {
+        use PatternElement::*;
+        match *self {
+            One(shape) => One(shape.mirrored()),
+            Fixed(shapes) => Fixed(shapes.mirrored()),
+            Wildcard => Wildcard,
+            Permutation(counter, pop) => Permutation(counter.mirrored(), pop),
+            Factorial(counter) => Factorial(counter.mirrored()),
+            Combination(counter, pop) => Combination(counter.mirrored(), pop),
+            Optional(counter) => Optional(counter.mirrored()),
+        }
+    }
+}
+
+impl Mirror for Pattern {
+    /// The sequences are the mirror images of the original, but their order may differ.
+    fn mirrored(&self) -> Self {
+        let elements = self.elements().iter().map(PatternElement::mirrored).collect();
+        Pattern::try_new(elements).expect("The elements are still valid.")
+    }
+}
+
+impl Mirror for PatternSet {
+    fn mirrored(&self) -> Self {
+        let patterns = self.patterns().iter().map(Pattern::mirrored).collect();
+        PatternSet::try_new(patterns).expect("The patterns are still valid.")
+    }
+}
+
+impl Mirror for ClippedBoard {
+    /// Flips the columns. The height is kept.
+    #[inline]
+    fn mirrored(&self) -> Self {
+        let mut board = self.board();
+        board.cols.reverse();
+        ClippedBoard::new_unsafe(board, self.height())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+    use itertools::Itertools;
+
+    use crate::{ClippedBoard, Mirror, Pattern, ShapeCounter, ShapeSequence};
+
+    #[test]
+    fn pattern_sequences() {
+        for pattern in ["*p7", "L,[SZO]p3", "[LLJS]!", "[LZ]c1,*", "TZ,[LS]?"] {
+            let pattern = Pattern::from_str(pattern).unwrap();
+            let mirrored = pattern.mirrored();
+            assert_eq!(mirrored.mirrored(), pattern);
+
+            let expected = pattern.to_sequences().iter()
+                .map(ShapeSequence::mirrored)
+                .sorted()
+                .collect_vec();
+            assert_eq!(mirrored.to_sequences().into_iter().sorted().collect_vec(), expected);
+        }
+
+        // The shapes in the combinations are sorted again.
+        let pattern = Pattern::from_str("[LS]c2").unwrap();
+        assert_eq!(pattern.mirrored().to_sequences(), vec![ShapeSequence::from_str("JZ").unwrap()]);
+
+        let counter = ShapeCounter::from_str("[LLSO]").unwrap();
+        assert_eq!(counter.mirrored(), ShapeCounter::from_str("[JJZO]").unwrap());
+    }
+
+    #[test]
+    fn board() {
+        let board = Board64::from_str("
+            X.........
+            XX......XX
+            XXX.....XX
+        ").unwrap();
+        let clipped = ClippedBoard::try_new(board, 4).unwrap();
+        let mirrored = clipped.mirrored();
+        assert_eq!(mirrored.height(), 4);
+        assert_eq!(mirrored.spaces(), clipped.spaces());
+        assert_ne!(mirrored, clipped);
+        assert_eq!(mirrored.mirrored(), clipped);
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+use std::str::FromStr;
+
+use fxhash::FxHashSet;
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{Pattern, PatternCardinalityError, PatternCreationError, PatternParseError, SequenceFilter, ShapeOrder, ShapeSequence};
+
+/// Returns the union of the sequences of the patterns.
+/// The sequences are in the order of the patterns, and only the first of the duplicates is kept.
+pub(crate) fn union_sequences(patterns: &[Pattern]) -> Vec<ShapeSequence> {
+    if let [pattern] = patterns {
+        return pattern.to_sequences();
+    }
+
+    let mut visited = FxHashSet::<ShapeSequence>::default();
+    patterns.iter()
+        .flat_map(|pattern| pattern.to_sequences())
+        .filter(|sequence| visited.insert(sequence.clone()))
+        .collect()
+}
+
+/// Returns the total count of the sequences of the patterns, without overflow.
+/// The duplicates between the patterns are counted for each, so it's the upper limit of the count of `union_sequences()`.
+pub(crate) fn try_len_sequences(patterns: &[Pattern]) -> Result<u128, PatternCardinalityError> {
+    patterns.iter()
+        .try_fold(0u128, |sum, pattern| sum.checked_add(pattern.try_len_shapes_vec()?).ok_or(PatternCardinalityError::Overflow))
+}
+
+/// Holds several patterns, and represents the union of their sequences (like a patterns file of solution-finder).
+/// The patterns may have different dimensions.
+/// ```
+/// use std::str::FromStr;
+/// use bitris_commands::prelude::*;
+///
+/// let pattern_set = PatternSet::from_str("
+///     T,*p3  # Starts with T
+///     I,*p3  # Starts with I
+///
+///     TI,[^I]p2  # Overlaps with the first line
+/// ").unwrap();
+/// assert_eq!(pattern_set.patterns().len(), 3);
+/// assert_eq!(pattern_set.to_sequences().len(), 420);
+/// assert_eq!(pattern_set.dim_shapes(), 4);
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct PatternSet {
+    patterns: Vec<Pattern>,
+}
+
+impl TryFrom<Vec<Pattern>> for PatternSet {
+    type Error = PatternCreationError;
+
+    fn try_from(patterns: Vec<Pattern>) -> Result<Self, Self::Error> {
+        PatternSet::try_new(patterns)
+    }
+}
+
+impl From<Pattern> for PatternSet {
+    fn from(pattern: Pattern) -> Self {
+        Self { patterns: vec![pattern] }
+    }
+}
+
+impl PatternSet {
+    pub fn try_new(patterns: Vec<Pattern>) -> Result<Self, PatternCreationError> {
+        if patterns.is_empty() {
+            return Err(PatternCreationError::NoShapeSequences);
+        }
+        Ok(Self { patterns })
+    }
+
+    #[inline]
+    pub fn patterns(&self) -> &[Pattern] {
+        self.patterns.as_slice()
+    }
+
+    /// Returns all sequences represented by the patterns without duplicates.
+    /// The sequences are in the order of the patterns.
+    pub fn to_sequences(&self) -> Vec<ShapeSequence> {
+        union_sequences(&self.patterns)
+    }
+
+    /// The total count of the sequences of the patterns, without overflow.
+    /// The duplicates between the patterns are counted for each, so it's the upper limit of `to_sequences().len()`.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let pattern_set = PatternSet::from_str("
+    ///     T,*p3
+    ///     TI,[^I]p2  # Overlaps with the first line
+    /// ").unwrap();
+    /// assert_eq!(pattern_set.try_len_shapes_vec(), Ok(210 + 30));
+    /// assert_eq!(pattern_set.to_sequences().len(), 210);
+    ///
+    /// let pattern_set = PatternSet::from_str(&vec!["*p7"; 11].join(",")).unwrap();
+    /// assert_eq!(pattern_set.try_len_shapes_vec(), Err(PatternCardinalityError::Overflow));
+    /// ```
+    pub fn try_len_shapes_vec(&self) -> Result<u128, PatternCardinalityError> {
+        try_len_sequences(&self.patterns)
+    }
+
+    /// Returns the sequences that pass the filter, in the same order as `to_sequences()`.
+    pub fn to_sequences_filtered(&self, filter: &SequenceFilter) -> Vec<ShapeSequence> {
+        self.to_sequences().into_iter()
+            .filter(|sequence| filter.test(sequence))
+            .collect()
+    }
+
+    /// Returns all orders represented by the patterns without duplicates.
+    pub fn to_orders(&self) -> Vec<ShapeOrder> {
+        self.to_sequences().into_iter()
+            .map(|sequence| sequence.to_shape_order())
+            .collect()
+    }
+
+    /// Returns true if one of the patterns represents the sequence.
+    #[inline]
+    pub fn contains(&self, sequence: &ShapeSequence) -> bool {
+        self.patterns.iter().any(|pattern| pattern.rank(sequence).is_some())
+    }
+
+    /// The number of elements in the longest shapes, as `Pattern::dim_shapes()`.
+    pub fn dim_shapes(&self) -> usize {
+        self.patterns.iter()
+            .map(|pattern| pattern.dim_shapes())
+            .max()
+            .expect("The pattern set do not have patterns.")
+    }
+
+    /// The number of elements in the shortest shapes, as `Pattern::min_dim_shapes()`.
+    pub fn min_dim_shapes(&self) -> usize {
+        self.patterns.iter()
+            .map(|pattern| pattern.min_dim_shapes())
+            .min()
+            .expect("The pattern set do not have patterns.")
+    }
+}
+
+impl fmt::Display for PatternSet {
+    /// Formats one pattern per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.patterns.iter().join("\n"))
+    }
+}
+
+/// A collection of errors that occur when parsing the pattern set.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PatternSetParseError {
+    #[error("Invalid pattern at line {0}: {1}")]
+    InvalidLine(usize, PatternParseError),
+    #[error(transparent)]
+    InvalidPatternSet(#[from] PatternCreationError),
+}
+
+impl FromStr for PatternSet {
+    type Err = PatternSetParseError;
+
+    /// Parses one pattern per line. The text after `#` is a comment, and blank lines are ignored.
+    /// The line numbers in errors start from 1.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let pattern_set = PatternSet::from_str("T,*p2 # comment\n\nI,*p2").unwrap();
+    /// assert_eq!(pattern_set.to_string(), "T,*p2\nI,*p2");
+    ///
+    /// assert_eq!(
+    ///     PatternSet::from_str("T\nX"),
+    ///     Err(PatternSetParseError::InvalidLine(2, PatternParseError::UnexpectedCharacter('X', 0))),
+    /// );
+    /// assert_eq!(
+    ///     PatternSet::from_str("# comment only"),
+    ///     Err(PatternSetParseError::InvalidPatternSet(PatternCreationError::NoShapeSequences)),
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let patterns = s.lines()
+            .enumerate()
+            .map(|(index, line)| (index, line.split('#').next().unwrap_or_default()))
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| Pattern::from_str(line).map_err(|error| PatternSetParseError::InvalidLine(index + 1, error)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PatternSet::try_new(patterns)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::Shape;
+
+    use crate::{Pattern, PatternSet, ShapeSequence};
+
+    #[test]
+    fn union() {
+        use Shape::*;
+
+        let pattern_set = PatternSet::from_str("
+            [TI]p2
+            [IO]p2
+            TIO
+        ").unwrap();
+
+        let sequences = pattern_set.to_sequences();
+        assert_eq!(sequences, vec![
+            ShapeSequence::new(vec![T, I]),
+            ShapeSequence::new(vec![I, T]),
+            ShapeSequence::new(vec![I, O]),
+            ShapeSequence::new(vec![O, I]),
+            ShapeSequence::new(vec![T, I, O]),
+        ]);
+        assert!(sequences.iter().all(|sequence| pattern_set.contains(sequence)));
+        assert!(!pattern_set.contains(&ShapeSequence::new(vec![T, O])));
+
+        assert_eq!(pattern_set.min_dim_shapes(), 2);
+        assert_eq!(pattern_set.dim_shapes(), 3);
+
+        let pattern = Pattern::from_str("*p2").unwrap();
+        assert_eq!(PatternSet::from(pattern.clone()).to_sequences(), pattern.to_sequences());
+        assert!(PatternSet::try_new(vec![]).is_err());
+    }
+}
@@ -1,15 +1,106 @@
+use std::fmt;
+use std::str::FromStr;
+
 use bitris::Shape;
 use itertools::{Itertools, repeat_n};
 use thiserror::Error;
 
-use crate::{ForEachVisitor, ShapeCounter, ShapeOrder, ShapeSequence};
-use crate::bit_shapes::BitShapes;
+use crate::{ForEachVisitor, SequenceFilter, ShapeCounter, ShapeOrder, ShapeSequence, WideBitShapes};
+use crate::internals::{from_char, to_char};
 
 /// Calculate the number of permutations.
-fn calculate_permutation_size(len: usize, pop: usize) -> usize {
-    assert!(pop <= len);
-    assert!(0 < pop);
-    ((len - pop + 1)..=len).fold(1, |sum, it| sum * it)
+/// Returns `None` if it overflows.
+fn checked_permutation_size(len: usize, pop: usize) -> Option<u128> {
+    debug_assert!(pop <= len);
+    ((len - pop + 1)..=len).try_fold(1u128, |sum, it| sum.checked_mul(it as u128))
+}
+
+/// Returns the index of the shapes in the permutations of the items.
+/// The permutations are in lexicographic order of the item positions, the same as `Itertools::permutations()`.
+/// If the items have duplicates, the smallest index is returned.
+/// Returns `None` if the index overflows.
+fn rank_permutation(items: &[Shape], pop: usize, shapes: &[Shape]) -> Option<u128> {
+    if shapes.len() != pop {
+        return None;
+    }
+
+    let mut remaining = items.to_vec();
+    let mut index = 0u128;
+    for (i, shape) in shapes.iter().enumerate() {
+        let position = remaining.iter().position(|it| it == shape)?;
+        remaining.remove(position);
+        let size = checked_permutation_size(items.len() - 1 - i, pop - 1 - i)?;
+        index = index.checked_add((position as u128).checked_mul(size)?)?;
+    }
+    Some(index)
+}
+
+/// The inverse of `rank_permutation()`. The index must be less than the count of the permutations.
+fn unrank_permutation(items: &[Shape], pop: usize, mut index: u128) -> Vec<Shape> {
+    let mut remaining = items.to_vec();
+    let mut shapes = Vec::with_capacity(pop);
+    for i in 0..pop {
+        let size = checked_permutation_size(items.len() - 1 - i, pop - 1 - i)
+            .expect("It's less than the count of the permutations.");
+        shapes.push(remaining.remove((index / size) as usize));
+        index %= size;
+    }
+    shapes
+}
+
+/// Returns the index of the sorted shapes in the combinations of the counts.
+/// The combinations are in lexicographic order, the same as `PatternElement::Combination` enumerates.
+fn rank_combination(mut counts: [u8; 7], pop: usize, shapes: &[Shape]) -> Option<u128> {
+    if shapes.len() != pop || !shapes.windows(2).all(|pair| pair[0] <= pair[1]) {
+        return None;
+    }
+
+    let mut index = 0u128;
+    let mut lower = 0;
+    for (i, &shape) in shapes.iter().enumerate() {
+        // Counts the combinations that have a smaller shape here.
+        for smaller in lower..shape as usize {
+            if 0 < counts[smaller] {
+                index += count_combinations_taking(&counts, smaller, pop - 1 - i);
+            }
+        }
+        if counts[shape as usize] == 0 {
+            return None;
+        }
+        counts[shape as usize] -= 1;
+        lower = shape as usize;
+    }
+    Some(index)
+}
+
+/// The inverse of `rank_combination()`. The index must be less than the count of the combinations.
+fn unrank_combination(mut counts: [u8; 7], pop: usize, mut index: u128) -> Vec<Shape> {
+    let mut shapes = Vec::with_capacity(pop);
+    let mut lower = 0;
+    for i in 0..pop {
+        let shape = (lower..7)
+            .filter(|&shape| 0 < counts[shape])
+            .find(|&shape| {
+                let size = count_combinations_taking(&counts, shape, pop - 1 - i);
+                if index < size {
+                    return true;
+                }
+                index -= size;
+                false
+            })
+            .expect("It's less than the count of the combinations.");
+        counts[shape] -= 1;
+        lower = shape;
+        shapes.push(Shape::try_from(shape).unwrap());
+    }
+    shapes
+}
+
+/// Returns the count of the combinations that take the shape first, and then `pop` shapes from the same or later shapes.
+fn count_combinations_taking(counts: &[u8; 7], shape: usize, pop: usize) -> u128 {
+    let mut rest = counts[shape..].to_vec();
+    rest[0] -= 1;
+    count_combinations(&rest, pop)
 }
 
 /// A collection of elements to define the order/sequence of the shapes.
@@ -18,8 +109,8 @@ pub enum PatternElement {
     /// A fixed shape (like `T`)
     One(Shape),
 
-    /// A sequence fixed shapes (like `TIO`)
-    Fixed(BitShapes),
+    /// A sequence fixed shapes (like `TIO`). Up to 66 shapes can be held.
+    Fixed(WideBitShapes),
 
     /// One from all shapes (like. `*`)
     Wildcard,
@@ -31,6 +122,13 @@ pub enum PatternElement {
     /// Permutations by taking all shapes from `ShapeCounter`. Duplicates are not removed.
     /// (like `[TIOLJSZ]p7`, `*!`)
     Factorial(ShapeCounter),
+
+    /// Combinations by taking `usize` shapes from `ShapeCounter` without caring about the order.
+    /// Each combination is represented once, with the shapes sorted. (like `[TIOL]c2`)
+    Combination(ShapeCounter, usize),
+
+    /// Zero or one of the shapes in `ShapeCounter`. The empty shapes come first. (like `[SZ]?`)
+    Optional(ShapeCounter),
 }
 
 impl PatternElement {
@@ -53,36 +151,207 @@ impl PatternElement {
                     .permutations(counter.len())
                     .collect_vec()
             }
+            PatternElement::Combination(counter, pop) => {
+                assert!(0 < pop && pop <= counter.len());
+                // The items are sorted, so each combination is sorted. Only the first of the same combinations is kept.
+                to_items(counter).into_iter()
+                    .combinations(pop)
+                    .unique()
+                    .collect_vec()
+            }
+            PatternElement::Optional(counter) => {
+                let mut shapes_vec = vec![vec![]];
+                shapes_vec.extend(counter.to_pairs().into_iter().map(|(shape, _)| vec![shape]));
+                shapes_vec
+            }
         }
     }
 
     /// The count of shapes the pattern has.
+    /// Panics if the permutation is invalid or the count exceeds `usize`. See `try_len_shapes_vec()` to avoid panics.
     pub fn len_shapes_vec(&self) -> usize {
+        let len = self.try_len_shapes_vec().expect("Failed to count the shapes");
+        usize::try_from(len).expect("The count of the shapes exceeds usize.")
+    }
+
+    /// The count of shapes the pattern has, without overflow.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use PatternElement::*;
+    ///
+    /// assert_eq!(Permutation(ShapeCounter::one_of_each(), 3).try_len_shapes_vec(), Ok(210));
+    /// assert_eq!(Factorial(ShapeCounter::new([4; 7])).try_len_shapes_vec(), Ok((1..=28).product()));
+    /// assert_eq!(Factorial(ShapeCounter::new([5; 7])).try_len_shapes_vec(), Err(PatternCardinalityError::Overflow));
+    /// assert_eq!(Permutation(ShapeCounter::one_of_each(), 8).try_len_shapes_vec(), Err(PatternCardinalityError::InvalidPermutation));
+    /// ```
+    pub fn try_len_shapes_vec(&self) -> Result<u128, PatternCardinalityError> {
+        use PatternCardinalityError::*;
         match *self {
-            PatternElement::One(_) => 1,
-            PatternElement::Fixed(_) => 1,
-            PatternElement::Wildcard => 7,
+            PatternElement::One(_) => Ok(1),
+            PatternElement::Fixed(_) => Ok(1),
+            PatternElement::Wildcard => Ok(7),
             PatternElement::Permutation(counter, pop) => {
-                assert!(0 < pop && pop <= counter.len());
-                calculate_permutation_size(counter.len(), pop)
+                if pop == 0 || counter.len() < pop {
+                    return Err(InvalidPermutation);
+                }
+                checked_permutation_size(counter.len(), pop).ok_or(Overflow)
+            }
+            PatternElement::Factorial(counter) => checked_permutation_size(counter.len(), counter.len()).ok_or(Overflow),
+            PatternElement::Combination(counter, pop) => {
+                if pop == 0 || counter.len() < pop {
+                    return Err(InvalidPermutation);
+                }
+                Ok(count_combinations(&to_counts(counter), pop))
             }
-            PatternElement::Factorial(counter) => calculate_permutation_size(counter.len(), counter.len()),
+            PatternElement::Optional(counter) => Ok(1 + counter.to_pairs().len() as u128),
         }
     }
 
     /// The number of elements in one shapes.
+    /// If the number varies (like `Optional`), it's the maximum.
     pub fn dim_shapes(&self) -> usize {
         match *self {
             PatternElement::One(_) => 1,
             PatternElement::Fixed(shapes) => shapes.len(),
             PatternElement::Wildcard => 1,
-            PatternElement::Permutation(counter, pop) => {
+            PatternElement::Permutation(counter, pop) | PatternElement::Combination(counter, pop) => {
                 assert!(0 < pop && pop <= counter.len());
                 pop
             }
             PatternElement::Factorial(counter) => counter.len(),
+            PatternElement::Optional(_) => 1,
         }
     }
+
+    /// The minimum number of elements in one shapes.
+    pub fn min_dim_shapes(&self) -> usize {
+        match *self {
+            PatternElement::Optional(_) => 0,
+            _ => self.dim_shapes(),
+        }
+    }
+
+    /// Returns the index of the shapes in `to_shapes_vec()` without enumerating them.
+    /// If the element does not represent the shapes, returns `None`.
+    /// If the shapes appear more than once (like `[TTI]p2`), the smallest index is returned.
+    /// Also returns `None` if the count of the shapes is invalid or overflows. See `try_len_shapes_vec()`.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use PatternElement::*;
+    /// use Shape::*;
+    ///
+    /// let element = Factorial(ShapeCounter::new([4; 7]));
+    /// let shapes = [Z, S, J, L, O, I, T].repeat(4);
+    /// let index = element.rank(&shapes).unwrap();
+    /// assert_eq!(element.unrank(index), Some(shapes));
+    ///
+    /// assert_eq!(Combination(ShapeCounter::one_of_each(), 3).rank(&[T, O, Z]), Some(8));
+    /// assert_eq!(Combination(ShapeCounter::one_of_each(), 3).rank(&[O, T, Z]), None);
+    /// assert_eq!(Factorial(ShapeCounter::new([5; 7])).rank(&[T].repeat(35)), None);
+    /// ```
+    pub fn rank(&self, shapes: &[Shape]) -> Option<u128> {
+        self.try_len_shapes_vec().ok()?;
+        self.rank_unchecked(shapes)
+    }
+
+    /// Same as `rank()`, but the count of the shapes is assumed to be valid.
+    fn rank_unchecked(&self, shapes: &[Shape]) -> Option<u128> {
+        match *self {
+            PatternElement::One(shape) => (shapes == [shape]).then_some(0),
+            PatternElement::Fixed(fixed) => (WideBitShapes::try_from(shapes) == Ok(fixed)).then_some(0),
+            PatternElement::Wildcard => match shapes {
+                [shape] => Some(*shape as u128),
+                _ => None,
+            },
+            PatternElement::Permutation(counter, pop) => rank_permutation(&to_items(counter), pop, shapes),
+            PatternElement::Factorial(counter) => rank_permutation(&to_items(counter), counter.len(), shapes),
+            PatternElement::Combination(counter, pop) => rank_combination(to_counts(counter), pop, shapes),
+            PatternElement::Optional(counter) => match shapes {
+                [] => Some(0),
+                [shape] => counter.to_pairs().iter().position(|(it, _)| it == shape).map(|index| index as u128 + 1),
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns the shapes at the index of `to_shapes_vec()` without enumerating them.
+    /// Returns `None` if the index is out of range, or the count of the shapes is invalid or overflows.
+    pub fn unrank(&self, index: u128) -> Option<Vec<Shape>> {
+        if self.try_len_shapes_vec().ok()? <= index {
+            return None;
+        }
+
+        let shapes = match *self {
+            PatternElement::One(shape) => vec![shape],
+            PatternElement::Fixed(shapes) => shapes.to_vec(),
+            PatternElement::Wildcard => vec![Shape::try_from(index as usize).unwrap()],
+            PatternElement::Permutation(counter, pop) => unrank_permutation(&to_items(counter), pop, index),
+            PatternElement::Factorial(counter) => unrank_permutation(&to_items(counter), counter.len(), index),
+            PatternElement::Combination(counter, pop) => unrank_combination(to_counts(counter), pop, index),
+            PatternElement::Optional(counter) => match index {
+                0 => vec![],
+                index => vec![counter.to_pairs()[index as usize - 1].0],
+            },
+        };
+        Some(shapes)
+    }
+}
+
+/// Returns the count of the combinations by taking `pop` shapes from the counts of the shapes.
+/// It's the coefficient of `x^pop` in the product of `(1 + x + ... + x^count)` for each shape.
+fn count_combinations(counts: &[u8], pop: usize) -> u128 {
+    let mut coefficients = vec![0u128; pop + 1];
+    coefficients[0] = 1;
+    for &count in counts {
+        let previous = coefficients.clone();
+        for total in 1..=pop {
+            coefficients[total] = (0..=(count as usize).min(total)).map(|taken| previous[total - taken]).sum();
+        }
+    }
+    coefficients[pop]
+}
+
+/// Returns the counts of the sequences from each element to the last, and `1` at the end.
+/// The first one is the count of all the sequences.
+fn to_strides(elements: &[PatternElement]) -> Result<Vec<u128>, PatternCardinalityError> {
+    let mut strides = vec![1u128; elements.len() + 1];
+    for (index, element) in elements.iter().enumerate().rev() {
+        strides[index] = strides[index + 1].checked_mul(element.try_len_shapes_vec()?)
+            .ok_or(PatternCardinalityError::Overflow)?;
+    }
+    Ok(strides)
+}
+
+/// Returns the index of the shapes in the sequences of the elements.
+/// `strides` are the counts of the sequences after each element. Since the count of all fits, the index never overflows.
+/// If the elements have variable lengths, all the ways to split the shapes are tried, and the smallest index is returned.
+fn rank_elements(elements: &[PatternElement], strides: &[u128], shapes: &[Shape]) -> Option<u128> {
+    let Some((element, rest)) = elements.split_first() else {
+        return shapes.is_empty().then_some(0);
+    };
+
+    (element.min_dim_shapes()..=element.dim_shapes().min(shapes.len()))
+        .filter_map(|dim| {
+            let index = element.rank_unchecked(&shapes[..dim])?;
+            Some(index * strides[0] + rank_elements(rest, &strides[1..], &shapes[dim..])?)
+        })
+        .min()
+}
+
+/// Returns the counts in the order of `Shape`, including zeros.
+fn to_counts(counter: ShapeCounter) -> [u8; 7] {
+    let mut counts = [0; 7];
+    for shape in Shape::all_into_iter() {
+        counts[shape as usize] = counter[shape];
+    }
+    counts
+}
+
+/// Returns the shapes of the counter in the same order as `to_shapes_vec()` takes them.
+fn to_items(counter: ShapeCounter) -> Vec<Shape> {
+    counter.to_pairs().into_iter()
+        .flat_map(|(shape, count)| repeat_n(shape, count as usize))
+        .collect()
 }
 
 /// Define the order/sequence of the shapes.
@@ -96,7 +365,7 @@ impl PatternElement {
 /// assert_eq!(pattern.dim_shapes(), 3);
 ///
 /// // `TI` (1 sequence)
-/// let pattern = Pattern::try_from(vec![Fixed(BitShapes::try_from(vec![Shape::T, Shape::I]).unwrap())]).unwrap();
+/// let pattern = Pattern::try_from(vec![Fixed(WideBitShapes::try_from(vec![Shape::T, Shape::I]).unwrap())]).unwrap();
 /// assert_eq!(pattern.len_shapes_vec(), 1);
 /// assert_eq!(pattern.dim_shapes(), 2);
 ///
@@ -108,6 +377,18 @@ impl PatternElement {
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct Pattern {
     elements: Vec<PatternElement>,
+    // The counts of the sequences from each element to the last, and `1` at the end.
+    // They are computed once when the pattern is made, since ranking refers to them for every element.
+    strides: Result<Vec<u128>, PatternCardinalityError>,
+}
+
+/// A collection of errors that occur when counting the sequences of the pattern.
+#[derive(Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PatternCardinalityError {
+    #[error("The element is an invalid permutation.")]
+    InvalidPermutation,
+    #[error("The count of sequences overflows.")]
+    Overflow,
 }
 
 /// A collection of errors that occur when making the pattern.
@@ -136,9 +417,17 @@ impl Pattern {
             return Err(NoShapeSequences);
         }
 
+        // The fixed shapes of one shape are held as `One`, since they are formatted and parsed as `One`.
+        let elements = elements.into_iter()
+            .map(|element| match element {
+                Fixed(shapes) if shapes.len() == 1 => One(shapes.to_vec()[0]),
+                element => element,
+            })
+            .collect_vec();
+
         for element in &elements {
             match element {
-                Permutation(counter, pop) => {
+                Permutation(counter, pop) | Combination(counter, pop) => {
                     if counter.len() <= 0 || *pop <= 0 || counter.len() < *pop {
                         return Err(ContainsInvalidPermutation);
                     }
@@ -147,7 +436,13 @@ impl Pattern {
             }
         }
 
-        Ok(Self { elements })
+        let strides = to_strides(&elements);
+        Ok(Self { elements, strides })
+    }
+
+    #[inline]
+    pub fn elements(&self) -> &[PatternElement] {
+        self.elements.as_slice()
     }
 
     #[allow(dead_code)]
@@ -216,6 +511,14 @@ impl Pattern {
             .collect()
     }
 
+    /// Returns the sequences that pass the filter, in the same order as `to_sequences()`.
+    /// See `SequenceFilter` for examples.
+    pub fn to_sequences_filtered(&self, filter: &SequenceFilter) -> Vec<ShapeSequence> {
+        self.to_sequences().into_iter()
+            .filter(|sequence| filter.test(sequence))
+            .collect()
+    }
+
     /// Returns all orders represented by the patterns.
     pub fn to_orders(&self) -> Vec<ShapeOrder> {
         self.to_shapes_vec().into_iter()
@@ -224,31 +527,307 @@ impl Pattern {
     }
 
     /// The count of shapes the patterns has.
+    /// Panics if the count exceeds `usize`. See `try_len_shapes_vec()` to avoid panics.
     pub fn len_shapes_vec(&self) -> usize {
-        if self.elements.is_empty() {
-            return 0;
-        }
-        self.elements.iter()
-            .map(|it| it.len_shapes_vec())
-            .fold(1, |sum, it| sum * it)
+        let len = self.try_len_shapes_vec().expect("Failed to count the shapes");
+        usize::try_from(len).expect("The count of the shapes exceeds usize.")
+    }
+
+    /// The count of shapes the patterns has, without overflow.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let pattern = Pattern::from_str("*p7,*p7").unwrap();
+    /// assert_eq!(pattern.try_len_shapes_vec(), Ok(5040 * 5040));
+    ///
+    /// let pattern = Pattern::from_str(&vec!["*p7"; 11].join(",")).unwrap();
+    /// assert_eq!(pattern.try_len_shapes_vec(), Err(PatternCardinalityError::Overflow));
+    /// ```
+    #[inline]
+    pub fn try_len_shapes_vec(&self) -> Result<u128, PatternCardinalityError> {
+        self.strides.as_ref()
+            .map(|strides| strides[0])
+            .map_err(|err| *err)
     }
 
     /// The number of elements in one shapes.
+    /// If the number varies (like `[SZ]?`), it's the maximum.
     pub fn dim_shapes(&self) -> usize {
         assert!(!self.elements.is_empty(), "The pattern do not have shapes.");
         self.elements.iter()
             .map(|it| it.dim_shapes())
             .fold(0, |sum, it| sum + it)
     }
+
+    /// The minimum number of elements in one shapes.
+    /// The dimension checks of the executors use this, since all sequences must be long enough.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let pattern = Pattern::from_str("*p4,[SZ]?").unwrap();
+    /// assert_eq!(pattern.min_dim_shapes(), 4);
+    /// assert_eq!(pattern.dim_shapes(), 5);
+    /// assert_eq!(pattern.len_shapes_vec(), 840 * 3);
+    /// ```
+    pub fn min_dim_shapes(&self) -> usize {
+        assert!(!self.elements.is_empty(), "The pattern do not have shapes.");
+        self.elements.iter()
+            .map(|it| it.min_dim_shapes())
+            .sum()
+    }
+
+    /// Returns the index of the sequence in `to_sequences()` without enumerating them.
+    /// If the pattern does not represent the sequence, returns `None`.
+    /// If the sequence appears more than once (like `[TTI]p2`), the smallest index is returned.
+    /// Also returns `None` if the count of the sequences overflows. See `try_len_shapes_vec()`.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    ///
+    /// let pattern = Pattern::from_str("T,*p3").unwrap();
+    /// let sequences = pattern.to_sequences();
+    ///
+    /// let sequence = ShapeSequence::new(vec![T, O, S, I]);
+    /// let index = pattern.rank(&sequence).unwrap();
+    /// assert_eq!(sequences[index as usize], sequence);
+    /// assert_eq!(pattern.unrank(index), Some(sequence));
+    ///
+    /// assert_eq!(pattern.rank(&ShapeSequence::new(vec![I, O, S, T])), None);
+    /// assert_eq!(pattern.rank(&ShapeSequence::new(vec![T, O, O, I])), None);
+    /// ```
+    pub fn rank(&self, sequence: &ShapeSequence) -> Option<u128> {
+        let shapes = sequence.shapes();
+        if shapes.len() < self.min_dim_shapes() || self.dim_shapes() < shapes.len() {
+            return None;
+        }
+
+        let strides = self.strides.as_ref().ok()?;
+        rank_elements(&self.elements, &strides[1..], shapes)
+    }
+
+    /// Returns the sequence at the index of `to_sequences()` without enumerating them.
+    /// It's useful to take contiguous index ranges of the sequences.
+    /// Returns `None` if the index is out of range, or the count of the sequences overflows.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let pattern = Pattern::from_str(&vec!["*p7"; 7].join(",")).unwrap();
+    /// let last = pattern.try_len_shapes_vec().unwrap() - 1;
+    /// let sequence = pattern.unrank(last).unwrap();
+    /// assert_eq!(sequence.to_string(), "ZSJLOIT".repeat(7));
+    /// assert_eq!(pattern.rank(&sequence), Some(last));
+    ///
+    /// assert_eq!(pattern.unrank(last + 1), None);
+    /// ```
+    pub fn unrank(&self, mut index: u128) -> Option<ShapeSequence> {
+        let strides = self.strides.as_ref().ok()?;
+        if strides[0] <= index {
+            return None;
+        }
+
+        let mut shapes = Vec::with_capacity(self.dim_shapes());
+        for (element, &stride) in self.elements.iter().zip(&strides[1..]) {
+            shapes.extend(element.unrank(index / stride)?);
+            index %= stride;
+        }
+        Some(ShapeSequence::new(shapes))
+    }
+}
+
+impl fmt::Display for PatternElement {
+    /// Formats in the same syntax as parsing.
+    /// Note that `Permutation` with one pop is formatted without `p1` (like `[TIO]`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_counter(f: &mut fmt::Formatter<'_>, counter: ShapeCounter) -> fmt::Result {
+            if counter == ShapeCounter::one_of_each() {
+                return write!(f, "*");
+            }
+            write!(f, "{}", counter)
+        }
+
+        match *self {
+            PatternElement::One(shape) => write!(f, "{}", to_char(shape)),
+            PatternElement::Fixed(shapes) => write!(f, "{}", shapes),
+            PatternElement::Wildcard => write!(f, "*"),
+            PatternElement::Permutation(counter, pop) => {
+                write_counter(f, counter)?;
+                if pop != 1 || counter == ShapeCounter::one_of_each() {
+                    write!(f, "p{}", pop)?;
+                }
+                Ok(())
+            }
+            PatternElement::Factorial(counter) => {
+                write_counter(f, counter)?;
+                write!(f, "!")
+            }
+            PatternElement::Combination(counter, pop) => {
+                write_counter(f, counter)?;
+                write!(f, "c{}", pop)
+            }
+            PatternElement::Optional(counter) => {
+                write_counter(f, counter)?;
+                write!(f, "?")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.elements.iter().join(","))
+    }
+}
+
+/// A collection of errors that occur when parsing the pattern.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PatternParseError {
+    #[error("Unexpected character '{0}' at {1}.")]
+    UnexpectedCharacter(char, usize),
+    #[error("The pattern ends unexpectedly.")]
+    UnexpectedEnd,
+    #[error("The fixed shapes are too long. Up to 66 are supported.")]
+    TooManyShapes,
+    #[error(transparent)]
+    InvalidPattern(#[from] PatternCreationError),
+}
+
+impl FromStr for Pattern {
+    type Err = PatternParseError;
+
+    /// Parses the pattern in the syntax of solution-finder.
+    /// The elements are separated by `,`, and whitespaces are ignored.
+    ///
+    /// * `T`: One
+    /// * `TIO`: Fixed
+    /// * `*`: Wildcard
+    /// * `*p3`, `[TIO]p2`, `[TIO]` (same as `[TIO]p1`): Permutation
+    /// * `*!`, `[TIO]!`: Factorial
+    /// * `*c3`, `[TIO]c2`: Combination
+    /// * `*?`, `[SZ]?`: Optional
+    /// * `[^TIO]`: the shapes other than those in the brackets
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    /// use PatternElement::*;
+    ///
+    /// let pattern = Pattern::from_str("T, [^T]p2, *!").unwrap();
+    /// assert_eq!(pattern, Pattern::try_from(vec![
+    ///     One(Shape::T),
+    ///     Permutation(ShapeCounter::from(vec![Shape::I, Shape::O, Shape::L, Shape::J, Shape::S, Shape::Z]), 2),
+    ///     Factorial(ShapeCounter::one_of_each()),
+    /// ]).unwrap());
+    /// assert_eq!(pattern.to_string(), "T,[IOLJSZ]p2,*!");
+    ///
+    /// assert_eq!(Pattern::from_str("T,X"), Err(PatternParseError::UnexpectedCharacter('X', 2)));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars = s.char_indices()
+            .filter(|(_, ch)| !ch.is_whitespace())
+            .collect_vec();
+
+        let elements = chars.split(|&(_, ch)| ch == ',')
+            .map(parse_element)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Pattern::try_new(elements)?)
+    }
+}
+
+fn parse_element(chars: &[(usize, char)]) -> Result<PatternElement, PatternParseError> {
+    use PatternParseError::*;
+
+    let Some(&(_, head)) = chars.first() else {
+        return Err(UnexpectedEnd);
+    };
+
+    match head {
+        '*' => parse_suffix(ShapeCounter::one_of_each(), &chars[1..], PatternElement::Wildcard),
+        '[' => {
+            let Some(close) = chars.iter().position(|&(_, ch)| ch == ']') else {
+                return Err(UnexpectedEnd);
+            };
+
+            let (negates, inner) = match chars[1..close].split_first() {
+                Some((&(_, '^'), inner)) => (true, inner),
+                _ => (false, &chars[1..close]),
+            };
+
+            let shapes = parse_shapes(inner)?;
+            let counter = if negates {
+                ShapeCounter::from(Shape::all_into_iter().filter(|shape| !shapes.contains(shape)).collect_vec())
+            } else {
+                ShapeCounter::from(shapes)
+            };
+            if counter.len() == 0 {
+                return Err(UnexpectedCharacter(']', chars[close].0));
+            }
+
+            parse_suffix(counter, &chars[close + 1..], PatternElement::Permutation(counter, 1))
+        }
+        _ => {
+            let shapes = parse_shapes(chars)?;
+            if shapes.len() == 1 {
+                Ok(PatternElement::One(shapes[0]))
+            } else {
+                WideBitShapes::try_from(shapes)
+                    .map(PatternElement::Fixed)
+                    .map_err(|_| TooManyShapes)
+            }
+        }
+    }
+}
+
+fn parse_shapes(chars: &[(usize, char)]) -> Result<Vec<Shape>, PatternParseError> {
+    chars.iter()
+        .map(|&(index, ch)| from_char(ch).ok_or(PatternParseError::UnexpectedCharacter(ch, index)))
+        .collect()
+}
+
+fn parse_suffix(
+    counter: ShapeCounter,
+    chars: &[(usize, char)],
+    default: PatternElement,
+) -> Result<PatternElement, PatternParseError> {
+    use PatternParseError::*;
+
+    match chars {
+        [] => Ok(default),
+        [(_, '!')] => Ok(PatternElement::Factorial(counter)),
+        [(_, '?')] => Ok(PatternElement::Optional(counter)),
+        [(_, '!' | '?'), (index, ch), ..] => Err(UnexpectedCharacter(*ch, *index)),
+        [(_, 'p'), digits @ ..] => Ok(PatternElement::Permutation(counter, parse_number(digits)?)),
+        [(_, 'c'), digits @ ..] => Ok(PatternElement::Combination(counter, parse_number(digits)?)),
+        [(index, ch), ..] => Err(UnexpectedCharacter(*ch, *index)),
+    }
+}
+
+fn parse_number(digits: &[(usize, char)]) -> Result<usize, PatternParseError> {
+    use PatternParseError::*;
+
+    if digits.is_empty() {
+        return Err(UnexpectedEnd);
+    }
+    let mut number = 0usize;
+    for &(index, ch) in digits {
+        let digit = ch.to_digit(10).ok_or(UnexpectedCharacter(ch, index))?;
+        number = number.saturating_mul(10).saturating_add(digit as usize);
+    }
+    Ok(number)
 }
 
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use bitris::Shape;
 
-    use crate::{Pattern, PatternCreationError, PatternElement, ShapeCounter};
-    use crate::bit_shapes::BitShapes;
+    use crate::{Pattern, PatternCardinalityError, PatternCreationError, PatternElement, PatternParseError, ShapeCounter, ShapeSequence};
+    use crate::WideBitShapes;
 
     #[test]
     fn one() {
@@ -258,9 +837,15 @@ mod tests {
 
     #[test]
     fn fixed() {
-        let shapes = BitShapes::try_from(vec![Shape::T, Shape::O, Shape::L]).unwrap();
+        let shapes = WideBitShapes::try_from(vec![Shape::T, Shape::O, Shape::L]).unwrap();
         let pattern = PatternElement::Fixed(shapes);
         assert_eq!(pattern.to_shapes_vec(), vec![vec![Shape::T, Shape::O, Shape::L]]);
+
+        // One shape is the same as `One`, so it's formatted to be parsed as the same.
+        let shapes = WideBitShapes::try_from(vec![Shape::T]).unwrap();
+        let pattern = Pattern::try_new(vec![PatternElement::Fixed(shapes)]).unwrap();
+        assert_eq!(pattern.elements(), [PatternElement::One(Shape::T)]);
+        assert_eq!(Pattern::from_str(&pattern.to_string()), Ok(pattern));
     }
 
     #[test]
@@ -325,6 +910,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cardinality() {
+        use PatternElement::*;
+
+        let counter = ShapeCounter::from(vec![Shape::I]);
+        assert_eq!(Permutation(counter, 2).try_len_shapes_vec(), Err(PatternCardinalityError::InvalidPermutation));
+        assert_eq!(Permutation(counter, 0).try_len_shapes_vec(), Err(PatternCardinalityError::InvalidPermutation));
+        assert_eq!(Factorial(ShapeCounter::empty()).try_len_shapes_vec(), Ok(1));
+
+        // 35! / 5! fits in u128, but 35! / 5! * 7 does not.
+        let pattern = Pattern::try_from(vec![Permutation(ShapeCounter::new([5; 7]), 30)]).unwrap();
+        assert_eq!(pattern.try_len_shapes_vec(), Ok((6..=35).product::<u128>()));
+        let pattern = Pattern::try_from(vec![Permutation(ShapeCounter::new([5; 7]), 30), Wildcard]).unwrap();
+        assert_eq!(pattern.try_len_shapes_vec(), Err(PatternCardinalityError::Overflow));
+    }
+
     #[test]
     fn large() {
         let patterns = Pattern::try_from(vec![
@@ -335,4 +936,151 @@ mod tests {
         assert_eq!(patterns.dim_shapes(), 9);
         assert_eq!(patterns.to_sequences().len(), 5040 * 210);
     }
+
+    #[test]
+    fn parse() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let pattern = Pattern::from_str("TIO, *, *p3, [TIO]p2, [SZ], *!, [LLJ]!, *c3, [TIO]c2, [SZ]?").unwrap();
+        assert_eq!(pattern, Pattern::try_from(vec![
+            Fixed(WideBitShapes::try_from(vec![T, I, O]).unwrap()),
+            Wildcard,
+            Permutation(ShapeCounter::one_of_each(), 3),
+            Permutation(ShapeCounter::from(vec![T, I, O]), 2),
+            Permutation(ShapeCounter::from(vec![S, Z]), 1),
+            Factorial(ShapeCounter::one_of_each()),
+            Factorial(ShapeCounter::from(vec![L, L, J])),
+            Combination(ShapeCounter::one_of_each(), 3),
+            Combination(ShapeCounter::from(vec![T, I, O]), 2),
+            Optional(ShapeCounter::from(vec![S, Z])),
+        ]).unwrap());
+        assert_eq!(pattern.to_string(), "TIO,*,*p3,[TIO]p2,[SZ],*!,[LLJ]!,*c3,[TIO]c2,[SZ]?");
+        assert_eq!(Pattern::from_str(&pattern.to_string()).unwrap(), pattern);
+
+        assert_eq!(Pattern::from_str("t").unwrap(), Pattern::try_from(vec![One(T)]).unwrap());
+        assert_eq!(
+            Pattern::from_str("[^TIOLJ]").unwrap(),
+            Pattern::try_from(vec![Permutation(ShapeCounter::from(vec![S, Z]), 1)]).unwrap(),
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        use PatternParseError::*;
+
+        assert_eq!(Pattern::from_str(""), Err(UnexpectedEnd));
+        assert_eq!(Pattern::from_str("T,"), Err(UnexpectedEnd));
+        assert_eq!(Pattern::from_str("[TI"), Err(UnexpectedEnd));
+        assert_eq!(Pattern::from_str("*p"), Err(UnexpectedEnd));
+        assert_eq!(Pattern::from_str("T, A"), Err(UnexpectedCharacter('A', 3)));
+        assert_eq!(Pattern::from_str("*px"), Err(UnexpectedCharacter('x', 2)));
+        assert_eq!(Pattern::from_str("*!!"), Err(UnexpectedCharacter('!', 2)));
+        assert_eq!(Pattern::from_str("*?p"), Err(UnexpectedCharacter('p', 2)));
+        assert_eq!(Pattern::from_str("*c"), Err(UnexpectedEnd));
+        assert_eq!(Pattern::from_str("[^TIOLJSZ]"), Err(UnexpectedCharacter(']', 9)));
+        assert_eq!(Pattern::from_str(&"T".repeat(67)), Err(TooManyShapes));
+        assert!(Pattern::from_str(&"T".repeat(66)).is_ok());
+        assert_eq!(
+            Pattern::from_str("[TI]p3"),
+            Err(InvalidPattern(PatternCreationError::ContainsInvalidPermutation)),
+        );
+    }
+
+    #[test]
+    fn rank_and_unrank() {
+        use Shape::*;
+
+        for text in ["*,T,*", "TIO,[TIO]p2", "*p3", "[LJSZ]!,O", "[TTI]p2", "[TTIO]c2,*", "T,[TI]?,[IO]?", "*c3,[SZ]?"] {
+            let pattern = Pattern::from_str(text).unwrap();
+            let sequences = pattern.to_sequences();
+            for (index, sequence) in sequences.iter().enumerate() {
+                assert_eq!(pattern.unrank(index as u128).as_ref(), Some(sequence));
+
+                // The smallest index is returned for duplicates.
+                let rank = pattern.rank(sequence).unwrap();
+                assert_eq!(rank as usize, sequences.iter().position(|it| it == sequence).unwrap());
+            }
+            assert_eq!(pattern.unrank(sequences.len() as u128), None);
+        }
+
+        // Combinations are ranked without enumerating them.
+        for pop in 1..=7 {
+            let element = PatternElement::Combination(ShapeCounter::from_str("[TTIOOOZ]").unwrap(), pop);
+            for (index, shapes) in element.to_shapes_vec().iter().enumerate() {
+                assert_eq!(element.rank(shapes), Some(index as u128));
+                assert_eq!(element.unrank(index as u128).as_ref(), Some(shapes));
+            }
+        }
+
+        let pattern = Pattern::from_str("[TTI]p2").unwrap();
+        assert_eq!(pattern.rank(&ShapeSequence::new(vec![I, I])), None);
+        assert_eq!(pattern.rank(&ShapeSequence::new(vec![T])), None);
+        let pattern = Pattern::from_str("[TTIO]c2").unwrap();
+        assert_eq!(pattern.rank(&ShapeSequence::new(vec![T, I])), Some(1));
+        assert_eq!(pattern.rank(&ShapeSequence::new(vec![I, T])), None);
+        assert_eq!(pattern.rank(&ShapeSequence::new(vec![I, I])), None);
+    }
+
+    #[test]
+    fn rank_and_unrank_large() {
+        use Shape::*;
+
+        // The count exceeds `u64`.
+        let pattern = Pattern::from_str("[TTTTIIIIOOOOLLLLJJJJSSSSZZZZ]!").unwrap();
+        let len = pattern.try_len_shapes_vec().unwrap();
+        assert!(u128::from(u64::MAX) < len);
+        let sequence = pattern.unrank(len - 1).unwrap();
+        assert_eq!(sequence.shapes(), [Z, Z, Z, Z, S, S, S, S, J, J, J, J, L, L, L, L, O, O, O, O, I, I, I, I, T, T, T, T]);
+        let rank = pattern.rank(&sequence).unwrap();
+        assert_eq!(pattern.unrank(rank), Some(sequence));
+
+        let pattern = Pattern::from_str(&vec!["*p7"; 7].join(",")).unwrap();
+        let len = pattern.try_len_shapes_vec().unwrap();
+        for index in [0, len / 3, len / 2, len - 1] {
+            let sequence = pattern.unrank(index).unwrap();
+            assert_eq!(pattern.rank(&sequence), Some(index));
+        }
+
+        // The count overflows `u128`.
+        let pattern = Pattern::from_str(&vec!["*p7"; 11].join(",")).unwrap();
+        let sequence = ShapeSequence::new([T, I, O, L, J, S, Z].repeat(11));
+        assert_eq!(pattern.rank(&sequence), None);
+        assert_eq!(pattern.unrank(0), None);
+    }
+
+    #[test]
+    fn combination_and_optional() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let element = Combination(ShapeCounter::from(vec![T, T, I, O]), 2);
+        assert_eq!(element.to_shapes_vec(), vec![vec![T, T], vec![T, I], vec![T, O], vec![I, O]]);
+        assert_eq!(element.len_shapes_vec(), 4);
+        assert_eq!(element.dim_shapes(), 2);
+        assert_eq!(element.min_dim_shapes(), 2);
+        assert_eq!(Combination(ShapeCounter::one_of_each(), 3).len_shapes_vec(), 35);
+        assert_eq!(Combination(ShapeCounter::new([2; 7]), 7).len_shapes_vec(), Combination(ShapeCounter::new([2; 7]), 7).to_shapes_vec().len());
+
+        let element = Optional(ShapeCounter::from(vec![S, Z]));
+        assert_eq!(element.to_shapes_vec(), vec![vec![], vec![S], vec![Z]]);
+        assert_eq!(element.len_shapes_vec(), 3);
+        assert_eq!(element.dim_shapes(), 1);
+        assert_eq!(element.min_dim_shapes(), 0);
+
+        let pattern = Pattern::from_str("T,[SZ]?,I").unwrap();
+        assert_eq!(pattern.to_sequences(), vec![
+            ShapeSequence::new(vec![T, I]),
+            ShapeSequence::new(vec![T, S, I]),
+            ShapeSequence::new(vec![T, Z, I]),
+        ]);
+        assert_eq!(pattern.min_dim_shapes(), 2);
+        assert_eq!(pattern.dim_shapes(), 3);
+
+        assert_eq!(
+            Pattern::try_from(vec![Combination(ShapeCounter::one_of_each(), 8)]).unwrap_err(),
+            PatternCreationError::ContainsInvalidPermutation,
+        );
+        assert_eq!(Combination(ShapeCounter::one_of_each(), 0).try_len_shapes_vec(), Err(PatternCardinalityError::InvalidPermutation));
+    }
 }
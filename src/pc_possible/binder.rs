@@ -27,6 +27,7 @@ pub struct PcPossibleExecutorBinder<T: RotationSystem> {
     pub clipped_board: ClippedBoard,
     pub shape_order: Rc<ShapeOrder>,
     pub allows_hold: bool,
+    pub requires_t_spin: bool,
 }
 
 impl PcPossibleExecutorBinder<SrsKickTable> {
@@ -46,6 +47,7 @@ impl<T: RotationSystem> PcPossibleExecutorBinder<T> {
     ///   + board: blank
     ///   + height: 4 lines
     ///   + allows hold: yes
+    ///   + requires T-spin: no
     pub fn default(rotation_system: Rc<T>) -> Self {
         Self {
             rotation_system,
@@ -53,6 +55,7 @@ impl<T: RotationSystem> PcPossibleExecutorBinder<T> {
             clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
             shape_order: Rc::from(ShapeOrder::new(vec![])),
             allows_hold: true,
+            requires_t_spin: false,
         }
     }
 
@@ -81,17 +84,19 @@ impl<T: RotationSystem> PcPossibleExecutorBinder<T> {
                     FromError::UnexpectedBoardSpaces => ToError::UnexpectedBoardSpaces,
                     FromError::ShortPatternDimension => ToError::ShortOrderDimension,
                     FromError::BoardIsTooHigh => ToError::BoardIsTooHigh,
+                    FromError::TooManySequences | FromError::SequenceIsTooLong => panic!("Unreachable assumption"),
                 }
             })
     }
 
     fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>, pattern: &'a Pattern) -> Result<PcPossibleBulkExecutor<T>, PcPossibleExecutorBulkCreationError> {
-        PcPossibleBulkExecutor::try_new(
+        // The pattern has only one sequence, and it's searched without holding it as a key.
+        PcPossibleBulkExecutor::try_new_without_limit(
             move_rules,
             self.clipped_board,
             pattern,
             self.allows_hold,
-        )
+        ).map(|executor| executor.requires_t_spin(self.requires_t_spin))
     }
 }
 
@@ -3,8 +3,8 @@ use std::rc::Rc;
 use bitris::prelude::*;
 use bitris::srs::SrsKickTable;
 
-use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
-use crate::pc_possible::{ExecuteInstruction, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, PcResults};
+use crate::{ClippedBoard, Pattern, PatternElement, PatternSet, SequenceFilter, ShapeCounter};
+use crate::pc_possible::{CacheKey, ExecuteInstruction, PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, PcResults, PcResultsCache, SymmetricRotationSystem};
 
 /// The binder to hold and tie settings for `PcPossibleBulkExecutor`.
 #[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
@@ -12,8 +12,9 @@ pub struct PcPossibleBulkExecutorBinder<T: RotationSystem> {
     pub rotation_system: Rc<T>,
     pub allow_move: AllowMove,
     pub clipped_board: ClippedBoard,
-    pub pattern: Rc<Pattern>,
+    pub pattern: Rc<PatternSet>,
     pub allows_hold: bool,
+    pub requires_t_spin: bool,
 }
 
 impl PcPossibleBulkExecutorBinder<SrsKickTable> {
@@ -31,17 +32,19 @@ impl<T: RotationSystem> PcPossibleBulkExecutorBinder<T> {
     ///   + allow move: softdrop
     ///   + board: blank
     ///   + height: 4 lines
-    ///   + pattern: factorial of all shapes (like `*p7`)
+    ///   + pattern: factorial of all shapes (like `*p7`). Several patterns can be set as a `PatternSet`.
     ///   + allows hold: yes
+    ///   + requires T-spin: no
     pub fn default(rotation_system: Rc<T>) -> Self {
         Self {
             rotation_system,
             allow_move: AllowMove::Softdrop,
             clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
-            pattern: Rc::from(Pattern::try_from(vec![
+            pattern: Rc::from(PatternSet::from(Pattern::try_from(vec![
                 PatternElement::Factorial(ShapeCounter::one_of_each()),
-            ]).unwrap()),
+            ]).unwrap())),
             allows_hold: true,
+            requires_t_spin: false,
         }
     }
 
@@ -59,13 +62,58 @@ impl<T: RotationSystem> PcPossibleBulkExecutorBinder<T> {
         Ok(executor.execute_with_early_stopping(early_stopping))
     }
 
+    // See `PcPossibleBulkExecutor::{try_new, execute_with_filter}` for more details.
+    pub fn try_execute_with_filter(&self, filter: &SequenceFilter) -> Result<PcResults, PcPossibleExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute_with_filter(filter))
+    }
+
+    /// Same as `try_execute()`, but returns the results in the cache without searching if the settings were already computed.
+    /// Otherwise, the results are searched and added to the cache. See `PcResultsCache` for more details.
+    pub fn try_execute_with_cache(&self, cache: &mut PcResultsCache<T>) -> Result<PcResults, PcPossibleExecutorBulkCreationError> {
+        let key = self.to_cache_key();
+        if let Some(results) = cache.get(&key) {
+            return Ok(results);
+        }
+
+        let results = self.try_execute()?;
+        cache.insert(key, results.clone());
+        Ok(results)
+    }
+
+    fn to_cache_key(&self) -> CacheKey {
+        CacheKey {
+            allow_move: self.allow_move,
+            clipped_board: self.clipped_board,
+            pattern_set: self.pattern.as_ref().clone(),
+            allows_hold: self.allows_hold,
+            requires_t_spin: self.requires_t_spin,
+        }
+    }
+
     fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<PcPossibleBulkExecutor<T>, PcPossibleExecutorBulkCreationError> {
-        PcPossibleBulkExecutor::try_new(
+        PcPossibleBulkExecutor::try_new_with_pattern_set(
             move_rules,
             self.clipped_board,
             self.pattern.as_ref(),
             self.allows_hold,
-        )
+        ).map(|executor| executor.requires_t_spin(self.requires_t_spin))
+    }
+}
+
+impl<T: SymmetricRotationSystem> PcPossibleBulkExecutorBinder<T> {
+    /// Same as `try_execute_with_cache()`, but the results are also derived from those of the mirror image in the cache.
+    /// The derived results are not added to the cache. See `PcResultsCache` for more details.
+    pub fn try_execute_with_mirrored_cache(&self, cache: &mut PcResultsCache<T>) -> Result<PcResults, PcPossibleExecutorBulkCreationError> {
+        let key = self.to_cache_key();
+        if let Some(results) = cache.get(&key).or_else(|| cache.derive_mirrored(&key)) {
+            return Ok(results);
+        }
+
+        let results = self.try_execute()?;
+        cache.insert(key, results.clone());
+        Ok(results)
     }
 }
 
@@ -77,7 +125,7 @@ mod tests {
 
     use bitris::prelude::*;
 
-    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    use crate::{ClippedBoard, Pattern, PatternElement, PatternSet, ShapeCounter};
     use crate::pc_possible::PcPossibleBulkExecutorBinder;
 
     #[test]
@@ -104,10 +152,19 @@ mod tests {
             ###.....##
         ").unwrap();
         binder.clipped_board = ClippedBoard::try_new(board, 4).unwrap();
-        binder.pattern = Rc::from(Pattern::try_from(vec![
+        binder.pattern = Rc::from(PatternSet::from(Pattern::try_from(vec![
             Permutation(ShapeCounter::one_of_each(), 6),
-        ]).unwrap());
+        ]).unwrap()));
+        let result = binder.try_execute().unwrap();
+        assert_eq!(result.count_succeed(), 4088);
+
+        // The union of several patterns.
+        binder.pattern = Rc::from(PatternSet::from_str("
+            *p6
+            T,[^T]p5 # Included in the first line
+        ").unwrap());
         let result = binder.try_execute().unwrap();
+        assert_eq!(result.count_keys(), 5040);
         assert_eq!(result.count_succeed(), 4088);
     }
 }
@@ -1,14 +1,18 @@
+use std::hash::Hash;
+
 use bitris::prelude::*;
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
 use thiserror::Error;
 
-use crate::{ClippedBoard, ForEachVisitor, OrderCursor, Pattern, PopOp, ShapeOrder, ShapeSequence};
-use crate::internals::{FuzzyShape, FuzzyShapeOrder};
+use crate::{ClippedBoard, ForEachVisitor, FuzzyOrderCursor, FuzzyShape, FuzzyShapeOrder, OrderCursor, Pattern, PatternSet, PopOp, SequenceFilter, ShapeOrder, ShapeSequence, WideBitShapes};
+use crate::pattern_set::{try_len_sequences, union_sequences};
+use crate::internals::{can_reach_by_rotation, judge_spin_type};
 use crate::pc_possible::{Buffer, PcResults, VerticalParity};
 use crate::pc_possible::bulk_executor::ExecuteInstruction::Continue;
 
 struct Visitor<'a> {
     result: &'a mut PcResults,
+    cut_sequences: &'a CutSequences<'a>,
 }
 
 impl<'a> ForEachVisitor<[FuzzyShape]> for Visitor<'a> {
@@ -23,8 +27,49 @@ impl<'a> ForEachVisitor<[Shape]> for Visitor<'a> {
     #[inline]
     fn visit(&mut self, shapes: &[Shape]) {
         let order = ShapeSequence::new(shapes.to_vec());
-        self.result.accept_if_present(&order, true);
+        self.cut_sequences.accept(self.result, &order, true);
+    }
+}
+
+/// The sequences longer than the shapes used in a PC, grouped by the sequences after being cut off.
+/// The shapes after them never affect the results, so only the cut-off sequences are searched,
+/// and the results are written back to all the sequences in the group.
+struct CutSequences<'a> {
+    max_used_shapes: usize,
+    groups: FxHashMap<ShapeSequence, Vec<&'a ShapeSequence>>,
+}
+
+impl<'a> CutSequences<'a> {
+    fn new(sequences: &'a [ShapeSequence], max_used_shapes: usize) -> Self {
+        let mut groups = FxHashMap::<ShapeSequence, Vec<&ShapeSequence>>::default();
+        for sequence in sequences {
+            if max_used_shapes < sequence.shapes().len() {
+                groups.entry(cut_off(sequence, max_used_shapes)).or_default().push(sequence);
+            }
+        }
+        Self { max_used_shapes, groups }
     }
+
+    #[inline]
+    fn cut_off(&self, sequence: &ShapeSequence) -> ShapeSequence {
+        cut_off(sequence, self.max_used_shapes)
+    }
+
+    /// Accepts the cut-off sequence itself and all the sequences in its group.
+    fn accept(&self, results: &mut PcResults, cut_sequence: &ShapeSequence, succeed: bool) {
+        results.accept_if_present(cut_sequence, succeed);
+        if let Some(sequences) = self.groups.get(cut_sequence) {
+            for sequence in sequences {
+                results.accept_if_present(sequence, succeed);
+            }
+        }
+    }
+}
+
+#[inline]
+fn cut_off(sequence: &ShapeSequence, max_used_shapes: usize) -> ShapeSequence {
+    let shapes = sequence.shapes();
+    ShapeSequence::new(shapes[..shapes.len().min(max_used_shapes)].to_vec())
 }
 
 
@@ -32,18 +77,88 @@ impl<'a> ForEachVisitor<[Shape]> for Visitor<'a> {
 /// The block counts and height on the board can determine the search depth. (Placed pieces will change the block counts.)
 /// If the search depth is the same and the head of shapes is the same, they are the same states.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
-struct SearchingState {
+struct SearchingState<S> {
     // The board does not include filled rows.
     board: Board64,
 
     height: u32,
 
-    first: Option<Shape>,
+    first: Option<S>,
+}
+
+static ALL_SHAPES: [Shape; 7] = [Shape::T, Shape::I, Shape::O, Shape::L, Shape::J, Shape::S, Shape::Z];
+
+/// The cursor of the order to be searched.
+/// If the popped shape is unknown, every shape is tried in its place.
+trait SearchCursor: Copy {
+    type Shape: Copy + Eq + Hash;
+
+    fn pop(&self, op: PopOp) -> (Option<Self::Shape>, Self);
+
+    fn first(&self) -> Option<Self::Shape>;
+
+    /// Returns the shapes that can be placed for the popped one.
+    fn candidates(shape: Self::Shape) -> &'static [Shape];
+
+    /// Returns the unused shapes if all of them are known.
+    fn unused_known_shapes(&self) -> Option<ShapeOrder>;
+}
+
+impl SearchCursor for OrderCursor<'_> {
+    type Shape = Shape;
+
+    #[inline]
+    fn pop(&self, op: PopOp) -> (Option<Shape>, Self) {
+        OrderCursor::pop(self, op)
+    }
+
+    #[inline]
+    fn first(&self) -> Option<Shape> {
+        OrderCursor::first(self)
+    }
+
+    #[inline]
+    fn candidates(shape: Shape) -> &'static [Shape] {
+        let index = shape as usize;
+        &ALL_SHAPES[index..=index]
+    }
+
+    #[inline]
+    fn unused_known_shapes(&self) -> Option<ShapeOrder> {
+        Some(self.unused_shapes())
+    }
+}
+
+impl SearchCursor for FuzzyOrderCursor<'_> {
+    type Shape = FuzzyShape;
+
+    #[inline]
+    fn pop(&self, op: PopOp) -> (Option<FuzzyShape>, Self) {
+        FuzzyOrderCursor::pop(self, op)
+    }
+
+    #[inline]
+    fn first(&self) -> Option<FuzzyShape> {
+        FuzzyOrderCursor::first(self)
+    }
+
+    #[inline]
+    fn candidates(shape: FuzzyShape) -> &'static [Shape] {
+        match shape {
+            FuzzyShape::Known(shape) => OrderCursor::candidates(shape),
+            FuzzyShape::Unknown => &ALL_SHAPES,
+        }
+    }
+
+    #[inline]
+    fn unused_known_shapes(&self) -> Option<ShapeOrder> {
+        FuzzyOrderCursor::unused_known_shapes(self)
+    }
 }
 
 
 #[inline]
-fn validate_board(clipped: &ClippedBoard) -> bool {
+pub(crate) fn validate_board(clipped: &ClippedBoard) -> bool {
     let wall = (1 << clipped.height()) - 1;
     let mut frees_sum = clipped.height() - clipped.board_ref().cols[0].count_ones();
 
@@ -74,6 +189,56 @@ pub enum PcPossibleExecutorBulkCreationError {
     ShortPatternDimension,
     #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
     BoardIsTooHigh,
+    #[error("The pattern has too many sequences to enumerate. Consider sampling the sequences and passing them to `try_new_with_sequences()`.")]
+    TooManySequences,
+    #[error("A sequence exceeds the upper limit of length. Up to 66 shapes are supported.")]
+    SequenceIsTooLong,
+}
+
+/// The upper limit of the count of sequences that the executor enumerates from patterns.
+/// Beyond this, the sequences and results take several gigabytes.
+pub const MAX_ENUMERABLE_SEQUENCES: u128 = 1 << 26;
+
+/// Returns `Err()` if the patterns have more sequences than `MAX_ENUMERABLE_SEQUENCES`.
+/// The count includes duplicates between the patterns. See `PatternSet::try_len_shapes_vec()`.
+pub(crate) fn check_enumerable(patterns: &[Pattern]) -> Result<(), PcPossibleExecutorBulkCreationError> {
+    if try_len_sequences(patterns).map_or(true, |size| MAX_ENUMERABLE_SEQUENCES < size) {
+        return Err(PcPossibleExecutorBulkCreationError::TooManySequences);
+    }
+    Ok(())
+}
+
+/// The sequences to be searched by the executor.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Hash, Debug)]
+enum SequenceSource<'a> {
+    // The union of the sequences of the patterns.
+    Patterns(&'a [Pattern]),
+
+    // The sequences as they are.
+    Sequences(&'a [ShapeSequence]),
+}
+
+impl SequenceSource<'_> {
+    fn to_sequences(self) -> Vec<ShapeSequence> {
+        match self {
+            SequenceSource::Patterns(patterns) => union_sequences(patterns),
+            SequenceSource::Sequences(sequences) => sequences.to_vec(),
+        }
+    }
+
+    fn dimensions(self) -> Vec<usize> {
+        match self {
+            SequenceSource::Patterns(patterns) => patterns.iter().map(|pattern| pattern.min_dim_shapes()).collect(),
+            SequenceSource::Sequences(sequences) => sequences.iter().map(|sequence| sequence.shapes().len()).collect(),
+        }
+    }
+
+    fn max_len(self) -> usize {
+        match self {
+            SequenceSource::Patterns(patterns) => patterns.iter().map(|pattern| pattern.dim_shapes()).max().unwrap_or(0),
+            SequenceSource::Sequences(sequences) => sequences.iter().map(|sequence| sequence.shapes().len()).max().unwrap_or(0),
+        }
+    }
 }
 
 /// The executor to find PC possibles.
@@ -81,10 +246,10 @@ pub enum PcPossibleExecutorBulkCreationError {
 pub struct PcPossibleBulkExecutor<'a, T: RotationSystem> {
     move_rules: &'a MoveRules<'a, T>,
     clipped_board: ClippedBoard,
-    pattern: &'a Pattern,
+    source: SequenceSource<'a>,
     allows_hold: bool,
-    has_extra_shapes: bool,
     spawn_position: BlPosition,
+    requires_t_spin: bool,
 }
 
 /// A collection of statements that instruct execution to continue/stop.
@@ -137,6 +302,111 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
         clipped_board: ClippedBoard,
         pattern: &'a Pattern,
         allows_hold: bool,
+    ) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        Self::try_new_without_limit(move_rules, clipped_board, pattern, allows_hold)?.check_size()?.check_len()
+    }
+
+    /// Same as `try_new()`, but the count and the length of sequences are not limited.
+    /// This is for the callers that search orders individually without enumerating the pattern.
+    pub(crate) fn try_new_without_limit(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+    ) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        Self::try_new_with_source(move_rules, clipped_board, SequenceSource::Patterns(std::slice::from_ref(pattern)), allows_hold)
+    }
+
+    /// Make PcPossibleBulkExecutor for the union of the sequences of the patterns.
+    /// The duplicate sequences are searched only once.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// The dimension is checked for the shortest pattern.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, PatternSet};
+    /// use bitris_commands::pc_possible::PcPossibleBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXX.....XX
+    ///     XXX....XXX
+    ///     XXX...XXXX
+    ///     XXX....XXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let pattern_set = PatternSet::from_str("
+    ///     I,*p4
+    ///     I,T,[^T]p3 # Included in the first line
+    /// ").expect("Failed to parse");
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new_with_pattern_set(&move_rules, clipped_board, &pattern_set, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert_eq!(results.count_succeed(), 711);
+    /// assert_eq!(results.count_keys(), 840);
+    /// ```
+    pub fn try_new_with_pattern_set(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        pattern_set: &'a PatternSet,
+        allows_hold: bool,
+    ) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        Self::try_new_with_source(move_rules, clipped_board, SequenceSource::Patterns(pattern_set.patterns()), allows_hold)?.check_size()?.check_len()
+    }
+
+    /// Make PcPossibleBulkExecutor for the sequences as they are (like sequences recorded from real games).
+    /// The sequences may have different lengths.
+    /// A PC uses `spaces / 4` shapes (plus one for hold), so the shapes after them are not searched since they never affect the results.
+    /// The results have the given sequences as keys, except for duplicates.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// The dimension is checked for the shortest sequence, and `ShortPatternDimension` is returned if no sequences are given.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, ShapeSequence};
+    /// use bitris_commands::pc_possible::PcPossibleBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXX.....XX
+    ///     XXX....XXX
+    ///     XXX...XXXX
+    ///     XXX....XXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let sequences: Vec<ShapeSequence> = ["ITJLO", "IZSOT", "ISZLJO"].iter()
+    ///     .map(|text| ShapeSequence::from_str(text).expect("Failed to parse"))
+    ///     .collect();
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new_with_sequences(&move_rules, clipped_board, &sequences, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert_eq!(results.count_keys(), 3);
+    /// assert_eq!(results.count_accepted(), 3);
+    /// ```
+    pub fn try_new_with_sequences(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        sequences: &'a [ShapeSequence],
+        allows_hold: bool,
+    ) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        Self::try_new_with_source(move_rules, clipped_board, SequenceSource::Sequences(sequences), allows_hold)?.check_len()
+    }
+
+    fn try_new_with_source(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        source: SequenceSource<'a>,
+        allows_hold: bool,
     ) -> Result<Self, PcPossibleExecutorBulkCreationError> {
         use PcPossibleExecutorBulkCreationError::*;
 
@@ -148,19 +418,71 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
             return Err(UnexpectedBoardSpaces);
         }
 
-        let dimension = pattern.dim_shapes() as u32;
-        if dimension < clipped_board.spaces() / 4 {
+        let min_dimension = source.dimensions().into_iter().min().unwrap_or(0) as u32;
+        if min_dimension < clipped_board.spaces() / 4 {
             return Err(ShortPatternDimension);
         }
 
         debug_assert!(0 < clipped_board.spaces());
 
-        let has_extra_shapes = clipped_board.spaces() / 4 < dimension;
-
         // Spawn above the height so that it is not stuck when harddrop only.
         let spawn_position = bl(5, clipped_board.height() as i32 + 4);
 
-        Ok(Self { move_rules, clipped_board, pattern, allows_hold, has_extra_shapes, spawn_position })
+        Ok(Self { move_rules, clipped_board, source, allows_hold, spawn_position, requires_t_spin: false })
+    }
+
+    /// Returns `Err()` if the patterns have too many sequences. See `check_enumerable()`.
+    fn check_size(self) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        if let SequenceSource::Patterns(patterns) = self.source {
+            check_enumerable(patterns)?;
+        }
+        Ok(self)
+    }
+
+    /// Returns `Err()` if a sequence is longer than the results can hold as a key (see `WideBitShapes::MAX_LEN`).
+    fn check_len(self) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        if WideBitShapes::MAX_LEN < self.source.max_len() {
+            return Err(PcPossibleExecutorBulkCreationError::SequenceIsTooLong);
+        }
+        Ok(self)
+    }
+
+    // A PC uses a shape for every 4 spaces, and one more can be held.
+    fn max_used_shapes(&self) -> usize {
+        self.clipped_board.spaces() as usize / 4 + if self.allows_hold { 1 } else { 0 }
+    }
+
+    /// Requires every T to be placed with a T-spin that clears lines (like `tspin` drop in solution-finder).
+    /// A T-spin is judged in the same way as `spin::SpinBulkExecutor`.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Shape, Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement};
+    /// use bitris_commands::pc_possible::PcPossibleBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXX...XXXX
+    ///     XXXX.XXXXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 2).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::One(Shape::T),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false)
+    ///     .expect("Failed to create an executor");
+    /// assert_eq!(executor.execute().count_succeed(), 1);
+    ///
+    /// // The T can be dropped into the slot, so it's not a T-spin.
+    /// let executor = executor.requires_t_spin(true);
+    /// assert_eq!(executor.execute().count_succeed(), 0);
+    /// ```
+    #[inline]
+    pub fn requires_t_spin(self, requires_t_spin: bool) -> Self {
+        Self { requires_t_spin, ..self }
     }
 
     /// Start the search for PC possible in bulk.
@@ -211,15 +533,54 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
     /// assert!(0 < result.count_pending());
     /// ```
     pub fn execute_with_early_stopping(&self, early_stopping: impl Fn(&PcResults) -> ExecuteInstruction) -> PcResults {
-        let sequences = self.pattern.to_sequences();
-        let infer_size = self.pattern.dim_shapes();
+        self.execute_sequences(self.source.to_sequences(), early_stopping)
+    }
 
+    /// Start the search for PC possible in bulk only for the sequences that pass the filter.
+    /// The keys of the results are also the filtered sequences.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, SequenceFilter};
+    /// use bitris_commands::pc_possible::PcPossibleBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXX.....XX
+    ///     XXX....XXX
+    ///     XXX...XXXX
+    ///     XXX....XXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::from_str("I,*p4").expect("Failed to parse");
+    ///
+    /// let executor = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// // Excludes the sequences that have S or Z after the I.
+    /// let filter = SequenceFilter::new()
+    ///     .excludes(Pattern::from_str("I,[SZ]").expect("Failed to parse"));
+    /// let results = executor.execute_with_filter(&filter);
+    /// assert_eq!(results.count_keys(), 600);
+    /// assert_eq!(results.count_accepted(), 600);
+    /// ```
+    pub fn execute_with_filter(&self, filter: &SequenceFilter) -> PcResults {
+        let sequences = self.source.to_sequences().into_iter()
+            .filter(|sequence| filter.test(sequence))
+            .collect();
+        self.execute_sequences(sequences, move |_| Continue)
+    }
+
+    fn execute_sequences(&self, sequences: Vec<ShapeSequence>, early_stopping: impl Fn(&PcResults) -> ExecuteInstruction) -> PcResults {
         let mut results = PcResults::new(&sequences);
+        let cut_sequences = CutSequences::new(&sequences, self.max_used_shapes());
 
-        let mut visited_states = FxHashSet::<SearchingState>::default();
+        let mut visited_states = FxHashSet::<SearchingState<Shape>>::default();
 
-        for sequence in sequences {
-            if let Some(_) = results.get(&sequence) {
+        for sequence in &sequences {
+            if let Some(_) = results.get(sequence) {
                 if early_stopping(&results) == ExecuteInstruction::Stop {
                     break;
                 }
@@ -228,16 +589,17 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
 
             visited_states.clear();
 
-            let order = sequence.to_shape_order();
+            let cut_sequence = cut_sequences.cut_off(sequence);
+            let order = cut_sequence.to_shape_order();
             if let Some(sequence_pc) = self.search_pc_order(self.clipped_board, order, &mut visited_states) {
-                results.accept_if_present(&sequence, true);
+                cut_sequences.accept(&mut results, &cut_sequence, true);
 
                 if self.allows_hold {
-                    let mut visitor = Visitor { result: &mut results };
-                    sequence_pc.infer_input_walk(infer_size, &mut visitor);
+                    let mut visitor = Visitor { result: &mut results, cut_sequences: &cut_sequences };
+                    sequence_pc.infer_input_walk(cut_sequence.shapes().len(), &mut visitor);
                 }
             } else {
-                results.accept_if_present(&sequence, false);
+                cut_sequences.accept(&mut results, &cut_sequence, false);
             }
 
             if early_stopping(&results) == ExecuteInstruction::Stop {
@@ -251,19 +613,38 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
     /// This function is dedicated to a single sequence because .
     /// The interface is not directly exposed since it's a shortcut to improve speed.
     pub(crate) fn execute_single(&self) -> bool {
-        let sequences = self.pattern.to_sequences();
+        let sequences = self.source.to_sequences();
         assert_eq!(sequences.len(), 1, "This function is dedicated to a single sequence.");
-        let order = sequences.first().unwrap().to_shape_order();
+        let order = cut_off(sequences.first().unwrap(), self.max_used_shapes()).to_shape_order();
 
-        let mut visited_states = FxHashSet::<SearchingState>::default();
+        let mut visited_states = FxHashSet::<SearchingState<Shape>>::default();
         self.search_pc_order(self.clipped_board, order, &mut visited_states).is_some()
     }
 
+    /// Searches a PC for the order, which is independent of the pattern.
+    /// Returns the sequence of shapes consumed by the PC found.
+    pub(crate) fn search_order(&self, order: ShapeOrder) -> Option<ShapeSequence> {
+        let mut visited_states = FxHashSet::<SearchingState<Shape>>::default();
+        self.search_pc_order(self.clipped_board, order, &mut visited_states)
+    }
+
+    /// Searches a PC for some fill of the unknown shapes in the order, which is independent of the pattern.
+    /// The unknown shapes are filled when they are popped, so the states are shared among the fills.
+    /// Returns the sequence of shapes consumed by the PC found.
+    pub(crate) fn search_fuzzy_order(&self, order: &FuzzyShapeOrder) -> Option<ShapeSequence> {
+        let cursor = order.new_cursor();
+        let mut visited_states = FxHashSet::<SearchingState<FuzzyShape>>::default();
+        let mut buffer = Buffer::with_resized(order.shapes().len());
+        let parity = VerticalParity::new(self.clipped_board);
+
+        self.pop_shape(cursor, self.clipped_board, &mut visited_states, &mut buffer, &parity)
+    }
+
     fn search_pc_order(
         &self,
         current_clipped_board: ClippedBoard,
         order: ShapeOrder,
-        visited_states: &mut FxHashSet::<SearchingState>,
+        visited_states: &mut FxHashSet::<SearchingState<Shape>>,
     ) -> Option<ShapeSequence> {
         let cursor = order.new_cursor();
         let mut buffer = Buffer::with_resized(cursor.len_unused());
@@ -272,18 +653,20 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
         self.pop_shape(cursor, current_clipped_board, visited_states, &mut buffer, &parity)
     }
 
-    fn pop_shape(
+    fn pop_shape<C: SearchCursor>(
         &self,
-        cursor: OrderCursor,
+        cursor: C,
         clipped_board: ClippedBoard,
-        visited_states: &mut FxHashSet::<SearchingState>,
+        visited_states: &mut FxHashSet::<SearchingState<C::Shape>>,
         buffer: &mut Buffer,
         parity: &VerticalParity,
     ) -> Option<ShapeSequence> {
         let (popped, next_cursor) = cursor.pop(PopOp::First);
-        if let Some(shape) = popped {
-            if let Some(order) = self.increment(shape, clipped_board, next_cursor, visited_states, buffer, parity) {
-                return Some(order);
+        if let Some(popped) = popped {
+            for &shape in C::candidates(popped) {
+                if let Some(order) = self.increment(shape, clipped_board, next_cursor, visited_states, buffer, parity) {
+                    return Some(order);
+                }
             }
         } else {
             return None;
@@ -291,9 +674,11 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
 
         if self.allows_hold {
             let (popped, next_cursor) = cursor.pop(PopOp::Second);
-            if let Some(shape) = popped {
-                if let Some(order) = self.increment(shape, clipped_board, next_cursor, visited_states, buffer, parity) {
-                    return Some(order);
+            if let Some(popped) = popped {
+                for &shape in C::candidates(popped) {
+                    if let Some(order) = self.increment(shape, clipped_board, next_cursor, visited_states, buffer, parity) {
+                        return Some(order);
+                    }
                 }
             }
         }
@@ -301,19 +686,19 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
         None
     }
 
-    fn increment(
+    fn increment<C: SearchCursor>(
         &self,
         shape: Shape,
         clipped_board: ClippedBoard,
-        next_cursor: OrderCursor,
-        visited_states: &mut FxHashSet::<SearchingState>,
+        next_cursor: C,
+        visited_states: &mut FxHashSet::<SearchingState<C::Shape>>,
         buffer: &mut Buffer,
         parity: &VerticalParity,
     ) -> Option<ShapeSequence> {
         buffer.increment(shape);
 
-        let placement = shape.with(Orientation::North).with(self.spawn_position);
-        let moves = self.move_rules.generate_minimized_moves(clipped_board.board(), placement);
+        let spawn = shape.with(Orientation::North).with(self.spawn_position);
+        let moves = self.move_rules.generate_minimized_moves(clipped_board.board(), spawn);
 
         for placement in moves {
             if clipped_board.height() as i32 <= placement.tr_placement().position.ty {
@@ -322,6 +707,17 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
 
             let mut board = clipped_board.board();
             let lines_cleared = placement.place_on_and_clear_lines(&mut board).unwrap();
+
+            if self.requires_t_spin && shape == Shape::T {
+                let before = clipped_board.board();
+                let is_t_spin = 0 < lines_cleared.count()
+                    && judge_spin_type(&before, placement).is_some()
+                    && can_reach_by_rotation(self.move_rules, &before, spawn, placement);
+                if !is_t_spin {
+                    continue;
+                }
+            }
+
             if board.is_empty() {
                 return Some(ShapeSequence::new(buffer.as_slice().to_vec()));
             }
@@ -340,12 +736,16 @@ impl<'a, T: RotationSystem> PcPossibleBulkExecutor<'a, T> {
                 continue;
             }
 
-            let shape_order = next_cursor.unused_shapes();
-            let rest_shapes = shape_order.shapes();
             let next_parity = parity.place(placement);
-            // The flag is off if the hold is enabled but does not have an extra piece (because parity is not affected by the shape order)
-            if !next_parity.validates(rest_shapes, 0, self.allows_hold && self.has_extra_shapes) {
-                continue;
+            // The parity is not validated while unknown shapes remain.
+            if let Some(shape_order) = next_cursor.unused_known_shapes() {
+                let rest_shapes = shape_order.shapes();
+                // The flag is off if the hold is enabled but does not have an extra piece (because parity is not affected by the shape order)
+                // It's judged for each order since the sequences may have different lengths.
+                let has_extra_shapes = next_clipped_board.spaces() / 4 < rest_shapes.len() as u32;
+                if !next_parity.validates(rest_shapes, 0, self.allows_hold && has_extra_shapes) {
+                    continue;
+                }
             }
 
             if let Some(order) = self.pop_shape(next_cursor, next_clipped_board, visited_states, buffer, &next_parity) {
@@ -366,7 +766,7 @@ mod tests {
 
     use bitris::{AllowMove, Board64, BoardOp, MoveRules, Shape, xy};
 
-    use crate::{BitShapes, ClippedBoard, Pattern, PatternElement, ShapeCounter, ShapeSequence};
+    use crate::{ClippedBoard, Pattern, PatternElement, SequenceFilter, ShapeCounter, ShapeSequence, WideBitShapes};
     use crate::pc_possible::{PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError};
 
     #[test]
@@ -400,6 +800,119 @@ mod tests {
         assert_eq!(result.get(&ShapeSequence::new(vec![O, O, O])), None);
     }
 
+    #[test]
+    fn same_as_filtered_results() {
+        use Shape::*;
+
+        let board = Board64::from_str("
+            ####....##
+            #####..###
+            ##########
+            #####..###
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::from_str("*p3").unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let executor = PcPossibleBulkExecutor::try_new(
+            &move_rules, clipped_board, &pattern, true,
+        ).unwrap();
+        let all = executor.execute();
+
+        let filter = SequenceFilter::new()
+            .excludes(Pattern::from_str("[SZ]").unwrap())
+            .requires_before(T, O);
+        let filtered = executor.execute_with_filter(&filter);
+        assert_eq!(filtered.count_keys(), pattern.to_sequences_filtered(&filter).len());
+        assert_eq!(filtered.count_pending(), 0);
+        for (sequence, succeed) in filtered.iter() {
+            assert!(filter.test(&sequence));
+            assert_eq!(succeed, all.get(&sequence));
+        }
+    }
+
+    #[test]
+    fn same_as_explicit_sequences() {
+        let board = Board64::from_str("
+            ####....##
+            #####..###
+            ##########
+            #####..###
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let pattern = Pattern::from_str("*p3").unwrap();
+        let all = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+            .unwrap()
+            .execute();
+
+        // Takes every fifth sequence, and the sequences of two shapes have the same results as the first two of them.
+        let mut sequences: Vec<ShapeSequence> = pattern.to_sequences().into_iter().step_by(5).collect();
+        sequences.extend(sequences.iter().take(10).map(|sequence| ShapeSequence::new(sequence.shapes()[..2].to_vec())).collect::<Vec<_>>());
+
+        let executor = PcPossibleBulkExecutor::try_new_with_sequences(&move_rules, clipped_board, &sequences, true).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_keys(), sequences.len());
+        assert_eq!(results.count_pending(), 0);
+        for (sequence, succeed) in results.iter() {
+            if sequence.shapes().len() == 3 {
+                assert_eq!(succeed, all.get(&sequence));
+            }
+        }
+
+        let result = PcPossibleBulkExecutor::try_new_with_sequences(&move_rules, clipped_board, &[], true);
+        assert_eq!(result.unwrap_err(), PcPossibleExecutorBulkCreationError::ShortPatternDimension);
+    }
+
+    #[test]
+    fn long_sequences() {
+        let board = Board64::from_str("
+            ####....##
+            #####..###
+            ##########
+            #####..###
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        // Like a sequence recorded through a game
+        let shapes = (0..100).map(|index| Shape::try_from(index % 7).unwrap()).collect::<Vec<_>>();
+        // The shapes after those used in a PC are not searched, but the results keep the sequences as they are.
+        let sequences = vec![ShapeSequence::new(shapes[..60].to_vec()), ShapeSequence::new(shapes[..10].to_vec())];
+        let results = PcPossibleBulkExecutor::try_new_with_sequences(&move_rules, clipped_board, &sequences, true)
+            .unwrap()
+            .execute();
+        assert_eq!(results.count_keys(), 2);
+        assert_eq!(results.count_accepted(), 2);
+
+        let cut_sequences = vec![ShapeSequence::new(shapes[..3].to_vec())];
+        let expected = PcPossibleBulkExecutor::try_new_with_sequences(&move_rules, clipped_board, &cut_sequences, true)
+            .unwrap()
+            .execute();
+        for sequence in &sequences {
+            assert_eq!(results.get(sequence), expected.get(&cut_sequences[0]));
+        }
+
+        // The sequences over the limit cannot be held as keys.
+        let sequences = vec![ShapeSequence::new(shapes.clone())];
+        assert_eq!(
+            PcPossibleBulkExecutor::try_new_with_sequences(&move_rules, clipped_board, &sequences, true).unwrap_err(),
+            PcPossibleExecutorBulkCreationError::SequenceIsTooLong,
+        );
+
+        // Patterns are checked in the same way.
+
+        let pattern = Pattern::try_from(vec![
+            PatternElement::Fixed(WideBitShapes::try_from(&shapes[..50]).unwrap()),
+            PatternElement::Fixed(WideBitShapes::try_from(&shapes[50..]).unwrap()),
+        ]).unwrap();
+        assert_eq!(
+            PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap_err(),
+            PcPossibleExecutorBulkCreationError::SequenceIsTooLong,
+        );
+    }
+
     #[test]
     fn execute_single() {
         use PatternElement::*;
@@ -415,7 +928,7 @@ mod tests {
 
         {
             let single_pattern = Pattern::try_from(vec![
-                Fixed(BitShapes::try_from(vec![J, O, I]).unwrap()),
+                Fixed(WideBitShapes::try_from(vec![J, O, I]).unwrap()),
             ]).unwrap();
             let executor = PcPossibleBulkExecutor::try_new(
                 &move_rules, clipped_board, &single_pattern, true,
@@ -424,7 +937,7 @@ mod tests {
         }
         {
             let single_pattern = Pattern::try_from(vec![
-                Fixed(BitShapes::try_from(vec![J, T, I]).unwrap()),
+                Fixed(WideBitShapes::try_from(vec![J, T, I]).unwrap()),
             ]).unwrap();
             let executor = PcPossibleBulkExecutor::try_new(
                 &move_rules, clipped_board, &single_pattern, true,
@@ -468,6 +981,18 @@ mod tests {
             PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap_err(),
             PcPossibleExecutorBulkCreationError::ShortPatternDimension,
         );
+
+        // The optional shape can be empty.
+        let pattern = Pattern::from_str("O,[IO]?").unwrap();
+        assert_eq!(
+            PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap_err(),
+            PcPossibleExecutorBulkCreationError::ShortPatternDimension,
+        );
+
+        let pattern = Pattern::from_str("[IO]?,O,O").unwrap();
+        let results = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap().execute();
+        assert_eq!(results.count_keys(), 3);
+        assert_eq!(results.count_succeed(), 3);
     }
 
     #[test]
@@ -493,6 +1018,30 @@ mod tests {
         assert_eq!(result.count_succeed(), 1);
     }
 
+    #[test]
+    fn error_too_many_sequences() {
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        // 5040^3 sequences
+        let pattern = Pattern::from_str("*p7,*p7,*p7").unwrap();
+        assert_eq!(
+            PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap_err(),
+            PcPossibleExecutorBulkCreationError::TooManySequences,
+        );
+
+        // Overflows
+        let pattern = Pattern::from_str(&vec!["*p7"; 11].join(",")).unwrap();
+        assert_eq!(
+            PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap_err(),
+            PcPossibleExecutorBulkCreationError::TooManySequences,
+        );
+
+        // 5040^2 sequences
+        let pattern = Pattern::from_str("*p7,*p7").unwrap();
+        assert!(PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).is_ok());
+    }
+
     #[test]
     fn error_exceeds_the_height_limit() {
         let height: u32 = 57;
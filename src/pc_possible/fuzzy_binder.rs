@@ -0,0 +1,104 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+
+use crate::{ClippedBoard, FuzzyQuantifier, FuzzyShapeOrder};
+use crate::pc_possible::{PcPossibleExecutorCreationError, PcPossibleFuzzyExecutor};
+
+/// The binder to hold and tie settings for `PcPossibleFuzzyExecutor`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcPossibleFuzzyExecutorBinder<T: RotationSystem> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub clipped_board: ClippedBoard,
+    pub fuzzy_order: Rc<FuzzyShapeOrder>,
+    pub allows_hold: bool,
+    pub quantifier: FuzzyQuantifier,
+    pub requires_t_spin: bool,
+}
+
+impl PcPossibleFuzzyExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `PcPossibleFuzzyExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        PcPossibleFuzzyExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem> PcPossibleFuzzyExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + [required] fuzzy_order: empty order. You must set this.
+    ///   + allow move: softdrop
+    ///   + board: blank
+    ///   + height: 4 lines
+    ///   + allows hold: yes
+    ///   + quantifier: every
+    ///   + requires T-spin: no
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
+            fuzzy_order: Rc::from(FuzzyShapeOrder::new(vec![])),
+            allows_hold: true,
+            quantifier: FuzzyQuantifier::Every,
+            requires_t_spin: false,
+        }
+    }
+
+    // See `PcPossibleFuzzyExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<bool, PcPossibleExecutorCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute())
+    }
+
+    fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<PcPossibleFuzzyExecutor<'a, T>, PcPossibleExecutorCreationError> {
+        PcPossibleFuzzyExecutor::try_new(
+            move_rules,
+            self.clipped_board,
+            self.fuzzy_order.as_ref(),
+            self.allows_hold,
+            self.quantifier,
+        ).map(|executor| executor.requires_t_spin(self.requires_t_spin))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, FuzzyQuantifier, FuzzyShapeOrder};
+    use crate::pc_possible::PcPossibleFuzzyExecutorBinder;
+
+    #[test]
+    fn reuse() {
+        let mut binder = PcPossibleFuzzyExecutorBinder::srs();
+        let board = Board64::from_str("
+            ..........
+            ....####..
+            ....######
+            ....######
+        ").unwrap();
+        binder.clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+
+        binder.fuzzy_order = Rc::new(FuzzyShapeOrder::from_str("IOTZSJL").unwrap());
+        assert!(binder.try_execute().unwrap());
+
+        binder.fuzzy_order = Rc::new(FuzzyShapeOrder::from_str("ZSIOLJT").unwrap());
+        assert!(!binder.try_execute().unwrap());
+
+        binder.fuzzy_order = Rc::new(FuzzyShapeOrder::from_str("??IOLJT").unwrap());
+        assert!(!binder.try_execute().unwrap());
+
+        binder.quantifier = FuzzyQuantifier::Some;
+        assert!(binder.try_execute().unwrap());
+    }
+}
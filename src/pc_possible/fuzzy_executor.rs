@@ -0,0 +1,286 @@
+use bitris::prelude::*;
+use fxhash::FxHashSet;
+use itertools::Itertools;
+
+use crate::{ClippedBoard, ForEachVisitor, FuzzyQuantifier, FuzzyShape, FuzzyShapeOrder, Pattern, PatternCreationError, PatternElement, ShapeOrder, ShapeSequence, WideBitShapes};
+use crate::pc_possible::{PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, PcPossibleExecutorCreationError};
+
+/// The executor to find PC possible for an order that includes unknown shapes.
+///
+/// The unknown shapes that cannot be reached before the PC is completed are not filled.
+///
+/// With `FuzzyQuantifier::Some`, the unknown shapes are filled when the search pops them, instead of expanding all `7^k` orders up front.
+/// So one search covers all the fills, and the states already visited are shared among them.
+///
+/// With `FuzzyQuantifier::Every`, the placements can depend on the shapes filled later, so each fill is searched separately.
+/// The search stops as soon as a fill fails.
+/// In addition, if hold is allowed, the fills that can consume the sequence of a PC already found are regarded as succeed without searching.
+/// The fills are held by the shapes in the unknown positions as `WideBitShapes`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcPossibleFuzzyExecutor<'a, T: RotationSystem> {
+    move_rules: &'a MoveRules<'a, T>,
+    clipped_board: ClippedBoard,
+    fuzzy_order: &'a FuzzyShapeOrder,
+    allows_hold: bool,
+    quantifier: FuzzyQuantifier,
+    requires_t_spin: bool,
+
+    // The shapes up to the last one that can be used for a PC.
+    pattern: Pattern,
+}
+
+impl<'a, T: RotationSystem> PcPossibleFuzzyExecutor<'a, T> {
+    /// Make PcPossibleFuzzyExecutor.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `PcPossibleExecutorCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, FuzzyQuantifier, FuzzyShapeOrder};
+    /// use bitris_commands::pc_possible::PcPossibleFuzzyExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     ..........
+    ///     ....XXXX..
+    ///     ....XXXXXX
+    ///     ....XXXXXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// // The last shape is unknown.
+    /// let fuzzy_order = FuzzyShapeOrder::from_str("IOTZS?").expect("Failed to parse");
+    ///
+    /// let executor = PcPossibleFuzzyExecutor::try_new(&move_rules, clipped_board, &fuzzy_order, true, FuzzyQuantifier::Some)
+    ///     .expect("Failed to create an executor");
+    /// assert!(executor.execute());
+    ///
+    /// let executor = PcPossibleFuzzyExecutor::try_new(&move_rules, clipped_board, &fuzzy_order, true, FuzzyQuantifier::Every)
+    ///     .expect("Failed to create an executor");
+    /// assert!(!executor.execute());
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        fuzzy_order: &'a FuzzyShapeOrder,
+        allows_hold: bool,
+        quantifier: FuzzyQuantifier,
+    ) -> Result<Self, PcPossibleExecutorCreationError> {
+        use PcPossibleExecutorBulkCreationError as FromError;
+        use PcPossibleExecutorCreationError as ToError;
+
+        // With hold, one more shape can be used than the count of pieces to fill the spaces.
+        let max_len = (clipped_board.spaces() / 4) as usize + allows_hold as usize;
+        let elements = fuzzy_order.shapes().iter()
+            .take(max_len)
+            .map(|&shape| match shape {
+                FuzzyShape::Known(shape) => PatternElement::One(shape),
+                FuzzyShape::Unknown => PatternElement::Wildcard,
+            })
+            .collect_vec();
+
+        let pattern = match Pattern::try_from(elements) {
+            Ok(pattern) => pattern,
+            Err(error) => return match error {
+                PatternCreationError::NoShapeSequences => Err(ToError::ShortOrderDimension),
+                PatternCreationError::ContainsInvalidPermutation => panic!("Unreachable assumption"),
+            },
+        };
+
+        // Validates the settings in the same way. The pattern is not enumerated, so the count is not limited.
+        if let Err(error) = PcPossibleBulkExecutor::try_new_without_limit(move_rules, clipped_board, &pattern, allows_hold) {
+            return Err(match error {
+                FromError::UnexpectedBoardSpaces => ToError::UnexpectedBoardSpaces,
+                FromError::ShortPatternDimension => ToError::ShortOrderDimension,
+                FromError::BoardIsTooHigh => ToError::BoardIsTooHigh,
+                FromError::TooManySequences | FromError::SequenceIsTooLong => panic!("Unreachable assumption"),
+            });
+        }
+
+        Ok(Self { move_rules, clipped_board, fuzzy_order, allows_hold, quantifier, requires_t_spin: false, pattern })
+    }
+
+    /// Requires every T to be placed with a T-spin that clears lines.
+    /// See `PcPossibleBulkExecutor::requires_t_spin()` for more details.
+    #[inline]
+    pub fn requires_t_spin(self, requires_t_spin: bool) -> Self {
+        Self { requires_t_spin, ..self }
+    }
+
+    /// Start the search for PC possible.
+    /// Returns true if a PC is possible for every (or some, by the quantifier) fill of the unknown shapes.
+    pub fn execute(&self) -> bool {
+        let executor = PcPossibleBulkExecutor::try_new_without_limit(self.move_rules, self.clipped_board, &self.pattern, self.allows_hold)
+            .expect("The settings must be validated")
+            .requires_t_spin(self.requires_t_spin);
+
+        let fuzzy_shapes = &self.fuzzy_order.shapes()[..self.pattern.dim_shapes()];
+        match self.quantifier {
+            FuzzyQuantifier::Every => {
+                let mut buffer = Vec::<Shape>::with_capacity(fuzzy_shapes.len());
+                let mut covered = FxHashSet::<WideBitShapes>::default();
+                self.fill_every(&executor, fuzzy_shapes, &mut buffer, &mut covered)
+            }
+            FuzzyQuantifier::Some => {
+                executor.search_fuzzy_order(&FuzzyShapeOrder::new(fuzzy_shapes.to_vec())).is_some()
+            }
+        }
+    }
+
+    /// Fills the unknown shapes in order, and searches each fill.
+    /// Returns false as soon as a fill fails.
+    fn fill_every(
+        &self,
+        executor: &PcPossibleBulkExecutor<T>,
+        fuzzy_shapes: &[FuzzyShape],
+        buffer: &mut Vec<Shape>,
+        covered: &mut FxHashSet<WideBitShapes>,
+    ) -> bool {
+        let index = buffer.len();
+        if fuzzy_shapes.len() <= index {
+            if to_fill(fuzzy_shapes, buffer).is_some_and(|fill| covered.contains(&fill)) {
+                return true;
+            }
+
+            return match executor.search_order(ShapeOrder::new(buffer.clone())) {
+                Some(sequence) => {
+                    let mut visitor = Visitor { fuzzy_shapes, covered };
+                    for order in self.to_covered_orders(&sequence, fuzzy_shapes.len()) {
+                        order.expand_as_wildcard_walk(&mut visitor);
+                    }
+                    true
+                }
+                None => false,
+            };
+        }
+
+        let shapes = match fuzzy_shapes[index] {
+            FuzzyShape::Known(shape) => vec![shape],
+            FuzzyShape::Unknown => Shape::all_into_iter().collect_vec(),
+        };
+
+        for shape in shapes {
+            buffer.push(shape);
+            let succeed = self.fill_every(executor, fuzzy_shapes, buffer, covered);
+            buffer.pop();
+
+            if !succeed {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Returns the orders that can consume the sequence, that is, the orders that can take the same PC.
+    fn to_covered_orders(&self, sequence: &ShapeSequence, len: usize) -> Vec<FuzzyShapeOrder> {
+        if self.allows_hold {
+            return sequence.infer_input(len);
+        }
+
+        let mut shapes = sequence.shapes().iter().map(|&shape| FuzzyShape::Known(shape)).collect_vec();
+        shapes.resize(len, FuzzyShape::Unknown);
+        vec![FuzzyShapeOrder::new(shapes)]
+    }
+}
+
+/// Returns the shapes in the unknown positions, that is, the fill.
+/// Returns `None` if the known shapes do not match, or the fill is too long to hold.
+fn to_fill(fuzzy_shapes: &[FuzzyShape], shapes: &[Shape]) -> Option<WideBitShapes> {
+    if fuzzy_shapes.len() != shapes.len() {
+        return None;
+    }
+
+    let mut fill = Vec::with_capacity(shapes.len());
+    for (&fuzzy_shape, &shape) in fuzzy_shapes.iter().zip(shapes) {
+        match fuzzy_shape {
+            FuzzyShape::Known(known) if known != shape => return None,
+            FuzzyShape::Known(_) => {}
+            FuzzyShape::Unknown => fill.push(shape),
+        }
+    }
+    WideBitShapes::try_from(&fill).ok()
+}
+
+/// Collects the fills covered by a PC already found.
+struct Visitor<'a> {
+    fuzzy_shapes: &'a [FuzzyShape],
+    covered: &'a mut FxHashSet<WideBitShapes>,
+}
+
+impl ForEachVisitor<[Shape]> for Visitor<'_> {
+    #[inline]
+    fn visit(&mut self, shapes: &[Shape]) {
+        if let Some(fill) = to_fill(self.fuzzy_shapes, shapes) {
+            self.covered.insert(fill);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, FuzzyQuantifier, FuzzyShape, FuzzyShapeOrder, Pattern, PatternElement};
+    use crate::pc_possible::{PcPossibleBulkExecutor, PcPossibleExecutorCreationError, PcPossibleFuzzyExecutor};
+
+    #[test]
+    fn same_as_bulk() {
+        let board = Board64::from_str("
+            ..........
+            ....####..
+            ....######
+            ....######
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        for text in ["IOTZSJL", "IO?ZS?L", "??TZSJ", "ZSIOLJ?", "?I?Z?J", "???OTZS"] {
+            let fuzzy_order = FuzzyShapeOrder::from_str(text).unwrap();
+            let pattern = Pattern::try_from(fuzzy_order.shapes().iter()
+                .map(|&shape| match shape {
+                    FuzzyShape::Known(shape) => PatternElement::One(shape),
+                    FuzzyShape::Unknown => PatternElement::Wildcard,
+                })
+                .collect::<Vec<_>>()
+            ).unwrap();
+
+            for allows_hold in [true, false] {
+                let results = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold)
+                    .unwrap()
+                    .execute();
+
+                for (quantifier, expected) in [
+                    (FuzzyQuantifier::Every, results.count_succeed() == results.count_keys() as u64),
+                    (FuzzyQuantifier::Some, 0 < results.count_succeed()),
+                ] {
+                    let executor = PcPossibleFuzzyExecutor::try_new(
+                        &move_rules, clipped_board, &fuzzy_order, allows_hold, quantifier,
+                    ).unwrap();
+                    assert_eq!(executor.execute(), expected, "{} {} {:?}", text, allows_hold, quantifier);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn error() {
+        use PcPossibleExecutorCreationError::*;
+
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let fuzzy_order = FuzzyShapeOrder::from_str("???").unwrap();
+        let result = PcPossibleFuzzyExecutor::try_new(&move_rules, clipped_board, &fuzzy_order, true, FuzzyQuantifier::Some);
+        assert_eq!(result.unwrap_err(), ShortOrderDimension);
+
+        let fuzzy_order = FuzzyShapeOrder::new(vec![]);
+        let result = PcPossibleFuzzyExecutor::try_new(&move_rules, clipped_board, &fuzzy_order, true, FuzzyQuantifier::Some);
+        assert_eq!(result.unwrap_err(), ShortOrderDimension);
+    }
+}
@@ -2,12 +2,24 @@ pub use binder::*;
 pub(crate) use buffer::*;
 pub use bulk_binder::*;
 pub use bulk_executor::*;
+pub use fuzzy_binder::*;
+pub use fuzzy_executor::*;
+pub use online_binder::*;
+pub use online_executor::*;
 pub use pc_results::*;
+pub use pc_results_io::*;
+pub use results_cache::*;
 pub(crate) use vertical_parity::*;
 
 mod binder;
 mod buffer;
 mod bulk_binder;
 mod bulk_executor;
+mod fuzzy_binder;
+mod fuzzy_executor;
+mod online_binder;
+mod online_executor;
 mod pc_results;
+mod pc_results_io;
+mod results_cache;
 mod vertical_parity;
@@ -0,0 +1,104 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+
+use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+use crate::pc_possible::{PcPossibleExecutorBulkCreationError, PcPossibleOnlineExecutor};
+
+/// The binder to hold and tie settings for `PcPossibleOnlineExecutor`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcPossibleOnlineExecutorBinder<T: RotationSystem> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub clipped_board: ClippedBoard,
+    pub pattern: Rc<Pattern>,
+    pub allows_hold: bool,
+    pub preview: usize,
+}
+
+impl PcPossibleOnlineExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `PcPossibleOnlineExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        PcPossibleOnlineExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem> PcPossibleOnlineExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + allow move: softdrop
+    ///   + board: blank
+    ///   + height: 4 lines
+    ///   + pattern: factorial of all shapes (like `*p7`)
+    ///   + allows hold: yes
+    ///   + preview: 5 shapes
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
+            pattern: Rc::from(Pattern::try_from(vec![
+                PatternElement::Factorial(ShapeCounter::one_of_each()),
+            ]).unwrap()),
+            allows_hold: true,
+            preview: 5,
+        }
+    }
+
+    // See `PcPossibleOnlineExecutor::{try_new, guarantees}` for more details.
+    pub fn try_guarantees(&self) -> Result<bool, PcPossibleExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.guarantees())
+    }
+
+    // See `PcPossibleOnlineExecutor::{try_new, max_success_rate}` for more details.
+    pub fn try_max_success_rate(&self) -> Result<f64, PcPossibleExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.max_success_rate())
+    }
+
+    fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<PcPossibleOnlineExecutor<'a, T>, PcPossibleExecutorBulkCreationError> {
+        PcPossibleOnlineExecutor::try_new(
+            move_rules,
+            self.clipped_board,
+            self.pattern.as_ref(),
+            self.allows_hold,
+            self.preview,
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, Pattern};
+    use crate::pc_possible::PcPossibleOnlineExecutorBinder;
+
+    #[test]
+    fn reuse() {
+        let mut binder = PcPossibleOnlineExecutorBinder::srs();
+        let board = Board64::from_str("
+            XXXXX.....
+            XXXXXX....
+            XXXXXXX...
+        ").unwrap();
+        binder.clipped_board = ClippedBoard::try_new(board, 3).unwrap();
+        binder.pattern = Rc::new(Pattern::from_str("*p3").unwrap());
+
+        let rate = binder.try_max_success_rate().unwrap();
+        assert!(!binder.try_guarantees().unwrap());
+
+        binder.preview = 0;
+        assert!(binder.try_max_success_rate().unwrap() < rate);
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::BTreeMap;
+
+use bitris::prelude::*;
+use fxhash::FxHashMap;
+
+use crate::{ClippedBoard, OrderCursor, Pattern, PopOp, ShapeOrder};
+use crate::pc_possible::bulk_executor::{check_enumerable, validate_board};
+use crate::pc_possible::PcPossibleExecutorBulkCreationError;
+
+/// Dataset for detecting the same state during online search.
+/// The revealed shapes determine the sequences that can still come, so they are part of the state.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+struct SearchingState {
+    // The board does not include filled rows.
+    board: Board64,
+    height: u32,
+
+    head: Option<Shape>,
+    index_of_second: usize,
+    hold_used: bool,
+    revealed: Vec<Shape>,
+}
+
+/// The executor to analyze PC possibles when the player sees only a limited number of next shapes (online).
+///
+/// Each decision (which shape to place and where) may depend only on the current shape, the hold, and the next `preview` shapes.
+/// After each placement, the next shape is revealed from the sequences of the pattern that match the shapes revealed so far.
+/// All sequences of the pattern are regarded as equally likely.
+/// If the sequences have different lengths (like `*p3,[SZ]?`), the end of a sequence is revealed in the same way as the shapes.
+///
+/// With hold, the shape in the hold is known in addition to the preview.
+/// When the hold is empty, holding the current shape is also a decision, and the next shape is revealed after it.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct PcPossibleOnlineExecutor<'a, T: RotationSystem> {
+    move_rules: &'a MoveRules<'a, T>,
+    clipped_board: ClippedBoard,
+    pattern: &'a Pattern,
+    allows_hold: bool,
+    preview: usize,
+    spawn_position: BlPosition,
+}
+
+impl<'a, T: RotationSystem> PcPossibleOnlineExecutor<'a, T> {
+    /// Make PcPossibleOnlineExecutor.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `PcPossibleExecutorBulkCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern};
+    /// use bitris_commands::pc_possible::{PcPossibleBulkExecutor, PcPossibleOnlineExecutor};
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXXXX.....
+    ///     XXXXXX....
+    ///     XXXXXXX...
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 3).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::from_str("*p3").expect("Failed to parse");
+    ///
+    /// // If all shapes are known in advance.
+    /// let results = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor")
+    ///     .execute();
+    /// let rate = results.count_succeed() as f64 / results.count_keys() as f64;
+    ///
+    /// // If no next shape is visible, some decisions cannot be made well.
+    /// let executor = PcPossibleOnlineExecutor::try_new(&move_rules, clipped_board, &pattern, true, 0)
+    ///     .expect("Failed to create an executor");
+    /// assert!(executor.max_success_rate() < rate);
+    /// assert!(!executor.guarantees());
+    ///
+    /// // In this case, one next shape is enough.
+    /// let executor = PcPossibleOnlineExecutor::try_new(&move_rules, clipped_board, &pattern, true, 1)
+    ///     .expect("Failed to create an executor");
+    /// assert!((executor.max_success_rate() - rate).abs() < 1e-9);
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+        preview: usize,
+    ) -> Result<Self, PcPossibleExecutorBulkCreationError> {
+        use PcPossibleExecutorBulkCreationError::*;
+
+        if 56 < clipped_board.height() {
+            return Err(BoardIsTooHigh);
+        }
+
+        if clipped_board.spaces() % 4 != 0 {
+            return Err(UnexpectedBoardSpaces);
+        }
+
+        if (pattern.min_dim_shapes() as u32) < clipped_board.spaces() / 4 {
+            return Err(ShortPatternDimension);
+        }
+
+        // The sequences are enumerated in the same way as the bulk executor.
+        check_enumerable(std::slice::from_ref(pattern))?;
+
+        debug_assert!(0 < clipped_board.spaces());
+
+        // Spawn above the height so that it is not stuck when harddrop only.
+        let spawn_position = bl(5, clipped_board.height() as i32 + 4);
+
+        Ok(Self { move_rules, clipped_board, pattern, allows_hold, preview, spawn_position })
+    }
+
+    /// Returns true if a strategy exists that takes a PC for every sequence in the pattern.
+    pub fn guarantees(&self) -> bool {
+        self.evaluate_root(true) == 1.0
+    }
+
+    /// Returns the success rate of PC with the best strategy.
+    /// If all shapes are visible, it's the same as the rate of `PcPossibleBulkExecutor`.
+    pub fn max_success_rate(&self) -> f64 {
+        self.evaluate_root(false)
+    }
+
+    fn evaluate_root(&self, requires_all: bool) -> f64 {
+        let orders: Vec<ShapeOrder> = self.pattern.to_sequences().iter()
+            .map(|sequence| sequence.to_shape_order())
+            .collect();
+        let candidates: Vec<usize> = (0..orders.len()).collect();
+
+        // Nothing is revealed at the start.
+        let cursor = orders[0].new_cursor();
+        let mut memo = FxHashMap::<SearchingState, f64>::default();
+        self.reveal(self.clipped_board, cursor, false, 0, &orders, &candidates, requires_all, 0.0, &mut memo)
+    }
+
+    fn to_visible(&self, cursor: &OrderCursor, hold_used: bool, len: usize) -> usize {
+        (cursor.index_of_second() + self.preview + hold_used as usize).min(len)
+    }
+
+    /// Returns the success rate from the state. All candidates have the same shapes up to `visible`, and the first is the longest.
+    /// If `requires_all` is true, the strategies that fail for any candidate are skipped, so it returns 0 or 1.
+    #[allow(clippy::too_many_arguments)]
+    fn evaluate(
+        &self,
+        clipped_board: ClippedBoard,
+        cursor: OrderCursor,
+        hold_used: bool,
+        visible: usize,
+        orders: &[ShapeOrder],
+        candidates: &[usize],
+        requires_all: bool,
+        memo: &mut FxHashMap<SearchingState, f64>,
+    ) -> f64 {
+        let representative = &orders[candidates[0]];
+        let state = SearchingState {
+            board: clipped_board.board(),
+            height: clipped_board.height(),
+            head: cursor.first(),
+            index_of_second: cursor.index_of_second(),
+            hold_used,
+            revealed: representative.shapes()[..visible].to_vec(),
+        };
+        if let Some(&rate) = memo.get(&state) {
+            return rate;
+        }
+
+        let mut best = 0.0;
+
+        // Holds the current shape when the hold is empty. Then, the next shape becomes the current, and it's revealed.
+        if self.allows_hold && !hold_used && cursor.second().is_some() {
+            best = self.reveal(clipped_board, cursor, true, visible, orders, candidates, requires_all, best, memo);
+        }
+
+        // If the hold is empty, only the current shape (the first) can be placed.
+        // Otherwise, the first is the shape in the hold, and the second is the current shape.
+        let ops: &[PopOp] = if hold_used { &[PopOp::First, PopOp::Second] } else { &[PopOp::First] };
+        'search: for &op in ops {
+            if 1.0 <= best {
+                break;
+            }
+
+            let (popped, next_cursor) = cursor.pop(op);
+            let Some(shape) = popped else {
+                continue;
+            };
+
+            let placement = shape.with(Orientation::North).with(self.spawn_position);
+            let moves = self.move_rules.generate_minimized_moves(clipped_board.board(), placement);
+
+            for placement in moves {
+                if clipped_board.height() as i32 <= placement.tr_placement().position.ty {
+                    continue;
+                }
+
+                let mut board = clipped_board.board();
+                let lines_cleared = placement.place_on_and_clear_lines(&mut board).unwrap();
+
+                if board.is_empty() {
+                    best = 1.0;
+                    break 'search;
+                }
+
+                let height = clipped_board.height() - lines_cleared.count();
+                let next_clipped_board = ClippedBoard::new_unsafe(board, height);
+                if (next_cursor.len_unused() as u32) * 4 < next_clipped_board.spaces() {
+                    continue;
+                }
+
+                if !validate_board(&next_clipped_board) {
+                    continue;
+                }
+
+                let rate = self.reveal(next_clipped_board, next_cursor, hold_used, visible, orders, candidates, requires_all, best, memo);
+                if best < rate {
+                    best = rate;
+                    if 1.0 <= best {
+                        break 'search;
+                    }
+                }
+            }
+        }
+
+        memo.insert(state, best);
+        best
+    }
+
+    /// Reveals the next shapes after the operation, and returns the success rate over them.
+    /// Stops early if the rate cannot exceed `best`, in which case the returned rate is not accurate.
+    #[allow(clippy::too_many_arguments)]
+    fn reveal(
+        &self,
+        clipped_board: ClippedBoard,
+        cursor: OrderCursor,
+        hold_used: bool,
+        visible: usize,
+        orders: &[ShapeOrder],
+        candidates: &[usize],
+        requires_all: bool,
+        best: f64,
+        memo: &mut FxHashMap<SearchingState, f64>,
+    ) -> f64 {
+        // The sequences may have different lengths (like `*p3,[SZ]?`), and the end of a sequence is also revealed.
+        let mut groups = BTreeMap::<&[Shape], Vec<usize>>::new();
+        for &candidate in candidates {
+            let shapes = orders[candidate].shapes();
+            let next_visible = self.to_visible(&cursor, hold_used, shapes.len());
+            groups.entry(&shapes[visible..next_visible]).or_default().push(candidate);
+        }
+
+        // The longest one represents the group, so that the cursor does not run out of the shapes that the others have.
+        for group in groups.values_mut() {
+            let longest = (0..group.len()).rev()
+                .max_by_key(|&index| orders[group[index]].shapes().len())
+                .unwrap();
+            group.swap(0, longest);
+        }
+
+        // Sums the rates weighted by the count of candidates to reduce the error.
+        let total = candidates.len() as f64;
+        let mut sum = 0.0;
+        let mut rest = total;
+        for (revealed, group) in &groups {
+            let group_rate = self.evaluate(
+                clipped_board, cursor.rebind(&orders[group[0]]), hold_used, visible + revealed.len(), orders, group, requires_all, memo,
+            );
+
+            if requires_all && group_rate < 1.0 {
+                return 0.0;
+            }
+
+            sum += group.len() as f64 * group_rate;
+            rest -= group.len() as f64;
+
+            // Even if all the rest succeed, it does not exceed the best.
+            if (sum + rest) / total <= best {
+                break;
+            }
+        }
+
+        if requires_all {
+            return 1.0;
+        }
+        sum / total
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, Pattern};
+    use crate::pc_possible::{PcPossibleBulkExecutor, PcPossibleExecutorBulkCreationError, PcPossibleOnlineExecutor};
+
+    #[test]
+    fn full_preview_is_same_as_bulk() {
+        let board = Board64::from_str("
+            XXXXX.....
+            XXXXXX....
+            XXXXXXX...
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 3).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let pattern = Pattern::from_str("*p4").unwrap();
+
+        for allows_hold in [true, false] {
+            let results = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold)
+                .unwrap()
+                .execute();
+            let expected = results.count_succeed() as f64 / results.count_keys() as f64;
+
+            let executor = PcPossibleOnlineExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold, 4).unwrap();
+            assert!((executor.max_success_rate() - expected).abs() < 1e-9);
+            assert!(!executor.guarantees());
+
+            // Less information never increases the rate.
+            let mut last = executor.max_success_rate();
+            for preview in (0..4).rev() {
+                let executor = PcPossibleOnlineExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold, preview).unwrap();
+                let rate = executor.max_success_rate();
+                assert!(rate <= last + 1e-9);
+                last = rate;
+            }
+        }
+    }
+
+    #[test]
+    fn optional_shapes() {
+        let board = Board64::from_str("
+            XXXXX.....
+            XXXXXX....
+            XXXXXXX...
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 3).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let pattern = Pattern::from_str("*p3,[SZ]?").unwrap();
+
+        for allows_hold in [true, false] {
+            let results = PcPossibleBulkExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold)
+                .unwrap()
+                .execute();
+            assert_eq!(results.count_keys(), 210 * 3);
+            let expected = results.count_succeed() as f64 / results.count_keys() as f64;
+
+            // The 4th shape is revealed for the sequences that have it.
+            let executor = PcPossibleOnlineExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold, 3).unwrap();
+            assert!((executor.max_success_rate() - expected).abs() < 1e-9);
+
+            let mut last = executor.max_success_rate();
+            for preview in (0..3).rev() {
+                let executor = PcPossibleOnlineExecutor::try_new(&move_rules, clipped_board, &pattern, allows_hold, preview).unwrap();
+                let rate = executor.max_success_rate();
+                assert!(rate <= last + 1e-9);
+                last = rate;
+            }
+        }
+    }
+
+    #[test]
+    fn guarantees() {
+        let board = Board64::from_str("
+            XXXX......
+            XXXX......
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let pattern = Pattern::from_str("[IOLJ]!").unwrap();
+        let executor = PcPossibleOnlineExecutor::try_new(&move_rules, clipped_board, &pattern, true, 0).unwrap();
+        assert!(executor.guarantees());
+        assert_eq!(executor.max_success_rate(), 1.0);
+
+        let pattern = Pattern::from_str("[IOLJ]p3").unwrap();
+        let executor = PcPossibleOnlineExecutor::try_new(&move_rules, clipped_board, &pattern, true, 2).unwrap();
+        assert!(!executor.guarantees());
+        assert!((executor.max_success_rate() - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn error() {
+        use PcPossibleExecutorBulkCreationError::*;
+
+        let board = Board64::from_str("
+            XXXXXX...X
+            XXXXXX....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 2).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let pattern = Pattern::from_str("*p2").unwrap();
+
+        let result = PcPossibleOnlineExecutor::try_new(&move_rules, clipped_board, &pattern, true, 1);
+        assert_eq!(result.unwrap_err(), UnexpectedBoardSpaces);
+    }
+}
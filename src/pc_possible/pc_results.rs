@@ -1,8 +1,7 @@
-use std::collections::hash_map::{IntoIter, Iter};
+use bitvec::vec::BitVec;
 
-use fxhash::FxHashMap;
-
-use crate::ShapeSequence;
+use crate::{Mirror, Randomizer, ShapeSequence, WideBitShapes};
+use crate::internals::SequenceKeys;
 
 /// Holds the results of Perfect Clears.
 ///
@@ -12,26 +11,48 @@ use crate::ShapeSequence;
 /// * Pending: `None`
 ///
 /// Therefore, the shape sequences to be searched (key) are established at `new()`.
+/// They are kept in the order given at `new()` without duplicates, so iteration is deterministic.
+/// When made from a pattern without duplicate sequences, the indices correspond to the positions of `Pattern::to_sequences()`.
+///
+/// Internally, the keys are stored once as `BitShapes`, or as `WideBitShapes` only if some shape sequences have more than 22 shapes.
+/// The states are stored as bits, so it stays compact for millions of sequences.
+/// Lookups are binary searches over the key positions sorted by key, so lookups and counts do not allocate.
+/// Only `iter_keys()` iterates without allocating, since the other iterators make a `ShapeSequence` for every entry.
 #[derive(Clone, PartialEq, Default, Debug)]
 pub struct PcResults {
-    succeed: FxHashMap<ShapeSequence, Option<bool>>,
+    keys: SequenceKeys,
+    accepted: BitVec,
+    succeed: BitVec,
+    count_accepted: u64,
+    count_succeed: u64,
 }
 
 impl PcResults {
+    /// Panics if a shape sequence has more shapes than `WideBitShapes` can hold.
     #[inline]
-    pub fn new(sequences: &Vec<ShapeSequence>) -> Self {
-        let mut succeed = FxHashMap::<ShapeSequence, Option<bool>>::default();
-        succeed.reserve(sequences.len());
-        for order in sequences {
-            succeed.insert(order.clone(), None);
+    pub fn new(sequences: &[ShapeSequence]) -> Self {
+        let keys = SequenceKeys::new(sequences.iter());
+        let len = keys.len();
+        Self {
+            keys,
+            accepted: BitVec::repeat(false, len),
+            succeed: BitVec::repeat(false, len),
+            count_accepted: 0,
+            count_succeed: 0,
         }
-        Self { succeed }
     }
 
     #[inline]
     pub fn accept_if_present(&mut self, sequence: &ShapeSequence, succeed: bool) -> bool {
-        if let Some(_) = self.succeed.get(&sequence) {
-            self.succeed.insert(sequence.clone(), Some(succeed));
+        if let Some(index) = self.index_of(sequence) {
+            if self.accepted[index] {
+                self.count_succeed -= self.succeed[index] as u64;
+            } else {
+                self.accepted.set(index, true);
+                self.count_accepted += 1;
+            }
+            self.succeed.set(index, succeed);
+            self.count_succeed += succeed as u64;
             true
         } else {
             false
@@ -41,7 +62,56 @@ impl PcResults {
     #[inline]
     #[allow(dead_code)]
     pub(crate) fn contains_key(&self, order: &ShapeSequence) -> bool {
-        self.succeed.contains_key(order)
+        self.index_of(order).is_some()
+    }
+
+    #[inline]
+    fn state_at(&self, index: usize) -> Option<bool> {
+        if self.accepted[index] {
+            Some(self.succeed[index])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the index of a shape sequence in the order given at `new()`.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let result = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec!(I, T, S)),
+    ///     ShapeSequence::new(vec!(I, T, Z)),
+    /// ]);
+    ///
+    /// assert_eq!(result.index_of(&ShapeSequence::new(vec!(I, T, Z))), Some(1));
+    /// assert_eq!(result.index_of(&ShapeSequence::new(vec!(I, T, O))), None);
+    /// ```
+    #[inline]
+    pub fn index_of(&self, sequence: &ShapeSequence) -> Option<usize> {
+        self.keys.position(sequence)
+    }
+
+    /// Returns the pair of shape sequence and result at the index.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let mut result = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec!(I, T, S)),
+    ///     ShapeSequence::new(vec!(I, T, Z)),
+    /// ]);
+    /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), true);
+    ///
+    /// assert_eq!(result.get_by_index(0), Some((ShapeSequence::new(vec!(I, T, S)), None)));
+    /// assert_eq!(result.get_by_index(1), Some((ShapeSequence::new(vec!(I, T, Z)), Some(true))));
+    /// assert_eq!(result.get_by_index(2), None);
+    /// ```
+    #[inline]
+    pub fn get_by_index(&self, index: usize) -> Option<(ShapeSequence, Option<bool>)> {
+        self.keys.get(index).map(|key| (ShapeSequence::from(key), self.state_at(index)))
     }
 
     /// Returns the result of a shape sequence.
@@ -65,12 +135,11 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn get(&self, sequence: &ShapeSequence) -> Option<bool> {
-        self.succeed.get(sequence).map(|it| *it).unwrap_or(None)
+        self.index_of(sequence).and_then(|index| self.state_at(index))
     }
 
-    /// Returns accepted shape sequence. The order of the shape sequences is undefined.
+    /// Returns accepted shape sequence in the order given at `new()`.
     /// ```
-    /// use itertools::Itertools;
     /// use bitris_commands::prelude::*;
     /// use bitris_commands::pc_possible::PcResults;
     /// use Shape::*;
@@ -85,25 +154,22 @@ impl PcResults {
     /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), false);
     ///
     /// assert_eq!(
-    ///     // The order of the shape sequences is undefined.
-    ///     result.accepted_shape_sequences().into_iter().sorted().collect_vec(),
+    ///     result.accepted_shape_sequences(),
     ///     vec![
-    ///         &ShapeSequence::new(vec!(I, T, S)),
-    ///         &ShapeSequence::new(vec!(I, T, Z)),
+    ///         ShapeSequence::new(vec!(I, T, S)),
+    ///         ShapeSequence::new(vec!(I, T, Z)),
     ///     ],
     /// );
     /// ```
     #[inline]
-    pub fn accepted_shape_sequences(&self) -> Vec<&ShapeSequence> {
-        self.succeed.iter()
-            .filter(|(_, value)| value.is_some())
-            .map(|(key, _)| key)
+    pub fn accepted_shape_sequences(&self) -> Vec<ShapeSequence> {
+        self.accepted.iter_ones()
+            .map(|index| self.keys.sequence_at(index))
             .collect()
     }
 
-    /// Returns the pair of shape sequence and result. The order of the shape sequences is undefined.
+    /// Returns the pair of shape sequence and result in the order given at `new()`.
     /// ```
-    /// use itertools::Itertools;
     /// use bitris_commands::prelude::*;
     /// use bitris_commands::pc_possible::PcResults;
     /// use Shape::*;
@@ -118,23 +184,47 @@ impl PcResults {
     /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), false);
     ///
     /// assert_eq!(
-    ///     // The order of the shape sequences is undefined.
-    ///     result.iter().sorted().collect_vec(),
+    ///     result.iter().collect::<Vec<_>>(),
+    ///     vec![
+    ///         (ShapeSequence::new(vec!(I, T, S)), Some(true)),
+    ///         (ShapeSequence::new(vec!(I, T, Z)), Some(false)),
+    ///         (ShapeSequence::new(vec!(I, T, O)), None),
+    ///     ],
+    /// );
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(ShapeSequence, Option<bool>)> + '_ {
+        self.iter_keys().map(|(key, state)| (ShapeSequence::from(key), state))
+    }
+
+    /// Same as `iter()`, but the shape sequences are returned as `WideBitShapes`, so it does not allocate.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let mut result = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec!(I, T, S)),
+    ///     ShapeSequence::new(vec!(I, T, Z)),
+    /// ]);
+    ///
+    /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), true);
+    ///
+    /// assert_eq!(
+    ///     result.iter_keys().collect::<Vec<_>>(),
     ///     vec![
-    ///         (&ShapeSequence::new(vec!(I, T, O)), &None),
-    ///         (&ShapeSequence::new(vec!(I, T, S)), &Some(true)),
-    ///         (&ShapeSequence::new(vec!(I, T, Z)), &Some(false)),
+    ///         (WideBitShapes::try_from(vec!(I, T, S)).unwrap(), None),
+    ///         (WideBitShapes::try_from(vec!(I, T, Z)).unwrap(), Some(true)),
     ///     ],
     /// );
     /// ```
     #[inline]
-    pub fn iter(&self) -> Iter<'_, ShapeSequence, Option<bool>> {
-        self.succeed.iter()
+    pub fn iter_keys(&self) -> impl Iterator<Item=(WideBitShapes, Option<bool>)> + '_ {
+        (0..self.keys.len()).map(|index| (self.keys.key_at(index), self.state_at(index)))
     }
 
-    /// Returns the pair of shape sequence and result. The order of the shape sequences is undefined.
+    /// Returns the pair of shape sequence and result in the order given at `new()`.
     /// ```
-    /// use itertools::Itertools;
     /// use bitris_commands::prelude::*;
     /// use bitris_commands::pc_possible::PcResults;
     /// use Shape::*;
@@ -149,18 +239,17 @@ impl PcResults {
     /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), false);
     ///
     /// assert_eq!(
-    ///     // The order of the shape sequences is undefined.
-    ///     result.into_iter().sorted().collect_vec(),
+    ///     result.into_iter().collect::<Vec<_>>(),
     ///     vec![
-    ///         (ShapeSequence::new(vec!(I, T, O)), None),
     ///         (ShapeSequence::new(vec!(I, T, S)), Some(true)),
     ///         (ShapeSequence::new(vec!(I, T, Z)), Some(false)),
+    ///         (ShapeSequence::new(vec!(I, T, O)), None),
     ///     ],
     /// );
     /// ```
     #[inline]
-    pub fn into_iter(self) -> IntoIter<ShapeSequence, Option<bool>> {
-        self.succeed.into_iter()
+    pub fn into_iter(self) -> impl Iterator<Item=(ShapeSequence, Option<bool>)> {
+        (0..self.keys.len()).map(move |index| (self.keys.sequence_at(index), self.state_at(index)))
     }
 
     /// Returns the count of shape sequences found to be succeed.
@@ -182,9 +271,7 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_succeed(&self) -> u64 {
-        self.succeed.values()
-            .filter(|value| value.unwrap_or(false))
-            .count() as u64
+        self.count_succeed
     }
 
     /// Returns the count of shape sequences found to be failed.
@@ -206,9 +293,7 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_failed(&self) -> u64 {
-        self.succeed.values()
-            .filter(|value| value.map(|flag| !flag).unwrap_or(false))
-            .count() as u64
+        self.count_accepted - self.count_succeed
     }
 
     /// Returns the count of shape sequences for which results were found.
@@ -232,9 +317,7 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_accepted(&self) -> u64 {
-        self.succeed.values()
-            .filter(|value| value.is_some())
-            .count() as u64
+        self.count_accepted
     }
 
     /// Returns the count of shape sequences for which results are not yet found.
@@ -258,9 +341,7 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_pending(&self) -> u64 {
-        self.succeed.values()
-            .filter(|value| value.is_none())
-            .count() as u64
+        self.keys.len() as u64 - self.count_accepted
     }
 
     /// Return the count of all shape sequences independent of the result.
@@ -280,7 +361,78 @@ impl PcResults {
     /// ```
     #[inline]
     pub fn count_keys(&self) -> usize {
-        self.succeed.len()
+        self.keys.len()
+    }
+
+    /// Returns the probability of succeed when the shape sequences come from the randomizer.
+    /// Each shape sequence is weighted by the probability that the randomizer generates it,
+    /// normalized over all the keys, so it's the probability given that one of the keys comes.
+    /// Pending shape sequences are not counted as succeed.
+    /// Returns 0 if the randomizer cannot generate any of the keys.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let mut result = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec!(T, I)),
+    ///     ShapeSequence::new(vec!(T, T)),
+    /// ]);
+    /// result.accept_if_present(&ShapeSequence::new(vec!(T, I)), true);
+    /// result.accept_if_present(&ShapeSequence::new(vec!(T, T)), false);
+    ///
+    /// // Each shape sequence has the same probability.
+    /// assert_eq!(result.success_probability(&MemorylessRandomizer), 0.5);
+    ///
+    /// // `TT` never comes from the same bag.
+    /// assert_eq!(result.success_probability(&BagRandomizer::seven_bag().with_offset(0)), 1.0);
+    /// ```
+    pub fn success_probability(&self, randomizer: &(impl Randomizer + ?Sized)) -> f64 {
+        let mut total = 0.0;
+        let mut succeed = 0.0;
+        for (sequence, state) in self.iter() {
+            let probability = randomizer.probability(&sequence);
+            total += probability;
+            if state == Some(true) {
+                succeed += probability;
+            }
+        }
+
+        if total <= 0.0 {
+            return 0.0;
+        }
+        (succeed / total).min(1.0)
+    }
+}
+
+impl Mirror for PcResults {
+    /// Returns the results for the mirrored board: each key is mirrored, and its state is kept.
+    /// The keys stay in the same positions, so the indices correspond to the original.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::PcResults;
+    /// use Shape::*;
+    ///
+    /// let mut result = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec!(L, S)),
+    ///     ShapeSequence::new(vec!(T, O)),
+    /// ]);
+    /// result.accept_if_present(&ShapeSequence::new(vec!(L, S)), true);
+    ///
+    /// let mirrored = result.mirrored();
+    /// assert_eq!(mirrored.get(&ShapeSequence::new(vec!(J, Z))), Some(true));
+    /// assert_eq!(mirrored.get(&ShapeSequence::new(vec!(T, O))), None);
+    /// assert_eq!(mirrored.get(&ShapeSequence::new(vec!(L, S))), None);
+    /// assert_eq!(mirrored.index_of(&ShapeSequence::new(vec!(T, O))), Some(1));
+    /// ```
+    fn mirrored(&self) -> Self {
+        Self {
+            keys: self.keys.mirrored(),
+            accepted: self.accepted.clone(),
+            succeed: self.succeed.clone(),
+            count_accepted: self.count_accepted,
+            count_succeed: self.count_succeed,
+        }
     }
 }
 
@@ -290,7 +442,7 @@ mod tests {
     use bitris::prelude::*;
 
     use crate::pc_possible::PcResults;
-    use crate::ShapeSequence;
+    use crate::{Mirror, ShapeSequence};
 
     #[test]
     fn pc_rate_result() {
@@ -359,4 +511,49 @@ mod tests {
             assert_eq!(result.get(&sequence), None);
         }
     }
+
+    #[test]
+    fn keeps_order() {
+        use Shape::*;
+        let sequences = vec![
+            ShapeSequence::new(vec!(Z, T)),
+            ShapeSequence::new(vec!(I, T)),
+            ShapeSequence::new(vec!(Z, T)),
+            ShapeSequence::new(vec!(O, T)),
+        ];
+        let result = PcResults::new(&sequences);
+
+        // Duplicates are removed.
+        assert_eq!(result.count_keys(), 3);
+        assert_eq!(
+            result.iter().map(|(sequence, _)| sequence).collect::<Vec<_>>(),
+            vec![sequences[0].clone(), sequences[1].clone(), sequences[3].clone()],
+        );
+        assert_eq!(result.index_of(&sequences[3]), Some(2));
+        assert_eq!(result.index_of(&sequences[2]), Some(0));
+        assert_eq!(result.index_of(&ShapeSequence::new(vec!(T, T))), None);
+    }
+
+    #[test]
+    fn long_sequences() {
+        use Shape::*;
+        let short = ShapeSequence::new(vec!(L, S));
+        let long = ShapeSequence::new([T, I, O, L, J, S, Z].repeat(4));
+        let mut result = PcResults::new(&vec![long.clone(), short.clone(), long.clone()]);
+        assert_eq!(result.count_keys(), 2);
+
+        assert!(result.accept_if_present(&long, true));
+        assert!(result.accept_if_present(&short, false));
+        assert_eq!(result.get(&long), Some(true));
+        assert_eq!(result.get(&short), Some(false));
+        assert_eq!(
+            result.iter().collect::<Vec<_>>(),
+            vec![(long.clone(), Some(true)), (short.clone(), Some(false))],
+        );
+
+        let mirrored = result.mirrored();
+        assert_eq!(mirrored.get(&long.mirrored()), Some(true));
+        assert_eq!(mirrored.get(&short.mirrored()), Some(false));
+        assert_eq!(mirrored.get(&short), None);
+    }
 }
@@ -0,0 +1,430 @@
+use std::str::FromStr;
+
+use bitris::prelude::*;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::pc_possible::PcResults;
+use crate::ShapeSequence;
+
+/// Holds the settings under which the results were made.
+/// They are written along with the results to identify them.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct PcResultsMetadata {
+    pub board: Board64,
+    pub height: u32,
+    pub pattern: String,
+    pub allows_hold: bool,
+    pub rotation_system: String,
+}
+
+/// A collection of errors that occur when importing the results.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PcResultsImportError {
+    #[error("The text is not valid JSON.")]
+    InvalidJson,
+    #[error("The metadata '{0}' is missing or invalid.")]
+    InvalidMetadata(String),
+    #[error("Unexpected line {0}.")]
+    UnexpectedLine(usize),
+    #[error("Invalid shape sequence '{0}'.")]
+    InvalidSequence(String),
+    #[error("Invalid state '{0}'. It must be succeed, failed, or pending.")]
+    InvalidState(String),
+}
+
+impl PcResults {
+    /// Returns the results as CSV.
+    /// The metadata is written as comments starting with `#`, followed by the header `sequence,state`.
+    /// Line breaks in the metadata (like a pattern set of several lines) are escaped as `\n`, and backslashes as `\\`.
+    /// The rows are sorted by the shape sequences in the order of the shapes `T, I, O, L, J, S, Z` (not alphabetical, e.g. `TO` comes before `IO`).
+    /// The state is one of `succeed`, `failed` and `pending`.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::{PcResults, PcResultsMetadata};
+    /// use Shape::*;
+    ///
+    /// let mut results = PcResults::new(&vec![
+    ///     ShapeSequence::new(vec![T, O]),
+    ///     ShapeSequence::new(vec![I, O]),
+    /// ]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![I, O]), true);
+    ///
+    /// let metadata = PcResultsMetadata {
+    ///     board: Board64::blank(),
+    ///     height: 4,
+    ///     pattern: "[TI],O".to_string(),
+    ///     allows_hold: true,
+    ///     rotation_system: "SRS".to_string(),
+    /// };
+    ///
+    /// let csv = results.to_csv(&metadata);
+    /// assert!(csv.ends_with("sequence,state\nTO,pending\nIO,succeed\n"));
+    ///
+    /// assert_eq!(PcResults::from_csv(csv.as_str()), Ok((metadata, results)));
+    /// ```
+    pub fn to_csv(&self, metadata: &PcResultsMetadata) -> String {
+        let mut lines = vec![
+            format!("# board: {}", to_board_text(metadata.board, metadata.height)),
+            format!("# height: {}", metadata.height),
+            format!("# pattern: {}", escape_value(&metadata.pattern)),
+            format!("# hold: {}", metadata.allows_hold),
+            format!("# rotation_system: {}", escape_value(&metadata.rotation_system)),
+            "sequence,state".to_string(),
+        ];
+
+        for (sequence, state) in self.to_sorted_rows() {
+            lines.push(format!("{},{}", sequence, state));
+        }
+
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    /// Returns the results as JSON in the form of `{"metadata": {...}, "results": [{"sequence": "TIO", "state": "succeed"}, ...]}`.
+    /// The results are sorted in the same order as `to_csv()`.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::pc_possible::{PcResults, PcResultsMetadata};
+    /// use Shape::*;
+    ///
+    /// let mut results = PcResults::new(&vec![ShapeSequence::new(vec![T, O])]);
+    /// results.accept_if_present(&ShapeSequence::new(vec![T, O]), false);
+    ///
+    /// let metadata = PcResultsMetadata {
+    ///     board: Board64::blank(),
+    ///     height: 4,
+    ///     pattern: "TO".to_string(),
+    ///     allows_hold: false,
+    ///     rotation_system: "SRS".to_string(),
+    /// };
+    ///
+    /// let json = results.to_json(&metadata);
+    /// assert!(json.contains(r#""results":[{"sequence":"TO","state":"failed"}]"#));
+    ///
+    /// assert_eq!(PcResults::from_json(json.as_str()), Ok((metadata, results)));
+    /// ```
+    pub fn to_json(&self, metadata: &PcResultsMetadata) -> String {
+        let results: Vec<Value> = self.to_sorted_rows().into_iter()
+            .map(|(sequence, state)| json!({ "sequence": sequence, "state": state }))
+            .collect();
+
+        json!({
+            "metadata": {
+                "board": to_board_text(metadata.board, metadata.height),
+                "height": metadata.height,
+                "pattern": metadata.pattern,
+                "hold": metadata.allows_hold,
+                "rotation_system": metadata.rotation_system,
+            },
+            "results": results,
+        }).to_string()
+    }
+
+    /// Restores the results and the metadata from CSV written by `to_csv()`.
+    /// The keys are in the order of the rows, so the original order is lost unless it's sorted (e.g. the order of the pattern).
+    /// Compare the results by their content (like with `get()`), not by `==`.
+    pub fn from_csv(text: &str) -> Result<(PcResultsMetadata, PcResults), PcResultsImportError> {
+        use PcResultsImportError::*;
+
+        let mut entries = Vec::<(String, String)>::new();
+        let mut rows = Vec::<(ShapeSequence, Option<bool>)>::new();
+        let mut has_header = false;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(entry) = line.strip_prefix('#') {
+                let (key, value) = entry.split_once(':').ok_or(UnexpectedLine(index + 1))?;
+                entries.push((key.trim().to_string(), value.trim().to_string()));
+                continue;
+            }
+
+            if !has_header {
+                if line != "sequence,state" {
+                    return Err(UnexpectedLine(index + 1));
+                }
+                has_header = true;
+                continue;
+            }
+
+            let (sequence, state) = line.split_once(',').ok_or(UnexpectedLine(index + 1))?;
+            rows.push((parse_sequence(sequence)?, parse_state(state)?));
+        }
+
+        let get = |key: &str| {
+            entries.iter()
+                .find(|(it, _)| it == key)
+                .map(|(_, value)| value.as_str())
+                .ok_or_else(|| InvalidMetadata(key.to_string()))
+        };
+
+        let height = get("height")?.parse::<u32>().map_err(|_| InvalidMetadata("height".to_string()))?;
+        let metadata = PcResultsMetadata {
+            board: parse_board_text(get("board")?)?,
+            height,
+            pattern: unescape_value(get("pattern")?).ok_or_else(|| InvalidMetadata("pattern".to_string()))?,
+            allows_hold: get("hold")?.parse::<bool>().map_err(|_| InvalidMetadata("hold".to_string()))?,
+            rotation_system: unescape_value(get("rotation_system")?).ok_or_else(|| InvalidMetadata("rotation_system".to_string()))?,
+        };
+
+        Ok((metadata, to_results(rows)))
+    }
+
+    /// Restores the results and the metadata from JSON written by `to_json()`.
+    /// The keys are in the order of the rows as `from_csv()`.
+    pub fn from_json(text: &str) -> Result<(PcResultsMetadata, PcResults), PcResultsImportError> {
+        use PcResultsImportError::*;
+
+        let value: Value = serde_json::from_str(text).map_err(|_| InvalidJson)?;
+
+        let metadata = &value["metadata"];
+        let invalid = |key: &str| InvalidMetadata(key.to_string());
+        let metadata = PcResultsMetadata {
+            board: parse_board_text(metadata["board"].as_str().ok_or_else(|| invalid("board"))?)?,
+            height: metadata["height"].as_u64()
+                .and_then(|height| u32::try_from(height).ok())
+                .ok_or_else(|| invalid("height"))?,
+            pattern: metadata["pattern"].as_str().ok_or_else(|| invalid("pattern"))?.to_string(),
+            allows_hold: metadata["hold"].as_bool().ok_or_else(|| invalid("hold"))?,
+            rotation_system: metadata["rotation_system"].as_str().ok_or_else(|| invalid("rotation_system"))?.to_string(),
+        };
+
+        let rows = value["results"].as_array()
+            .ok_or(InvalidJson)?
+            .iter()
+            .map(|row| {
+                let sequence = row["sequence"].as_str().ok_or(InvalidJson)?;
+                let state = row["state"].as_str().ok_or(InvalidJson)?;
+                Ok((parse_sequence(sequence)?, parse_state(state)?))
+            })
+            .collect::<Result<Vec<_>, PcResultsImportError>>()?;
+
+        Ok((metadata, to_results(rows)))
+    }
+
+    /// Sorts the rows by the shape sequences. `Shape` is ordered as `T, I, O, L, J, S, Z`.
+    fn to_sorted_rows(&self) -> Vec<(String, &'static str)> {
+        let mut rows: Vec<(ShapeSequence, Option<bool>)> = self.iter().collect();
+        rows.sort();
+        rows.into_iter()
+            .map(|(sequence, succeed)| {
+                let state = match succeed {
+                    Some(true) => "succeed",
+                    Some(false) => "failed",
+                    None => "pending",
+                };
+                (sequence.to_string(), state)
+            })
+            .collect()
+    }
+}
+
+fn to_results(rows: Vec<(ShapeSequence, Option<bool>)>) -> PcResults {
+    let sequences: Vec<ShapeSequence> = rows.iter().map(|(sequence, _)| sequence.clone()).collect();
+    let mut results = PcResults::new(&sequences);
+    for (sequence, succeed) in rows {
+        if let Some(succeed) = succeed {
+            results.accept_if_present(&sequence, succeed);
+        }
+    }
+    results
+}
+
+/// Escapes the line breaks so that the value is written in one line of the comments.
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Restores the value escaped by `escape_value()`. Returns `None` if an unknown escape is found.
+fn unescape_value(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => unescaped.push('\\'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+fn parse_sequence(text: &str) -> Result<ShapeSequence, PcResultsImportError> {
+    ShapeSequence::from_str(text)
+        .map_err(|_| PcResultsImportError::InvalidSequence(text.to_string()))
+}
+
+fn parse_state(text: &str) -> Result<Option<bool>, PcResultsImportError> {
+    match text.trim() {
+        "succeed" => Ok(Some(true)),
+        "failed" => Ok(Some(false)),
+        "pending" => Ok(None),
+        _ => Err(PcResultsImportError::InvalidState(text.to_string())),
+    }
+}
+
+/// Returns the rows from top to bottom separated by `/` (like `XX......XX/XXX....XXX`).
+fn to_board_text(board: Board64, height: u32) -> String {
+    let height = height.max(board.well_top());
+    (0..height as i32).rev()
+        .map(|y| {
+            (0..10)
+                .map(|x| if board.is_occupied_at(xy(x, y)) { 'X' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn parse_board_text(text: &str) -> Result<Board64, PcResultsImportError> {
+    if text.is_empty() {
+        return Ok(Board64::blank());
+    }
+    Board64::from_str(text.replace('/', "\n").as_str())
+        .map_err(|_| PcResultsImportError::InvalidMetadata("board".to_string()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::pc_possible::{PcResults, PcResultsImportError, PcResultsMetadata};
+    use crate::{Pattern, ShapeSequence};
+
+    #[test]
+    fn round_trip() {
+        use Shape::*;
+
+        let board = Board64::from_str("
+            XX......XX
+            XXX....XXX
+        ").unwrap();
+        let metadata = PcResultsMetadata {
+            board,
+            height: 3,
+            pattern: "T,*p2".to_string(),
+            allows_hold: true,
+            rotation_system: "SRS".to_string(),
+        };
+
+        let mut results = PcResults::new(&vec![
+            ShapeSequence::new(vec![T, I, O]),
+            ShapeSequence::new(vec![T, O, I]),
+            ShapeSequence::new(vec![T, S, Z]),
+        ]);
+        results.accept_if_present(&ShapeSequence::new(vec![T, O, I]), true);
+        results.accept_if_present(&ShapeSequence::new(vec![T, S, Z]), false);
+
+        let csv = results.to_csv(&metadata);
+        assert_eq!(csv, "\
+# board: ........../XX......XX/XXX....XXX
+# height: 3
+# pattern: T,*p2
+# hold: true
+# rotation_system: SRS
+sequence,state
+TIO,pending
+TOI,succeed
+TSZ,failed
+");
+        assert_eq!(PcResults::from_csv(csv.as_str()).unwrap(), (metadata.clone(), results.clone()));
+
+        let json = results.to_json(&metadata);
+        assert_eq!(PcResults::from_json(json.as_str()).unwrap(), (metadata, results));
+    }
+
+    #[test]
+    fn round_trip_multi_line_pattern() {
+        use Shape::*;
+
+        // Like a pattern set read from a file.
+        let metadata = PcResultsMetadata {
+            board: Board64::blank(),
+            height: 4,
+            pattern: "T,*p3\nI,*p3\n".to_string(),
+            allows_hold: true,
+            rotation_system: "SRS".to_string(),
+        };
+
+        let mut results = PcResults::new(&vec![ShapeSequence::new(vec![T, I, O, L])]);
+        results.accept_if_present(&ShapeSequence::new(vec![T, I, O, L]), true);
+
+        let csv = results.to_csv(&metadata);
+        assert!(csv.contains("# pattern: T,*p3\\nI,*p3\\n\n"));
+        assert_eq!(PcResults::from_csv(csv.as_str()).unwrap(), (metadata.clone(), results.clone()));
+
+        let metadata = PcResultsMetadata { pattern: "T\\n".to_string(), ..metadata };
+        assert_eq!(PcResults::from_csv(results.to_csv(&metadata).as_str()).unwrap().0, metadata);
+    }
+
+    #[test]
+    fn round_trip_unsorted_keys() {
+        let metadata = PcResultsMetadata {
+            board: Board64::blank(),
+            height: 4,
+            pattern: "*p3".to_string(),
+            allows_hold: true,
+            rotation_system: "SRS".to_string(),
+        };
+
+        let sequences: Vec<ShapeSequence> = Pattern::from_str("*p3").unwrap().to_sequences().into_iter().rev().collect();
+        let mut results = PcResults::new(&sequences);
+        for (index, sequence) in sequences.iter().enumerate().step_by(3) {
+            results.accept_if_present(sequence, index % 2 == 0);
+        }
+
+        // The keys are restored in the order of the rows, so the contents are compared.
+        let assert_same_contents = |restored: PcResults| {
+            assert_eq!(restored.count_keys(), results.count_keys());
+            assert_eq!(restored.count_succeed(), results.count_succeed());
+            assert_eq!(restored.count_failed(), results.count_failed());
+            for sequence in &sequences {
+                assert_eq!(restored.get(sequence), results.get(sequence));
+            }
+        };
+
+        let (restored_metadata, restored) = PcResults::from_csv(results.to_csv(&metadata).as_str()).unwrap();
+        assert_eq!(restored_metadata, metadata);
+        assert_same_contents(restored);
+
+        let (restored_metadata, restored) = PcResults::from_json(results.to_json(&metadata).as_str()).unwrap();
+        assert_eq!(restored_metadata, metadata);
+        assert_same_contents(restored);
+    }
+
+    #[test]
+    fn errors() {
+        use PcResultsImportError::*;
+
+        assert_eq!(PcResults::from_json("{"), Err(InvalidJson));
+        assert_eq!(PcResults::from_json("{}"), Err(InvalidMetadata("board".to_string())));
+
+        let csv = "# board: \n# height: 4\n# pattern: *\n# hold: true\n# rotation_system: SRS\n";
+        assert_eq!(PcResults::from_csv(format!("{}sequence,state\nT,succeed\n", csv).as_str()).unwrap().1.count_succeed(), 1);
+        assert_eq!(PcResults::from_csv(format!("{}T,succeed\n", csv).as_str()), Err(UnexpectedLine(6)));
+        assert_eq!(PcResults::from_csv(format!("{}sequence,state\nX,succeed\n", csv).as_str()), Err(InvalidSequence("X".to_string())));
+        assert_eq!(PcResults::from_csv(format!("{}sequence,state\nT,ok\n", csv).as_str()), Err(InvalidState("ok".to_string())));
+        assert_eq!(PcResults::from_csv("sequence,state\n"), Err(InvalidMetadata("height".to_string())));
+    }
+}
@@ -0,0 +1,236 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use bitris::prelude::*;
+use fxhash::FxHashMap;
+
+use crate::{ClippedBoard, Mirror, PatternSet};
+use crate::pc_possible::PcResults;
+
+/// The settings that determine the results, except the rotation system.
+/// The rotation system is the type parameter of the cache instead.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) struct CacheKey {
+    pub(crate) allow_move: AllowMove,
+    pub(crate) clipped_board: ClippedBoard,
+    pub(crate) pattern_set: PatternSet,
+    pub(crate) allows_hold: bool,
+    pub(crate) requires_t_spin: bool,
+}
+
+impl CacheKey {
+    fn mirrored(&self) -> Self {
+        Self {
+            clipped_board: self.clipped_board.mirrored(),
+            pattern_set: self.pattern_set.mirrored(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Declares that the rotation system is symmetric.
+/// That is, the kicks of each shape are the mirror images of those of its mirror shape (L and J, S and Z) with the rotation reversed,
+/// so a placement can be reached if and only if its mirror image can be reached on the flipped board.
+///
+/// SRS is not symmetric (e.g. the kicks of I), so it does not implement this.
+pub trait SymmetricRotationSystem: RotationSystem {}
+
+/// Holds the results already computed by `PcPossibleBulkExecutorBinder::try_execute_with_cache()`.
+/// If the same settings come again, the results are returned without searching.
+/// The cache is typed by the rotation system, so the results are never shared between different rotation systems.
+///
+/// With `PcPossibleBulkExecutorBinder::try_execute_with_mirrored_cache()`, the results are also derived from those of the mirror image
+/// (the flipped board and the pattern with L/J and S/Z swapped). It's available only for a `SymmetricRotationSystem`.
+/// ```
+/// use std::rc::Rc;
+/// use std::slice::Iter;
+/// use std::str::FromStr;
+/// use bitris_commands::prelude::*;
+/// use bitris_commands::pc_possible::*;
+///
+/// // Rotates without kicks, which is symmetric.
+/// struct NoKickTable;
+///
+/// impl RotationSystem for NoKickTable {
+///     fn iter_kicks(&self, piece: Piece, _: Rotation) -> Iter<'_, Kick> {
+///         const KICK: [Kick; 1] = [Kick::new(Offset::new(0, 0))];
+///         match piece.shape {
+///             Shape::O => [].iter(),
+///             _ => KICK.iter(),
+///         }
+///     }
+///
+///     fn is_moving_in_rotation(&self, shape: Shape) -> bool {
+///         shape != Shape::O
+///     }
+/// }
+///
+/// impl SymmetricRotationSystem for NoKickTable {}
+///
+/// let mut cache = PcResultsCache::new();
+///
+/// let mut binder = PcPossibleBulkExecutorBinder::default(Rc::from(NoKickTable));
+/// let board = Board64::from_str("
+///     XXXXX.....
+///     XXXXXX....
+///     XXXXXXX...
+/// ").unwrap();
+/// binder.clipped_board = ClippedBoard::try_new(board, 3).unwrap();
+/// binder.pattern = Rc::new(PatternSet::from_str("L,*p2").unwrap());
+/// let results = binder.try_execute_with_mirrored_cache(&mut cache).unwrap();
+/// assert_eq!(cache.len(), 1);
+///
+/// // Derived from the results above without searching.
+/// binder.clipped_board = binder.clipped_board.mirrored();
+/// binder.pattern = Rc::new(binder.pattern.mirrored());
+/// let derived = binder.try_execute_with_mirrored_cache(&mut cache).unwrap();
+/// assert_eq!(cache.len(), 1);
+/// assert_eq!(derived.count_succeed(), results.count_succeed());
+/// ```
+pub struct PcResultsCache<T: RotationSystem> {
+    entries: FxHashMap<CacheKey, PcResults>,
+    rotation_system: PhantomData<fn() -> T>,
+}
+
+impl<T: RotationSystem> Default for PcResultsCache<T> {
+    fn default() -> Self {
+        Self { entries: FxHashMap::default(), rotation_system: PhantomData }
+    }
+}
+
+impl<T: RotationSystem> Clone for PcResultsCache<T> {
+    fn clone(&self) -> Self {
+        Self { entries: self.entries.clone(), rotation_system: PhantomData }
+    }
+}
+
+impl<T: RotationSystem> fmt::Debug for PcResultsCache<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PcResultsCache").field("entries", &self.entries).finish()
+    }
+}
+
+impl<T: RotationSystem> PcResultsCache<T> {
+    /// Making the empty cache. The results are reused only for the same settings.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the count of the results searched. The derived results are not counted.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the results for the settings if they are the same as those searched.
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<PcResults> {
+        self.entries.get(key).cloned()
+    }
+
+    /// Returns the results derived from those searched for the mirror image of the settings.
+    /// The callers must ensure that the rotation system is symmetric.
+    /// Returns `None` if some mirrored sequences are not found in the keys (e.g. the pattern has combinations).
+    /// The derived results have the keys in the order of the pattern set.
+    pub(crate) fn derive_mirrored(&self, key: &CacheKey) -> Option<PcResults> {
+        let mirrored_key = key.mirrored();
+        let results = self.entries.get(&mirrored_key)?;
+        let mut derived = PcResults::new(&key.pattern_set.to_sequences());
+        for (sequence, state) in results.mirrored().iter() {
+            if let Some(succeed) = state {
+                derived.accept_if_present(&sequence, succeed);
+            }
+        }
+
+        // The mirror images of combinations are not sorted, so they may not be found in the keys.
+        if derived.count_accepted() != results.count_accepted() {
+            return None;
+        }
+        Some(derived)
+    }
+
+    pub(crate) fn insert(&mut self, key: CacheKey, results: PcResults) {
+        self.entries.insert(key, results);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::slice::Iter;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, Mirror, PatternSet};
+    use crate::pc_possible::{PcPossibleBulkExecutorBinder, PcResultsCache, SymmetricRotationSystem};
+
+    #[derive(Copy, Clone, Debug)]
+    struct NoKickTable;
+
+    impl RotationSystem for NoKickTable {
+        fn iter_kicks(&self, piece: Piece, _: Rotation) -> Iter<'_, Kick> {
+            const KICK: [Kick; 1] = [Kick::new(Offset::new(0, 0))];
+            match piece.shape {
+                Shape::O => [].iter(),
+                _ => KICK.iter(),
+            }
+        }
+
+        fn is_moving_in_rotation(&self, shape: Shape) -> bool {
+            shape != Shape::O
+        }
+    }
+
+    impl SymmetricRotationSystem for NoKickTable {}
+
+    #[test]
+    fn same_as_searching_mirrored() {
+        let mut binder = PcPossibleBulkExecutorBinder::default(Rc::from(NoKickTable));
+        let board = Board64::from_str("
+            XXXX....XX
+            XXX.....XX
+            XX......XX
+            XXX.....XX
+        ").unwrap();
+        binder.clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        binder.pattern = Rc::from(PatternSet::from_str("
+            L,*p5
+            [SZ]p2,*p4
+        ").unwrap());
+
+        let mut cache = PcResultsCache::new();
+        let results = binder.try_execute_with_mirrored_cache(&mut cache).unwrap();
+        assert_eq!(binder.try_execute_with_mirrored_cache(&mut cache).unwrap(), results);
+        assert_eq!(cache.len(), 1);
+
+        let mut mirrored_binder = binder.clone();
+        mirrored_binder.clipped_board = binder.clipped_board.mirrored();
+        mirrored_binder.pattern = Rc::from(binder.pattern.mirrored());
+        let expected = mirrored_binder.try_execute().unwrap();
+
+        let derived = mirrored_binder.try_execute_with_mirrored_cache(&mut cache).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(derived, expected);
+
+        // Not derived without mirroring.
+        assert_eq!(mirrored_binder.try_execute_with_cache(&mut cache).unwrap(), expected);
+        assert_eq!(cache.len(), 2);
+
+        // Other settings are not derived.
+        mirrored_binder.allows_hold = false;
+        mirrored_binder.try_execute_with_mirrored_cache(&mut cache).unwrap();
+        assert_eq!(cache.len(), 3);
+    }
+}
@@ -0,0 +1,374 @@
+use bitris::Shape;
+
+use crate::{ShapeCounter, ShapeSequence};
+
+/// The model that generates the shapes of the game.
+///
+/// Each randomizer defines the probability of each shape to come next from the shapes so far.
+/// Then, the probabilities of whole sequences are derived from them.
+pub trait Randomizer {
+    /// Returns the probability of each shape to come next after the shapes that have come so far.
+    /// The shapes that cannot come are omitted, and the probabilities sum to 1.
+    /// If the shapes so far cannot come at all, the result is undefined.
+    fn next_probabilities(&self, history: &[Shape]) -> Vec<(Shape, f64)>;
+
+    /// Returns the probability that the generated shapes start with the sequence.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    ///
+    /// let randomizer = MemorylessRandomizer;
+    /// assert!((randomizer.probability(&ShapeSequence::new(vec![T, T])) - 1.0 / 49.0).abs() < 1e-9);
+    ///
+    /// let randomizer = BagRandomizer::seven_bag().with_offset(0);
+    /// assert_eq!(randomizer.probability(&ShapeSequence::new(vec![T, T])), 0.0);
+    /// ```
+    fn probability(&self, sequence: &ShapeSequence) -> f64 {
+        let shapes = sequence.shapes();
+        let mut probability = 1.0;
+        for index in 0..shapes.len() {
+            let next = self.next_probabilities(&shapes[..index]).into_iter()
+                .find(|&(shape, _)| shape == shapes[index])
+                .map_or(0.0, |(_, probability)| probability);
+            if next == 0.0 {
+                return 0.0;
+            }
+            probability *= next;
+        }
+        probability
+    }
+
+    /// Returns all sequences of the length that can come, with their probabilities. The probabilities sum to 1.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let randomizer = BagRandomizer::seven_bag().with_offset(0);
+    /// let sequences = randomizer.to_weighted_sequences(7);
+    /// assert_eq!(sequences.len(), 5040);
+    /// assert!(sequences.iter().all(|(_, probability)| (probability - 1.0 / 5040.0).abs() < 1e-12));
+    /// ```
+    fn to_weighted_sequences(&self, len: usize) -> Vec<(ShapeSequence, f64)> {
+        fn build(
+            randomizer: &(impl Randomizer + ?Sized),
+            len: usize,
+            buffer: &mut Vec<Shape>,
+            probability: f64,
+            out: &mut Vec<(ShapeSequence, f64)>,
+        ) {
+            if buffer.len() == len {
+                out.push((ShapeSequence::new(buffer.clone()), probability));
+                return;
+            }
+
+            for (shape, next) in randomizer.next_probabilities(buffer) {
+                if next <= 0.0 {
+                    continue;
+                }
+                buffer.push(shape);
+                build(randomizer, len, buffer, probability * next, out);
+                buffer.pop();
+            }
+        }
+
+        let mut out = Vec::new();
+        build(self, len, &mut Vec::with_capacity(len), 1.0, &mut out);
+        out
+    }
+
+    /// Samples a sequence of the length.
+    /// `random` must return a uniform random number in `[0, 1)` for each call.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// // A simple linear congruential generator. Use any random number generator you like.
+    /// let mut seed = 1u64;
+    /// let mut random = || {
+    ///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///     (seed >> 11) as f64 / (1u64 << 53) as f64
+    /// };
+    ///
+    /// let randomizer = BagRandomizer::seven_bag().with_offset(0);
+    /// let sequence = randomizer.sample(14, &mut random);
+    /// assert_eq!(ShapeCounter::from(sequence.shapes()[..7].to_vec()), ShapeCounter::one_of_each());
+    /// assert_eq!(ShapeCounter::from(sequence.shapes()[7..].to_vec()), ShapeCounter::one_of_each());
+    /// ```
+    fn sample(&self, len: usize, random: &mut dyn FnMut() -> f64) -> ShapeSequence {
+        let mut shapes = Vec::<Shape>::with_capacity(len);
+        while shapes.len() < len {
+            let nexts = self.next_probabilities(&shapes);
+            let mut rest = random();
+            let shape = nexts.iter()
+                .find(|&&(_, probability)| {
+                    rest -= probability;
+                    rest < 0.0
+                })
+                .or(nexts.last())
+                .map(|&(shape, _)| shape)
+                .expect("No shape can come next");
+            shapes.push(shape);
+        }
+        ShapeSequence::new(shapes)
+    }
+}
+
+/// Generates each shape with the same probability, independent of the shapes so far.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub struct MemorylessRandomizer;
+
+impl Randomizer for MemorylessRandomizer {
+    fn next_probabilities(&self, _: &[Shape]) -> Vec<(Shape, f64)> {
+        Shape::all_into_iter().map(|shape| (shape, 1.0 / 7.0)).collect()
+    }
+}
+
+/// Generates the shapes by drawing from a shuffled bag, which contains the same count of each shape.
+/// When the bag becomes empty, it's refilled.
+///
+/// The offset is the count of shapes already drawn from the first bag before the sequence starts.
+/// If the offset is unknown, every offset is regarded as equally likely.
+/// ```
+/// use bitris_commands::prelude::*;
+/// use Shape::*;
+///
+/// // The first bag has only one shape left, so the same shape can come twice in a row.
+/// let randomizer = BagRandomizer::seven_bag().with_offset(6);
+/// assert!((randomizer.probability(&ShapeSequence::new(vec![T, T])) - 1.0 / 49.0).abs() < 1e-9);
+///
+/// // If the offset is unknown, it's possible only when the offset is 6.
+/// let randomizer = BagRandomizer::seven_bag();
+/// assert!((randomizer.probability(&ShapeSequence::new(vec![T, T])) - 1.0 / 49.0 / 7.0).abs() < 1e-9);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct BagRandomizer {
+    bag: ShapeCounter,
+    offset: Option<usize>,
+}
+
+impl BagRandomizer {
+    /// Makes the randomizer with the bag containing the shapes. The offset is unknown.
+    /// Panics if the bag is empty.
+    #[inline]
+    pub fn new(bag: ShapeCounter) -> Self {
+        assert!(!bag.is_empty());
+        Self { bag, offset: None }
+    }
+
+    /// The bag containing one of each shape. The offset is unknown.
+    #[inline]
+    pub fn seven_bag() -> Self {
+        Self::new(ShapeCounter::one_of_each())
+    }
+
+    /// The bag containing two of each shape. The offset is unknown.
+    #[inline]
+    pub fn fourteen_bag() -> Self {
+        Self::new(ShapeCounter::new([2; 7]))
+    }
+
+    /// Fixes the count of shapes already drawn from the first bag.
+    /// Panics if it's not less than the size of the bag.
+    #[inline]
+    pub fn with_offset(self, offset: usize) -> Self {
+        assert!(offset < self.bag.len());
+        Self { offset: Some(offset), ..self }
+    }
+
+    #[inline]
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Returns the probability of the history and the shapes left in the current bag.
+    /// The shapes drawn before the sequence are unknown, so the rest of the first bag behaves like a new bag that runs out early.
+    fn walk(&self, history: &[Shape], offset: usize) -> (f64, ShapeCounter) {
+        let mut probability = 1.0;
+        let mut remaining = self.bag;
+        let mut left = self.bag.len() - offset;
+        for &shape in history {
+            probability *= remaining[shape] as f64 / remaining.len() as f64;
+            if !remaining.remove(shape) {
+                return (0.0, remaining);
+            }
+
+            left -= 1;
+            if left == 0 {
+                remaining = self.bag;
+                left = self.bag.len();
+            }
+        }
+        (probability, remaining)
+    }
+}
+
+impl Randomizer for BagRandomizer {
+    fn next_probabilities(&self, history: &[Shape]) -> Vec<(Shape, f64)> {
+        let offsets = match self.offset {
+            Some(offset) => offset..offset + 1,
+            None => 0..self.bag.len(),
+        };
+
+        // Mixes the offsets by the probability of the history for each.
+        let mut probabilities = [0.0; 7];
+        let mut total = 0.0;
+        for offset in offsets {
+            let (weight, remaining) = self.walk(history, offset);
+            if weight == 0.0 {
+                continue;
+            }
+            for (shape, count) in remaining.to_pairs() {
+                probabilities[shape as usize] += weight * count as f64 / remaining.len() as f64;
+            }
+            total += weight;
+        }
+
+        Shape::all_into_iter()
+            .filter(|&shape| 0.0 < probabilities[shape as usize])
+            .map(|shape| (shape, probabilities[shape as usize] / total))
+            .collect()
+    }
+}
+
+/// Generates the shapes avoiding the recent ones, like TGM.
+///
+/// It rolls a shape at random, and rerolls if it's in the history of the recent shapes.
+/// After the rolls run out, the last rolled shape is taken even if it's in the history.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct HistoryRandomizer {
+    initial_history: Vec<Shape>,
+    rolls: usize,
+    first_excludes: ShapeCounter,
+}
+
+impl HistoryRandomizer {
+    /// Makes the randomizer with the history before the first shape and the count of rolls.
+    /// The length of the history is kept.
+    /// Panics if the count of rolls is zero.
+    #[inline]
+    pub fn new(initial_history: Vec<Shape>, rolls: usize) -> Self {
+        assert!(0 < rolls);
+        Self { initial_history, rolls, first_excludes: ShapeCounter::empty() }
+    }
+
+    /// The randomizer like TGM: the history of 4 starts with `ZZZZ` and 4 rolls.
+    /// The first shape is one of I, J, L and T.
+    #[inline]
+    pub fn tgm1() -> Self {
+        use Shape::*;
+        Self::new(vec![Z, Z, Z, Z], 4).excludes_first(ShapeCounter::from(vec![O, S, Z]))
+    }
+
+    /// The randomizer like TGM2: the history of 4 starts with `ZSSZ` and 6 rolls.
+    /// The first shape is one of I, J, L and T.
+    #[inline]
+    pub fn tgm2() -> Self {
+        use Shape::*;
+        Self::new(vec![Z, S, S, Z], 6).excludes_first(ShapeCounter::from(vec![O, S, Z]))
+    }
+
+    /// Sets the shapes that never come first. The first shape is chosen from the others at random.
+    #[inline]
+    pub fn excludes_first(self, first_excludes: ShapeCounter) -> Self {
+        assert!(first_excludes.to_pairs().len() < 7);
+        Self { first_excludes, ..self }
+    }
+}
+
+impl Randomizer for HistoryRandomizer {
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    ///
+    /// let randomizer = HistoryRandomizer::tgm1();
+    /// assert_eq!(randomizer.probability(&ShapeSequence::new(vec![S])), 0.0);
+    ///
+    /// // With 4 rolls, a shape in the history comes only if all rolls hit the history.
+    /// let probability = randomizer.probability(&ShapeSequence::new(vec![T, T]));
+    /// assert!((probability - (2.0f64 / 7.0).powi(3) / 7.0 / 4.0).abs() < 1e-9);
+    /// ```
+    fn next_probabilities(&self, history: &[Shape]) -> Vec<(Shape, f64)> {
+        if history.is_empty() && !self.first_excludes.is_empty() {
+            let candidates: Vec<Shape> = Shape::all_into_iter()
+                .filter(|&shape| self.first_excludes[shape] == 0)
+                .collect();
+            let probability = 1.0 / candidates.len() as f64;
+            return candidates.into_iter().map(|shape| (shape, probability)).collect();
+        }
+
+        // The recent shapes, including those in the initial history.
+        let recent: Vec<Shape> = self.initial_history.iter()
+            .chain(history)
+            .rev()
+            .take(self.initial_history.len())
+            .copied()
+            .collect();
+        let in_history = |shape: Shape| recent.contains(&shape);
+
+        let hits = Shape::all_into_iter().filter(|&shape| in_history(shape)).count() as f64 / 7.0;
+        Shape::all_into_iter()
+            .map(|shape| {
+                let probability = if in_history(shape) {
+                    // Only the last roll can take it.
+                    hits.powi(self.rolls as i32 - 1) / 7.0
+                } else {
+                    // Any roll can take it, after the previous rolls hit the history.
+                    (0..self.rolls).map(|roll| hits.powi(roll as i32)).sum::<f64>() / 7.0
+                };
+                (shape, probability)
+            })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::Shape;
+
+    use crate::{BagRandomizer, HistoryRandomizer, MemorylessRandomizer, Randomizer, ShapeCounter};
+
+    fn assert_sums_to_one(randomizer: &impl Randomizer, len: usize) {
+        let sum: f64 = randomizer.to_weighted_sequences(len).iter().map(|(_, probability)| probability).sum();
+        assert!((sum - 1.0).abs() < 1e-9, "{}", sum);
+    }
+
+    #[test]
+    fn sums_to_one() {
+        assert_sums_to_one(&MemorylessRandomizer, 3);
+        assert_sums_to_one(&BagRandomizer::seven_bag(), 4);
+        assert_sums_to_one(&BagRandomizer::seven_bag().with_offset(5), 4);
+        assert_sums_to_one(&BagRandomizer::fourteen_bag(), 3);
+        assert_sums_to_one(&HistoryRandomizer::tgm1(), 3);
+        assert_sums_to_one(&HistoryRandomizer::tgm2(), 3);
+    }
+
+    #[test]
+    fn bag() {
+        use Shape::*;
+
+        // Each of the 7 offsets is equally likely to be seen first, so the first shape is uniform.
+        let randomizer = BagRandomizer::seven_bag();
+        assert!(randomizer.next_probabilities(&[]).into_iter().all(|(_, probability)| (probability - 1.0 / 7.0).abs() < 1e-9));
+
+        // All 7 distinct shapes are consistent with every offset, and the offsets are weighted by the probability of the history.
+        // The offset 0 starts the next bag, and the others leave the shapes drawn earlier in the second bag, so T is the most likely.
+        let history = [T, I, O, L, J, S, Z];
+        let next = randomizer.next_probabilities(&history);
+        let expected = [(T, 1.0 / 4.0), (I, 91.0 / 604.0), (O, 81.0 / 604.0), (L, 77.0 / 604.0), (J, 37.0 / 302.0), (S, 35.0 / 302.0), (Z, 15.0 / 151.0)];
+        assert_eq!(next.len(), expected.len());
+        for ((shape, probability), (expected_shape, expected_probability)) in next.into_iter().zip(expected) {
+            assert_eq!(shape, expected_shape);
+            assert!((probability - expected_probability).abs() < 1e-9, "{:?} {}", shape, probability);
+        }
+
+        // With the duplicated T, the offsets that put both in the same bag are excluded.
+        let history = [T, I, O, L, J, S, T];
+        let next = randomizer.next_probabilities(&history);
+        assert!(next.iter().all(|&(shape, _)| shape != T));
+
+        // In the 14-bag, the same shape can come twice from the same bag, but not three times.
+        let randomizer = BagRandomizer::fourteen_bag().with_offset(0);
+        let next = randomizer.next_probabilities(&[T, T]);
+        assert!(next.iter().all(|&(shape, _)| shape != T));
+        assert_eq!(ShapeCounter::from(next.iter().map(|&(shape, _)| shape).collect::<Vec<_>>()).len(), 6);
+    }
+}
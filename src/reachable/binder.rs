@@ -0,0 +1,101 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+
+use crate::{ClippedBoard, ShapeOrder};
+use crate::reachable::{ReachableState, ReachableStatesExecutor, ReachableStatesExecutorCreationError};
+
+/// The binder to hold and tie settings for `ReachableStatesExecutor`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct ReachableStatesExecutorBinder<T: RotationSystem> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub clipped_board: ClippedBoard,
+    pub shape_order: Rc<ShapeOrder>,
+    pub allows_hold: bool,
+    pub max_depth: usize,
+}
+
+impl ReachableStatesExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `ReachableStatesExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        ReachableStatesExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem> ReachableStatesExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + [required] shape_order: empty order. You must set this.
+    ///   + allow move: softdrop
+    ///   + board: blank
+    ///   + height: 4 lines
+    ///   + allows hold: yes
+    ///   + max depth: 2
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
+            shape_order: Rc::from(ShapeOrder::new(vec![])),
+            allows_hold: true,
+            max_depth: 2,
+        }
+    }
+
+    // See `ReachableStatesExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<Vec<ReachableState>, ReachableStatesExecutorCreationError> {
+        self.try_execute_with_filter(|_| true)
+    }
+
+    // See `ReachableStatesExecutor::{try_new, execute_with_filter}` for more details.
+    pub fn try_execute_with_filter(
+        &self,
+        filter: impl Fn(&ReachableState) -> bool,
+    ) -> Result<Vec<ReachableState>, ReachableStatesExecutorCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute_with_filter(filter))
+    }
+
+    fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<ReachableStatesExecutor<'a, T>, ReachableStatesExecutorCreationError> {
+        ReachableStatesExecutor::try_new(
+            move_rules,
+            self.clipped_board,
+            self.shape_order.as_ref(),
+            self.allows_hold,
+            self.max_depth,
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use bitris::prelude::*;
+
+    use crate::ShapeOrder;
+    use crate::reachable::ReachableStatesExecutorBinder;
+
+    #[test]
+    fn reuse() {
+        use Shape::*;
+
+        let mut binder = ReachableStatesExecutorBinder::srs();
+        binder.shape_order = Rc::new(ShapeOrder::new(vec![O, I]));
+
+        binder.max_depth = 1;
+        assert_eq!(binder.try_execute().unwrap().len(), 9 + 17);
+
+        binder.allows_hold = false;
+        assert_eq!(binder.try_execute().unwrap().len(), 9);
+
+        let states = binder.try_execute_with_filter(|state| state.placements()[0].position.lx == 0).unwrap();
+        assert_eq!(states.len(), 1);
+    }
+}
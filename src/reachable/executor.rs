@@ -0,0 +1,251 @@
+use bitris::prelude::*;
+use fxhash::FxHashSet;
+use thiserror::Error;
+
+use crate::{ClippedBoard, OrderCursor, PopOp, ShapeOrder};
+use crate::reachable::ReachableState;
+
+/// Dataset for detecting the same state during the walk.
+/// The remaining states are determined by the board and the shapes not yet used (including the head).
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct SearchingState {
+    board: Board64,
+    unused: ShapeOrder,
+}
+
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ReachableStatesExecutorCreationError {
+    #[error("The order has no shapes.")]
+    EmptyOrder,
+    #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
+    BoardIsTooHigh,
+}
+
+/// The executor to enumerate all states reachable by placing pieces from the order.
+///
+/// Pieces are placed in the order, with hold if allowed, and filled rows are cleared after each placement.
+/// Each piece must be placed below the height of the clipped board. Unlike PC, the height does not shrink when lines are cleared.
+///
+/// States reached by different paths are deduplicated, and only the first path found is kept.
+/// To bound the explosion of states, the walk stops at the max depth, and a filter can prune states on the way.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct ReachableStatesExecutor<'a, T: RotationSystem> {
+    move_rules: &'a MoveRules<'a, T>,
+    clipped_board: ClippedBoard,
+    shape_order: &'a ShapeOrder,
+    allows_hold: bool,
+    max_depth: usize,
+    spawn_position: BlPosition,
+}
+
+impl<'a, T: RotationSystem> ReachableStatesExecutor<'a, T> {
+    /// Make ReachableStatesExecutor.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `ReachableStatesExecutorCreationError` for error patterns.
+    /// ```
+    /// use bitris::{Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, ShapeOrder};
+    /// use bitris_commands::reachable::ReachableStatesExecutor;
+    /// use bitris::Shape::*;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).expect("Failed to clip");
+    /// let shape_order = ShapeOrder::new(vec![O, O]);
+    ///
+    /// let executor = ReachableStatesExecutor::try_new(&move_rules, clipped_board, &shape_order, true, 2)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let states = executor.execute();
+    ///
+    /// // 9 states of one O on the ground.
+    /// assert_eq!(states.iter().filter(|state| state.depth() == 1).count(), 9);
+    ///
+    /// // The walk stops at the max depth.
+    /// assert!(states.iter().any(|state| state.depth() == 2));
+    /// assert!(states.iter().all(|state| state.depth() <= 2));
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        shape_order: &'a ShapeOrder,
+        allows_hold: bool,
+        max_depth: usize,
+    ) -> Result<Self, ReachableStatesExecutorCreationError> {
+        use ReachableStatesExecutorCreationError::*;
+
+        if 56 < clipped_board.height() {
+            return Err(BoardIsTooHigh);
+        }
+
+        if shape_order.shapes().is_empty() {
+            return Err(EmptyOrder);
+        }
+
+        // Spawn above the height so that it is not stuck when harddrop only.
+        let spawn_position = bl(5, clipped_board.height() as i32 + 4);
+
+        Ok(Self { move_rules, clipped_board, shape_order, allows_hold, max_depth, spawn_position })
+    }
+
+    /// Start the walk and return all distinct states, in ascending order of depth.
+    /// The initial state (no placements) is not included.
+    pub fn execute(&self) -> Vec<ReachableState> {
+        self.execute_with_filter(|_| true)
+    }
+
+    /// Start the walk with a filter.
+    /// If the filter returns `false`, the state is discarded and pieces are not placed beyond it.
+    pub fn execute_with_filter(&self, filter: impl Fn(&ReachableState) -> bool) -> Vec<ReachableState> {
+        let mut results = Vec::<ReachableState>::new();
+        let mut visited_states = FxHashSet::<SearchingState>::default();
+
+        let mut current = vec![(self.clipped_board.board(), self.shape_order.new_cursor(), Vec::<BlPlacement>::new())];
+
+        for _ in 0..self.max_depth {
+            let mut next = Vec::new();
+
+            for (board, cursor, placements) in current {
+                let mut walk = |op: PopOp| {
+                    let (popped, next_cursor) = cursor.pop(op);
+                    if let Some(shape) = popped {
+                        self.increment(shape, board, next_cursor, &placements, &mut visited_states, &filter, &mut results, &mut next);
+                    }
+                };
+
+                walk(PopOp::First);
+                if self.allows_hold {
+                    walk(PopOp::Second);
+                }
+            }
+
+            if next.is_empty() {
+                break;
+            }
+
+            current = next;
+        }
+
+        results
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn increment<'b>(
+        &self,
+        shape: Shape,
+        board: Board64,
+        next_cursor: OrderCursor<'b>,
+        placements: &[BlPlacement],
+        visited_states: &mut FxHashSet<SearchingState>,
+        filter: &impl Fn(&ReachableState) -> bool,
+        results: &mut Vec<ReachableState>,
+        next: &mut Vec<(Board64, OrderCursor<'b>, Vec<BlPlacement>)>,
+    ) {
+        let placement = shape.with(Orientation::North).with(self.spawn_position);
+        let moves = self.move_rules.generate_minimized_moves(board, placement);
+
+        for placement in moves {
+            if self.clipped_board.height() as i32 <= placement.tr_placement().position.ty {
+                continue;
+            }
+
+            let mut next_board = board;
+            placement.place_on_and_clear_lines(&mut next_board).unwrap();
+
+            let unused = next_cursor.unused_shapes();
+            if !visited_states.insert(SearchingState { board: next_board, unused: unused.clone() }) {
+                continue;
+            }
+
+            let mut next_placements = placements.to_vec();
+            next_placements.push(placement);
+
+            let state = ReachableState::new(next_board, next_placements, unused);
+            if !filter(&state) {
+                continue;
+            }
+
+            next.push((next_board, next_cursor, state.placements().to_vec()));
+            results.push(state);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, ShapeOrder};
+    use crate::reachable::{ReachableStatesExecutor, ReachableStatesExecutorCreationError};
+
+    #[test]
+    fn hold_and_depth() {
+        use Shape::*;
+
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let shape_order = ShapeOrder::new(vec![O, I]);
+
+        // Without hold, only O can be placed first.
+        let executor = ReachableStatesExecutor::try_new(&move_rules, clipped_board, &shape_order, false, 1).unwrap();
+        let states = executor.execute();
+        assert_eq!(states.len(), 9);
+        assert!(states.iter().all(|state| state.placements()[0].piece.shape == O));
+        assert!(states.iter().all(|state| state.unused_shapes() == &ShapeOrder::new(vec![I])));
+
+        // With hold, I can be placed first: 7 flat and 10 vertical.
+        let executor = ReachableStatesExecutor::try_new(&move_rules, clipped_board, &shape_order, true, 1).unwrap();
+        assert_eq!(executor.execute().len(), 9 + 17);
+
+        // The depth is bounded by the order.
+        let executor = ReachableStatesExecutor::try_new(&move_rules, clipped_board, &shape_order, true, 5).unwrap();
+        let states = executor.execute();
+        assert_eq!(states.iter().map(|state| state.depth()).max(), Some(2));
+        assert!(states.windows(2).all(|pair| pair[0].depth() <= pair[1].depth()));
+    }
+
+    #[test]
+    fn deduplicate_and_filter() {
+        use Shape::*;
+
+        let board = Board64::from_str("
+            XXXXXX....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let shape_order = ShapeOrder::new(vec![O, O]);
+
+        // Two O on the right clear the line in either order, and the states are merged.
+        let executor = ReachableStatesExecutor::try_new(&move_rules, clipped_board, &shape_order, false, 2).unwrap();
+        let states = executor.execute();
+        let cleared = states.iter()
+            .filter(|state| state.depth() == 2 && state.board() == Board64::from_str("
+                ......XXXX
+            ").unwrap())
+            .count();
+        assert_eq!(cleared, 1);
+
+        // The filter prunes states stacked higher than 2 rows.
+        let executor = ReachableStatesExecutor::try_new(&move_rules, clipped_board, &shape_order, false, 2).unwrap();
+        let states = executor.execute_with_filter(|state| state.board().well_top() <= 2);
+        assert!(states.iter().all(|state| state.board().well_top() <= 2));
+        assert!(states.iter().any(|state| state.depth() == 2));
+    }
+
+    #[test]
+    fn empty_order() {
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        let shape_order = ShapeOrder::default();
+        assert_eq!(
+            ReachableStatesExecutor::try_new(&move_rules, clipped_board, &shape_order, true, 1).unwrap_err(),
+            ReachableStatesExecutorCreationError::EmptyOrder,
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub use binder::*;
+pub use executor::*;
+pub use reachable_state::*;
+
+mod binder;
+mod executor;
+mod reachable_state;
@@ -0,0 +1,43 @@
+use bitris::prelude::*;
+
+use crate::ShapeOrder;
+
+/// Represents a distinct state reached by placing pieces from the order.
+/// The placements are one of the paths to reach the state, in the order in which they were placed.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ReachableState {
+    board: Board64,
+    placements: Vec<BlPlacement>,
+    unused: ShapeOrder,
+}
+
+impl ReachableState {
+    #[inline]
+    pub(crate) fn new(board: Board64, placements: Vec<BlPlacement>, unused: ShapeOrder) -> Self {
+        Self { board, placements, unused }
+    }
+
+    /// Returns the board after the placements. Filled rows are already cleared.
+    #[inline]
+    pub fn board(&self) -> Board64 {
+        self.board
+    }
+
+    #[inline]
+    pub fn placements(&self) -> &[BlPlacement] {
+        self.placements.as_slice()
+    }
+
+    /// Returns the count of pieces placed.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.placements.len()
+    }
+
+    /// Returns the shapes that have not been used yet.
+    /// If hold is allowed, the head is the shape that can be swapped (it's in the hold or the current).
+    #[inline]
+    pub fn unused_shapes(&self) -> &ShapeOrder {
+        &self.unused
+    }
+}
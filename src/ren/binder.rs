@@ -0,0 +1,138 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{Pattern, PatternCreationError, PatternElement, ShapeOrder};
+use crate::ren::{RenBulkExecutor, RenExecutorBulkCreationError, RenSolution};
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum RenExecutorCreationError {
+    #[error("The order has no shapes.")]
+    EmptyOrder,
+    #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
+    BoardIsTooHigh,
+}
+
+/// The binder to hold and tie settings for finding the maximum combo of a single order.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct RenExecutorBinder<T: RotationSystem> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub board: Board64,
+    pub shape_order: Rc<ShapeOrder>,
+    pub allows_hold: bool,
+}
+
+impl RenExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `RenExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        RenExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem> RenExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + [required] board: blank. You must set this.
+    ///   + [required] shape_order: empty order. You must set this.
+    ///   + allow move: softdrop
+    ///   + allows hold: yes
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            board: Board64::blank(),
+            shape_order: Rc::from(ShapeOrder::new(vec![])),
+            allows_hold: true,
+        }
+    }
+
+    /// Returns the moves that keep the combo going the longest.
+    /// If no piece can clear a line first, returns a solution with no placements.
+    // See `RenBulkExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<RenSolution, RenExecutorCreationError> {
+        use RenExecutorBulkCreationError as FromError;
+        use RenExecutorCreationError as ToError;
+
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let pattern = match Pattern::try_from(
+            self.shape_order.shapes().iter().map(|&shape| PatternElement::One(shape)).collect_vec()
+        ) {
+            Ok(pattern) => pattern,
+            Err(error) => return match error {
+                PatternCreationError::NoShapeSequences => Err(ToError::EmptyOrder),
+                PatternCreationError::ContainsInvalidPermutation => panic!("Unreachable assumption"),
+            },
+        };
+
+        RenBulkExecutor::try_new(&move_rules, self.board, &pattern, self.allows_hold)
+            .map(|executor| {
+                executor.execute_single()
+            })
+            .map_err(|error| {
+                match error {
+                    FromError::BoardIsTooHigh => ToError::BoardIsTooHigh,
+                }
+            })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::ShapeOrder;
+    use crate::ren::{RenExecutorBinder, RenExecutorCreationError};
+
+    #[test]
+    fn reuse() {
+        use Shape::*;
+
+        let mut binder = RenExecutorBinder::srs();
+        binder.board = Board64::from_str("
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX.XXXXXX
+        ").unwrap();
+
+        binder.shape_order = Rc::new(ShapeOrder::new(vec![I, L, J, T]));
+        let solution = binder.try_execute().unwrap();
+        assert!(2 <= solution.consecutive_clears());
+        assert_eq!(solution.placements().len() as u32, solution.consecutive_clears());
+
+        // Every placement clears lines.
+        let mut board = binder.board;
+        for placement in solution.placements() {
+            assert!(0 < placement.place_on_and_clear_lines(&mut board).unwrap().count());
+        }
+
+        binder.shape_order = Rc::new(ShapeOrder::new(vec![O]));
+        let solution = binder.try_execute().unwrap();
+        assert_eq!(solution.consecutive_clears(), 0);
+        assert!(solution.placements().is_empty());
+    }
+
+    #[test]
+    fn error() {
+        let binder = RenExecutorBinder::srs();
+        assert_eq!(binder.try_execute().unwrap_err(), RenExecutorCreationError::EmptyOrder);
+    }
+}
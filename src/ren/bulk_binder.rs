@@ -0,0 +1,103 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+
+use crate::{Pattern, PatternElement, ShapeCounter};
+use crate::ren::{RenBulkExecutor, RenExecutorBulkCreationError, RenResults};
+
+/// The binder to hold and tie settings for `RenBulkExecutor`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct RenBulkExecutorBinder<T: RotationSystem> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub board: Board64,
+    pub pattern: Rc<Pattern>,
+    pub allows_hold: bool,
+}
+
+impl RenBulkExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `RenBulkExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        RenBulkExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem> RenBulkExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + [required] board: blank. You must set this.
+    ///   + allow move: softdrop
+    ///   + pattern: factorial of all shapes (like `*p7`)
+    ///   + allows hold: yes
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            board: Board64::blank(),
+            pattern: Rc::from(Pattern::try_from(vec![
+                PatternElement::Factorial(ShapeCounter::one_of_each()),
+            ]).unwrap()),
+            allows_hold: true,
+        }
+    }
+
+    // See `RenBulkExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<RenResults, RenExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute())
+    }
+
+    fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<RenBulkExecutor<'a, T>, RenExecutorBulkCreationError> {
+        RenBulkExecutor::try_new(
+            move_rules,
+            self.board,
+            self.pattern.as_ref(),
+            self.allows_hold,
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{Pattern, PatternElement, ShapeCounter};
+    use crate::ren::RenBulkExecutorBinder;
+
+    #[test]
+    fn reuse() {
+        use PatternElement::*;
+
+        let mut binder = RenBulkExecutorBinder::srs();
+        binder.board = Board64::from_str("
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX.XXXXXX
+        ").unwrap();
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            Permutation(ShapeCounter::one_of_each(), 2),
+        ]).unwrap());
+
+        let with_hold = binder.try_execute().unwrap();
+        assert_eq!(with_hold.count_accepted(), 42);
+
+        binder.allows_hold = false;
+        let without_hold = binder.try_execute().unwrap();
+        assert_eq!(without_hold.count_accepted(), 42);
+
+        // Hold never makes the combo shorter.
+        assert!(without_hold.count_at_least(1) <= with_hold.count_at_least(1));
+        assert!(without_hold.count_at_least(2) <= with_hold.count_at_least(2));
+    }
+}
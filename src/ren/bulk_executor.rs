@@ -0,0 +1,277 @@
+use bitris::prelude::*;
+use fxhash::FxHashMap;
+use thiserror::Error;
+
+use crate::{OrderCursor, Pattern, PopOp, ShapeOrder};
+use crate::ren::{RenResults, RenSolution};
+
+/// Dataset for detecting the same state during REN search.
+/// The maximum combo from here is determined by the board and the shapes not yet used (including the head).
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct SearchingState {
+    board: Board64,
+    unused: ShapeOrder,
+}
+
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum RenExecutorBulkCreationError {
+    #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
+    BoardIsTooHigh,
+}
+
+/// The executor to find the moves that maximize consecutive line clears (REN/combo).
+///
+/// The combo continues while each placed piece clears at least one line, and ends at the first piece that does not.
+/// The board is typically a 4-wide well with a few residual blocks.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct RenBulkExecutor<'a, T: RotationSystem> {
+    move_rules: &'a MoveRules<'a, T>,
+    board: Board64,
+    pattern: &'a Pattern,
+    allows_hold: bool,
+    spawn_position: BlPosition,
+}
+
+impl<'a, T: RotationSystem> RenBulkExecutor<'a, T> {
+    /// Make RenBulkExecutor.
+    /// Filled rows on the board are cleared in advance.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `RenExecutorBulkCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Shape, Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{Pattern, PatternElement};
+    /// use bitris_commands::ren::RenBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXX....XXX
+    ///     XXX....XXX
+    ///     XXX....XXX
+    ///     XXX....XXX
+    ///     XXX....XXX
+    ///     XXX....XXX
+    ///     XXX.XXXXXX
+    /// ").expect("Failed to create a board");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Wildcard,
+    ///     PatternElement::Wildcard,
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = RenBulkExecutor::try_new(&move_rules, board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert_eq!(results.count_accepted(), 49);
+    /// assert_eq!(results.max_consecutive_clears(), Some(2));
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        board: Board64,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+    ) -> Result<Self, RenExecutorBulkCreationError> {
+        use RenExecutorBulkCreationError::*;
+
+        let mut board = board;
+        board.clear_lines();
+
+        if 56 < board.well_top() {
+            return Err(BoardIsTooHigh);
+        }
+
+        // Spawn above the blocks so that it is not stuck when harddrop only.
+        let spawn_position = bl(5, board.well_top() as i32 + 4);
+
+        Ok(Self { move_rules, board, pattern, allows_hold, spawn_position })
+    }
+
+    /// Start the search for the maximum combos in bulk.
+    pub fn execute(&self) -> RenResults {
+        let sequences = self.pattern.to_sequences();
+
+        let mut results = RenResults::new(&sequences);
+
+        // Sequences that share the rest of shapes can reuse the results.
+        let mut memo = FxHashMap::<SearchingState, Vec<BlPlacement>>::default();
+
+        for sequence in sequences {
+            if results.get(&sequence).is_some() {
+                continue;
+            }
+
+            let order = sequence.to_shape_order();
+            let best = self.pop_shape(order.new_cursor(), self.board, &mut memo);
+            results.accept_if_present(&sequence, best.len() as u32);
+        }
+
+        results
+    }
+
+    /// This function is dedicated to a single sequence.
+    /// The interface is not directly exposed since the binder provides it.
+    pub(crate) fn execute_single(&self) -> RenSolution {
+        let sequences = self.pattern.to_sequences();
+        assert_eq!(sequences.len(), 1, "This function is dedicated to a single sequence.");
+        let order = sequences.first().unwrap().to_shape_order();
+
+        let mut memo = FxHashMap::<SearchingState, Vec<BlPlacement>>::default();
+        RenSolution::new(self.pop_shape(order.new_cursor(), self.board, &mut memo))
+    }
+
+    // Returns the placements of the longest combo from the state.
+    fn pop_shape(
+        &self,
+        cursor: OrderCursor,
+        board: Board64,
+        memo: &mut FxHashMap<SearchingState, Vec<BlPlacement>>,
+    ) -> Vec<BlPlacement> {
+        if !cursor.has_next() {
+            return Vec::new();
+        }
+
+        let state = SearchingState { board, unused: cursor.unused_shapes() };
+        if let Some(best) = memo.get(&state) {
+            return best.clone();
+        }
+
+        let mut best = Vec::<BlPlacement>::new();
+
+        let (popped, next_cursor) = cursor.pop(PopOp::First);
+        if let Some(shape) = popped {
+            self.increment(shape, board, next_cursor, memo, &mut best);
+        }
+
+        if self.allows_hold && best.len() < cursor.len_unused() {
+            let (popped, next_cursor) = cursor.pop(PopOp::Second);
+            if let Some(shape) = popped {
+                self.increment(shape, board, next_cursor, memo, &mut best);
+            }
+        }
+
+        memo.insert(state, best.clone());
+        best
+    }
+
+    fn increment(
+        &self,
+        shape: Shape,
+        board: Board64,
+        next_cursor: OrderCursor,
+        memo: &mut FxHashMap<SearchingState, Vec<BlPlacement>>,
+        best: &mut Vec<BlPlacement>,
+    ) {
+        let placement = shape.with(Orientation::North).with(self.spawn_position);
+        let moves = self.move_rules.generate_minimized_moves(board, placement);
+
+        // It can be extended at most by the rest of shapes. If reached, no further search is needed.
+        let limit = next_cursor.len_unused() + 1;
+
+        for placement in moves {
+            if limit <= best.len() {
+                return;
+            }
+
+            let mut next_board = board;
+            let lines_cleared = placement.place_on_and_clear_lines(&mut next_board).unwrap();
+            if lines_cleared.count() == 0 {
+                continue;
+            }
+
+            let rest = self.pop_shape(next_cursor, next_board, memo);
+            if best.len() < rest.len() + 1 {
+                best.clear();
+                best.push(placement);
+                best.extend(rest);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{Pattern, PatternElement, ShapeCounter, ShapeSequence};
+    use crate::ren::{RenBulkExecutor, RenExecutorBulkCreationError};
+
+    #[test]
+    fn four_wide() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let board = Board64::from_str("
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX.XXXXXX
+        ").unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let pattern = Pattern::try_from(vec![
+            Permutation(ShapeCounter::one_of_each(), 3),
+        ]).unwrap();
+        let executor = RenBulkExecutor::try_new(&move_rules, board, &pattern, true).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_keys(), 210);
+        assert_eq!(results.count_pending(), 0);
+
+        let distribution = results.distribution();
+        assert_eq!(distribution.iter().sum::<u64>(), 210);
+        assert_eq!(results.count_at_least(0), 210);
+
+        // Neither S nor O can fill the hole in the bottom row, so the combo cannot start.
+        assert_eq!(results.get(&ShapeSequence::new(vec![S, O, I])), Some(0));
+        assert!(0 < distribution[0]);
+
+        // The I can start the combo after it's held.
+        assert!(1 <= results.get(&ShapeSequence::new(vec![O, I, S])).unwrap());
+    }
+
+    #[test]
+    fn hold() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let board = Board64::from_str("
+            XXX....XXX
+            XXX....XXX
+            XXX....XXX
+            XXX.XXXXXX
+        ").unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let pattern = Pattern::try_from(vec![One(O), One(I)]).unwrap();
+
+        let executor = RenBulkExecutor::try_new(&move_rules, board, &pattern, true).unwrap();
+        assert_eq!(executor.execute().get(&ShapeSequence::new(vec![O, I])), Some(1));
+
+        let executor = RenBulkExecutor::try_new(&move_rules, board, &pattern, false).unwrap();
+        assert_eq!(executor.execute().get(&ShapeSequence::new(vec![O, I])), Some(0));
+    }
+
+    #[test]
+    fn too_high() {
+        let mut board = Board64::blank();
+        board.set_at(xy(0, 57));
+        let pattern = Pattern::try_from(vec![PatternElement::Wildcard]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+        assert_eq!(
+            RenBulkExecutor::try_new(&move_rules, board, &pattern, true).unwrap_err(),
+            RenExecutorBulkCreationError::BoardIsTooHigh,
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use bitris::prelude::*;
+
+use crate::ShapeSequence;
+use crate::internals::SequenceKeys;
+
+/// Represents the moves that keep the combo going the longest.
+/// The placements are in the order in which they were placed, and each of them clears at least one line.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub struct RenSolution {
+    placements: Vec<BlPlacement>,
+}
+
+impl RenSolution {
+    #[inline]
+    pub(crate) fn new(placements: Vec<BlPlacement>) -> Self {
+        Self { placements }
+    }
+
+    /// Returns the count of consecutive line clears.
+    /// Note that the combo displayed in many games is one less than this (the first clear is not counted).
+    #[inline]
+    pub fn consecutive_clears(&self) -> u32 {
+        self.placements.len() as u32
+    }
+
+    #[inline]
+    pub fn placements(&self) -> &[BlPlacement] {
+        self.placements.as_slice()
+    }
+}
+
+/// Holds the maximum consecutive line clears of shape sequences.
+///
+/// Each shape sequence has the maximum count of consecutive clears found by the search (`Some(clears)`), or `None` while pending.
+/// It's the same as `RenSolution::consecutive_clears()`, so the combo displayed in many games is one less than this.
+/// A count of 0 means the first piece cannot clear any line.
+///
+/// Therefore, the shape sequences to be searched (key) are established at `new()`.
+/// They are kept in the order given at `new()` without duplicates, so iteration is deterministic.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct RenResults {
+    keys: SequenceKeys,
+    clears: Vec<Option<u32>>,
+}
+
+impl RenResults {
+    /// Panics if a shape sequence has more shapes than `WideBitShapes` can hold.
+    #[inline]
+    pub fn new(sequences: &[ShapeSequence]) -> Self {
+        let keys = SequenceKeys::new(sequences.iter());
+        let clears = vec![None; keys.len()];
+        Self { keys, clears }
+    }
+
+    #[inline]
+    pub fn accept_if_present(&mut self, sequence: &ShapeSequence, consecutive_clears: u32) -> bool {
+        if let Some(index) = self.keys.position(sequence) {
+            self.clears[index] = Some(consecutive_clears);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the maximum consecutive clears of a shape sequence.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::ren::RenResults;
+    /// use Shape::*;
+    ///
+    /// let mut result = RenResults::new(&vec![
+    ///     ShapeSequence::new(vec!(I, T, S)),
+    ///     ShapeSequence::new(vec!(I, T, Z)),
+    /// ]);
+    ///
+    /// assert_eq!(result.get(&ShapeSequence::new(vec!(I, T, S))), None);
+    /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, S)), 3);
+    /// assert_eq!(result.get(&ShapeSequence::new(vec!(I, T, S))), Some(3));
+    /// ```
+    #[inline]
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<u32> {
+        self.keys.position(sequence).and_then(|index| self.clears[index])
+    }
+
+    /// Returns the count of shape sequences for each maximum consecutive clears.
+    /// The index is the count of consecutive clears, and pending shape sequences are not included.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::ren::RenResults;
+    /// use Shape::*;
+    ///
+    /// let mut result = RenResults::new(&vec![
+    ///     ShapeSequence::new(vec!(I, T, S)),
+    ///     ShapeSequence::new(vec!(I, T, Z)),
+    ///     ShapeSequence::new(vec!(I, T, O)),
+    /// ]);
+    ///
+    /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, S)), 3);
+    /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), 1);
+    /// assert_eq!(result.distribution(), vec![0, 1, 0, 1]);
+    /// ```
+    pub fn distribution(&self) -> Vec<u64> {
+        let mut distribution = Vec::<u64>::new();
+        for &clears in self.clears.iter().flatten() {
+            let index = clears as usize;
+            if distribution.len() <= index {
+                distribution.resize(index + 1, 0);
+            }
+            distribution[index] += 1;
+        }
+        distribution
+    }
+
+    /// Returns the largest of the maximum consecutive clears of accepted shape sequences.
+    /// If nothing is accepted, returns `None`.
+    #[inline]
+    pub fn max_consecutive_clears(&self) -> Option<u32> {
+        self.clears.iter().flatten().copied().max()
+    }
+
+    /// Returns the count of shape sequences whose maximum consecutive clears are greater than or equal to the argument.
+    #[inline]
+    pub fn count_at_least(&self, consecutive_clears: u32) -> u64 {
+        self.clears.iter()
+            .flatten()
+            .filter(|&&it| consecutive_clears <= it)
+            .count() as u64
+    }
+
+    /// Returns the count of shape sequences that are accepted.
+    #[inline]
+    pub fn count_accepted(&self) -> u64 {
+        self.clears.iter()
+            .filter(|it| it.is_some())
+            .count() as u64
+    }
+
+    /// Returns the count of shape sequences that are pending.
+    #[inline]
+    pub fn count_pending(&self) -> u64 {
+        self.clears.iter()
+            .filter(|it| it.is_none())
+            .count() as u64
+    }
+
+    /// Return the count of all shape sequences independent of the result.
+    #[inline]
+    pub fn count_keys(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns the pair of shape sequence and result in the order given at `new()`.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use bitris_commands::ren::RenResults;
+    /// use Shape::*;
+    ///
+    /// let mut result = RenResults::new(&vec![
+    ///     ShapeSequence::new(vec!(I, T, S)),
+    ///     ShapeSequence::new(vec!(I, T, Z)),
+    /// ]);
+    ///
+    /// result.accept_if_present(&ShapeSequence::new(vec!(I, T, Z)), 2);
+    ///
+    /// assert_eq!(
+    ///     result.iter().collect::<Vec<_>>(),
+    ///     vec![
+    ///         (ShapeSequence::new(vec!(I, T, S)), None),
+    ///         (ShapeSequence::new(vec!(I, T, Z)), Some(2)),
+    ///     ],
+    /// );
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(ShapeSequence, Option<u32>)> + '_ {
+        self.clears.iter()
+            .enumerate()
+            .map(|(index, &clears)| (self.keys.sequence_at(index), clears))
+    }
+
+    /// Returns the pair of shape sequence and result in the order given at `new()`.
+    #[inline]
+    pub fn into_iter(self) -> impl Iterator<Item=(ShapeSequence, Option<u32>)> {
+        let keys = self.keys;
+        self.clears.into_iter()
+            .enumerate()
+            .map(move |(index, clears)| (keys.sequence_at(index), clears))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+
+    use crate::ShapeSequence;
+    use crate::ren::RenResults;
+
+    #[test]
+    fn with_pending() {
+        use Shape::*;
+
+        let sequences = vec![
+            ShapeSequence::new(vec![I, T, S]),
+            ShapeSequence::new(vec![I, T, Z]),
+            ShapeSequence::new(vec![I, T, O]),
+            ShapeSequence::new(vec![I, T, L]),
+            ShapeSequence::new(vec![I, T, J]),
+        ];
+        let mut results = RenResults::new(&sequences);
+        assert_eq!(results.distribution(), Vec::<u64>::new());
+        assert_eq!(results.count_at_least(0), 0);
+        assert_eq!(results.max_consecutive_clears(), None);
+
+        results.accept_if_present(&sequences[0], 0);
+        results.accept_if_present(&sequences[1], 2);
+        results.accept_if_present(&sequences[2], 2);
+        results.accept_if_present(&sequences[3], 3);
+
+        // The last one is pending, so it's not counted.
+        assert_eq!(results.count_pending(), 1);
+        assert_eq!(results.distribution(), vec![1, 0, 2, 1]);
+        assert_eq!(results.count_at_least(0), 4);
+        assert_eq!(results.count_at_least(2), 3);
+        assert_eq!(results.count_at_least(3), 1);
+        assert_eq!(results.count_at_least(4), 0);
+        assert_eq!(results.max_consecutive_clears(), Some(3));
+
+        // Accepting again overwrites the result.
+        results.accept_if_present(&sequences[3], 1);
+        assert_eq!(results.distribution(), vec![1, 1, 2]);
+        assert_eq!(results.max_consecutive_clears(), Some(2));
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+
+use bitris::Shape;
+
+use crate::{Pattern, PatternSet, ShapeSequence};
+
+type Predicate<'a> = Box<dyn Fn(&ShapeSequence) -> bool + 'a>;
+
+/// Returns true if the sequence starts with one of the sequences of the pattern.
+fn starts_with(sequence: &ShapeSequence, pattern: &Pattern) -> bool {
+    let shapes = sequence.shapes();
+    (pattern.min_dim_shapes()..=pattern.dim_shapes().min(shapes.len()))
+        .any(|dim| pattern.rank(&ShapeSequence::new(shapes[..dim].to_vec())).is_some())
+}
+
+/// Narrows down the sequences of patterns by the conditions.
+/// A sequence passes the filter if it satisfies all conditions. If it has no conditions, all pass.
+/// ```
+/// use std::str::FromStr;
+/// use bitris_commands::prelude::*;
+/// use Shape::*;
+///
+/// let pattern = Pattern::from_str("*p7").unwrap();
+///
+/// // All `*p7` except those starting with S or Z.
+/// let filter = SequenceFilter::new()
+///     .excludes(Pattern::from_str("[SZ]").unwrap());
+/// assert_eq!(pattern.to_sequences_filtered(&filter).len(), 3600);
+///
+/// // Only sequences where T comes before I.
+/// let filter = SequenceFilter::new()
+///     .requires_before(T, I);
+/// assert_eq!(pattern.to_sequences_filtered(&filter).len(), 2520);
+///
+/// // Any conditions by closures.
+/// let filter = SequenceFilter::new()
+///     .filter(|sequence| sequence.shapes()[6] == O);
+/// assert_eq!(pattern.to_sequences_filtered(&filter).len(), 720);
+/// ```
+#[derive(Default)]
+pub struct SequenceFilter<'a> {
+    predicates: Vec<Predicate<'a>>,
+}
+
+impl<'a> SequenceFilter<'a> {
+    /// Makes the filter that passes all sequences.
+    #[inline]
+    pub fn new() -> Self {
+        Self { predicates: Vec::new() }
+    }
+
+    /// Keeps only the sequences that satisfy the predicate.
+    pub fn filter(mut self, predicate: impl Fn(&ShapeSequence) -> bool + 'a) -> Self {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Removes the sequences that start with one of the sequences of the pattern.
+    /// The pattern can be shorter than the sequences (like `[SZ]` to exclude those starting with S or Z).
+    pub fn excludes(self, pattern: Pattern) -> Self {
+        self.filter(move |sequence| !starts_with(sequence, &pattern))
+    }
+
+    /// Removes the sequences that start with one of the sequences of the patterns.
+    pub fn excludes_all(self, pattern_set: PatternSet) -> Self {
+        self.filter(move |sequence| !pattern_set.patterns().iter().any(|pattern| starts_with(sequence, pattern)))
+    }
+
+    /// Keeps only the sequences that start with one of the sequences of the pattern.
+    pub fn requires(self, pattern: Pattern) -> Self {
+        self.filter(move |sequence| starts_with(sequence, &pattern))
+    }
+
+    /// Keeps only the sequences where the first `former` comes before the first `latter`.
+    /// The sequences without `latter` are kept if they have `former`.
+    pub fn requires_before(self, former: Shape, latter: Shape) -> Self {
+        self.filter(move |sequence| {
+            let shapes = sequence.shapes();
+            match (shapes.iter().position(|&shape| shape == former), shapes.iter().position(|&shape| shape == latter)) {
+                (Some(former), Some(latter)) => former < latter,
+                (Some(_), None) => true,
+                (None, _) => false,
+            }
+        })
+    }
+
+    /// Returns true if the sequence satisfies all conditions.
+    #[inline]
+    pub fn test(&self, sequence: &ShapeSequence) -> bool {
+        self.predicates.iter().all(|predicate| predicate(sequence))
+    }
+
+    /// Returns the count of the conditions.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.predicates.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.predicates.is_empty()
+    }
+}
+
+impl fmt::Debug for SequenceFilter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SequenceFilter")
+            .field("predicates", &self.predicates.len())
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::Shape;
+
+    use crate::{Pattern, PatternSet, SequenceFilter, ShapeSequence};
+
+    #[test]
+    fn combines() {
+        use Shape::*;
+
+        let pattern = Pattern::from_str("*p3").unwrap();
+        assert_eq!(pattern.to_sequences_filtered(&SequenceFilter::new()), pattern.to_sequences());
+
+        let filter = SequenceFilter::new()
+            .excludes_all(PatternSet::from_str("[SZ]\nO").unwrap())
+            .requires(Pattern::from_str("*,T").unwrap());
+        let sequences = pattern.to_sequences_filtered(&filter);
+        assert_eq!(sequences.len(), 3 * 5);
+        assert!(sequences.iter().all(|sequence| {
+            ![S, Z, O].contains(&sequence.shapes()[0]) && sequence.shapes()[1] == T
+        }));
+
+        // The order of the sequences is kept.
+        let expected: Vec<ShapeSequence> = pattern.to_sequences().into_iter()
+            .filter(|sequence| filter.test(sequence))
+            .collect();
+        assert_eq!(sequences, expected);
+
+        let filter = SequenceFilter::new().requires_before(T, I);
+        assert!(filter.test(&ShapeSequence::new(vec![T, O, I])));
+        assert!(filter.test(&ShapeSequence::new(vec![O, T])));
+        assert!(!filter.test(&ShapeSequence::new(vec![I, T])));
+        assert!(!filter.test(&ShapeSequence::new(vec![O, I])));
+        assert_eq!(filter.len(), 1);
+    }
+}
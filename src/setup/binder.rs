@@ -0,0 +1,156 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{ClippedBoard, Pattern, PatternCreationError, PatternElement, ShapeOrder};
+use crate::setup::{SetupBulkExecutor, SetupExecutorBulkCreationError, SetupSolution};
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum SetupExecutorCreationError {
+    #[error("The mask has no cells to fill.")]
+    EmptyMask,
+    #[error("Unexpected the count of mask cells.")]
+    UnexpectedMaskSpaces,
+    #[error("The mask overlaps blocks on the board.")]
+    MaskOverlapsBoard,
+    #[error("The mask exceeds the height.")]
+    MaskExceedsHeight,
+    #[error("The order is too short to fill the mask.")]
+    ShortOrderDimension,
+    #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
+    BoardIsTooHigh,
+}
+
+/// The binder to hold and tie settings for finding setups of a single order.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct SetupExecutorBinder<T: RotationSystem> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub clipped_board: ClippedBoard,
+    pub mask: Board64,
+    pub shape_order: Rc<ShapeOrder>,
+    pub allows_hold: bool,
+}
+
+impl SetupExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `SetupExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        SetupExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem> SetupExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + [required] mask: blank. You must set this.
+    ///   + [required] shape_order: empty order. You must set this.
+    ///   + allow move: softdrop
+    ///   + board: blank
+    ///   + height: 4 lines
+    ///   + allows hold: yes
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
+            mask: Board64::blank(),
+            shape_order: Rc::from(ShapeOrder::new(vec![])),
+            allows_hold: true,
+        }
+    }
+
+    /// Returns all solutions to build the setup from the order.
+    /// If the setup cannot be built, returns an empty vec.
+    // See `SetupBulkExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<Vec<SetupSolution>, SetupExecutorCreationError> {
+        use SetupExecutorBulkCreationError as FromError;
+        use SetupExecutorCreationError as ToError;
+
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let pattern = match Pattern::try_from(
+            self.shape_order.shapes().iter().map(|&shape| PatternElement::One(shape)).collect_vec()
+        ) {
+            Ok(pattern) => pattern,
+            Err(error) => return match error {
+                PatternCreationError::NoShapeSequences => Err(ToError::ShortOrderDimension),
+                PatternCreationError::ContainsInvalidPermutation => panic!("Unreachable assumption"),
+            },
+        };
+
+        SetupBulkExecutor::try_new(&move_rules, self.clipped_board, self.mask, &pattern, self.allows_hold)
+            .map(|executor| {
+                executor.execute().solutions().to_vec()
+            })
+            .map_err(|error| {
+                match error {
+                    FromError::EmptyMask => ToError::EmptyMask,
+                    FromError::UnexpectedMaskSpaces => ToError::UnexpectedMaskSpaces,
+                    FromError::MaskOverlapsBoard => ToError::MaskOverlapsBoard,
+                    FromError::MaskExceedsHeight => ToError::MaskExceedsHeight,
+                    FromError::ShortPatternDimension => ToError::ShortOrderDimension,
+                    FromError::BoardIsTooHigh => ToError::BoardIsTooHigh,
+                }
+            })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, ShapeOrder};
+    use crate::setup::{SetupExecutorBinder, SetupExecutorCreationError};
+
+    #[test]
+    fn reuse() {
+        use Shape::*;
+
+        let mut binder = SetupExecutorBinder::srs();
+        let board = Board64::from_str("
+            ####......
+            ####......
+            ####......
+            ####......
+        ").unwrap();
+        binder.clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        binder.mask = Board64::from_str("
+            ....####..
+            ....####..
+        ").unwrap();
+
+        binder.shape_order = Rc::new(ShapeOrder::new(vec![O, T, O]));
+        assert_eq!(binder.try_execute().unwrap().len(), 1);
+
+        binder.shape_order = Rc::new(ShapeOrder::new(vec![T, S, Z]));
+        assert!(binder.try_execute().unwrap().is_empty());
+    }
+
+    #[test]
+    fn error() {
+        use Shape::*;
+        use SetupExecutorCreationError::*;
+
+        let mut binder = SetupExecutorBinder::srs();
+        binder.shape_order = Rc::new(ShapeOrder::new(vec![O]));
+        assert_eq!(binder.try_execute().unwrap_err(), EmptyMask);
+
+        binder.mask = Board64::from_str("
+            ####......
+            ####......
+        ").unwrap();
+        assert_eq!(binder.try_execute().unwrap_err(), ShortOrderDimension);
+
+        binder.shape_order = Rc::new(ShapeOrder::default());
+        assert_eq!(binder.try_execute().unwrap_err(), ShortOrderDimension);
+    }
+}
@@ -0,0 +1,103 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+
+use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+use crate::setup::{SetupBulkExecutor, SetupExecutorBulkCreationError, SetupResults};
+
+/// The binder to hold and tie settings for `SetupBulkExecutor`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct SetupBulkExecutorBinder<T: RotationSystem> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub clipped_board: ClippedBoard,
+    pub mask: Board64,
+    pub pattern: Rc<Pattern>,
+    pub allows_hold: bool,
+}
+
+impl SetupBulkExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `SetupBulkExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        SetupBulkExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem> SetupBulkExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + [required] mask: blank. You must set this.
+    ///   + allow move: softdrop
+    ///   + board: blank
+    ///   + height: 4 lines
+    ///   + pattern: factorial of all shapes (like `*p7`)
+    ///   + allows hold: yes
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
+            mask: Board64::blank(),
+            pattern: Rc::from(Pattern::try_from(vec![
+                PatternElement::Factorial(ShapeCounter::one_of_each()),
+            ]).unwrap()),
+            allows_hold: true,
+        }
+    }
+
+    // See `SetupBulkExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<SetupResults, SetupExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute())
+    }
+
+    fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<SetupBulkExecutor<'a, T>, SetupExecutorBulkCreationError> {
+        SetupBulkExecutor::try_new(
+            move_rules,
+            self.clipped_board,
+            self.mask,
+            self.pattern.as_ref(),
+            self.allows_hold,
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+    use crate::setup::SetupBulkExecutorBinder;
+
+    #[test]
+    fn reuse() {
+        use PatternElement::*;
+
+        let mut binder = SetupBulkExecutorBinder::srs();
+        binder.clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        binder.mask = Board64::from_str("
+            ##........
+            ##........
+        ").unwrap();
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            Permutation(ShapeCounter::one_of_each(), 2),
+        ]).unwrap());
+
+        let results = binder.try_execute().unwrap();
+        assert_eq!(results.solutions().len(), 1);
+        assert_eq!(results.count_succeed(), 12); // The sequences containing O
+        assert_eq!(results.count_keys(), 42);
+
+        binder.allows_hold = false;
+        let results = binder.try_execute().unwrap();
+        assert_eq!(results.count_succeed(), 6); // The sequences starting with O
+    }
+}
@@ -0,0 +1,373 @@
+use bitris::prelude::*;
+use fxhash::FxHashSet;
+use thiserror::Error;
+
+use crate::{ClippedBoard, OrderCursor, Pattern, PopOp, ShapeSequence};
+use crate::setup::{SetupResults, SetupSolution};
+
+/// Dataset for detecting the same state during setup search.
+/// Line clears are ignored, so the placed pieces and the head of shapes determine the state.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+struct SearchingState {
+    // Sorted to ignore the order in which they were placed.
+    placements: Vec<BlPlacement>,
+
+    first: Option<Shape>,
+}
+
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum SetupExecutorBulkCreationError {
+    #[error("The mask has no cells to fill.")]
+    EmptyMask,
+    #[error("Unexpected the count of mask cells.")]
+    UnexpectedMaskSpaces,
+    #[error("The mask overlaps blocks on the board.")]
+    MaskOverlapsBoard,
+    #[error("The mask exceeds the height.")]
+    MaskExceedsHeight,
+    #[error("The pattern is too short to fill the mask.")]
+    ShortPatternDimension,
+    #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
+    BoardIsTooHigh,
+}
+
+/// The executor to find the ways to build a setup.
+///
+/// A setup is defined by a mask, the cells to be filled by the placed pieces.
+/// Each piece must be placed only on the cells of the mask, and line clears are ignored.
+/// In other words, rows filled during the search remain on the board as they are.
+///
+/// The mask uses the same coordinates as the clipped board (after its filled rows are cleared).
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct SetupBulkExecutor<'a, T: RotationSystem> {
+    move_rules: &'a MoveRules<'a, T>,
+    clipped_board: ClippedBoard,
+    mask: Board64,
+    pattern: &'a Pattern,
+    allows_hold: bool,
+    spawn_position: BlPosition,
+}
+
+impl<'a, T: RotationSystem> SetupBulkExecutor<'a, T> {
+    /// Make SetupBulkExecutor.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `SetupExecutorBulkCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Shape, Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement};
+    /// use bitris_commands::setup::SetupBulkExecutor;
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).expect("Failed to clip");
+    ///
+    /// // The cells to be filled.
+    /// let mask = Board64::from_str("
+    ///     XXXX......
+    ///     XXXX......
+    /// ").expect("Failed to create a mask");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::Wildcard,
+    ///     PatternElement::Wildcard,
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = SetupBulkExecutor::try_new(&move_rules, clipped_board, mask, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert_eq!(results.solutions().len(), 4); // OO, II, LL, JJ
+    /// assert_eq!(results.count_succeed(), 4);
+    /// assert_eq!(results.count_keys(), 49);
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        mask: Board64,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+    ) -> Result<Self, SetupExecutorBulkCreationError> {
+        use SetupExecutorBulkCreationError::*;
+
+        if 56 < clipped_board.height() {
+            return Err(BoardIsTooHigh);
+        }
+
+        let mask_spaces = mask.count_blocks();
+        if mask_spaces == 0 {
+            return Err(EmptyMask);
+        }
+
+        if mask_spaces % 4 != 0 {
+            return Err(UnexpectedMaskSpaces);
+        }
+
+        if clipped_board.height() < mask.well_top() {
+            return Err(MaskExceedsHeight);
+        }
+
+        let overlaps = (0..10).any(|x| clipped_board.board_ref().cols[x] & mask.cols[x] != 0);
+        if overlaps {
+            return Err(MaskOverlapsBoard);
+        }
+
+        if (pattern.min_dim_shapes() as u32) < mask_spaces / 4 {
+            return Err(ShortPatternDimension);
+        }
+
+        // Spawn above the height so that it is not stuck when harddrop only.
+        let spawn_position = bl(5, clipped_board.height() as i32 + 4);
+
+        Ok(Self { move_rules, clipped_board, mask, pattern, allows_hold, spawn_position })
+    }
+
+    /// Start the search for setups in bulk.
+    /// All sequences are searched to the end to collect every solution.
+    pub fn execute(&self) -> SetupResults {
+        let mut solutions = FxHashSet::<SetupSolution>::default();
+        let mut results = Vec::<(ShapeSequence, bool)>::new();
+        let mut searched = FxHashSet::<ShapeSequence>::default();
+
+        let mut visited_states = FxHashSet::<SearchingState>::default();
+        let mut placements = Vec::<BlPlacement>::with_capacity(self.pattern.dim_shapes());
+
+        for sequence in self.pattern.to_sequences() {
+            if !searched.insert(sequence.clone()) {
+                continue;
+            }
+
+            visited_states.clear();
+
+            let order = sequence.to_shape_order();
+            let succeed = self.pop_shape(
+                order.new_cursor(),
+                self.clipped_board.board(),
+                self.mask.count_blocks(),
+                &mut placements,
+                &mut visited_states,
+                &mut solutions,
+            );
+
+            results.push((sequence, succeed));
+        }
+
+        let mut solutions: Vec<SetupSolution> = solutions.into_iter().collect();
+        solutions.sort();
+
+        SetupResults::new(solutions, results)
+    }
+
+    fn pop_shape(
+        &self,
+        cursor: OrderCursor,
+        board: Board64,
+        remaining: u32,
+        placements: &mut Vec<BlPlacement>,
+        visited_states: &mut FxHashSet<SearchingState>,
+        solutions: &mut FxHashSet<SetupSolution>,
+    ) -> bool {
+        if (cursor.len_unused() as u32) * 4 < remaining {
+            return false;
+        }
+
+        let mut found = false;
+
+        let (popped, next_cursor) = cursor.pop(PopOp::First);
+        if let Some(shape) = popped {
+            found |= self.increment(shape, board, next_cursor, remaining, placements, visited_states, solutions);
+        } else {
+            return false;
+        }
+
+        if self.allows_hold {
+            let (popped, next_cursor) = cursor.pop(PopOp::Second);
+            if let Some(shape) = popped {
+                found |= self.increment(shape, board, next_cursor, remaining, placements, visited_states, solutions);
+            }
+        }
+
+        found
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn increment(
+        &self,
+        shape: Shape,
+        board: Board64,
+        next_cursor: OrderCursor,
+        remaining: u32,
+        placements: &mut Vec<BlPlacement>,
+        visited_states: &mut FxHashSet<SearchingState>,
+        solutions: &mut FxHashSet<SetupSolution>,
+    ) -> bool {
+        let mut found = false;
+
+        let placement = shape.with(Orientation::North).with(self.spawn_position);
+        let moves = self.move_rules.generate_minimized_moves(board, placement);
+
+        for placement in moves {
+            // Only the cells of the mask can be filled.
+            if placement.locations().into_iter().any(|location| self.mask.is_free_at(location)) {
+                continue;
+            }
+
+            placements.push(placement);
+
+            if remaining <= 4 {
+                solutions.insert(SetupSolution::new(placements.clone()));
+                found = true;
+            } else {
+                let state = SearchingState {
+                    placements: {
+                        let mut sorted = placements.clone();
+                        sorted.sort();
+                        sorted
+                    },
+                    first: next_cursor.first(),
+                };
+
+                if visited_states.insert(state) {
+                    let mut next_board = board;
+                    for location in placement.locations() {
+                        next_board.set_at(location);
+                    }
+
+                    found |= self.pop_shape(next_cursor, next_board, remaining - 4, placements, visited_states, solutions);
+                }
+            }
+
+            placements.pop();
+        }
+
+        found
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter, ShapeSequence};
+    use crate::setup::{SetupBulkExecutor, SetupExecutorBulkCreationError};
+
+    #[test]
+    fn setup_on_board() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let board = Board64::from_str("
+            ..........
+            ..........
+            ######....
+            ######....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let mask = Board64::from_str("
+            ......####
+            ......####
+        ").unwrap();
+        let pattern = Pattern::try_from(vec![
+            Permutation(ShapeCounter::one_of_each(), 3),
+        ]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let executor = SetupBulkExecutor::try_new(
+            &move_rules, clipped_board, mask, &pattern, true,
+        ).unwrap();
+        let results = executor.execute();
+
+        // Only 2 pieces are used: I+I, O+O, L+L and J+J need duplicates, and no sequence has them.
+        assert_eq!(results.count_succeed(), 0);
+        assert_eq!(results.count_keys(), 210);
+        assert!(results.solutions().is_empty());
+
+        let pattern = Pattern::try_from(vec![
+            One(I),
+            One(I),
+        ]).unwrap();
+        let executor = SetupBulkExecutor::try_new(
+            &move_rules, clipped_board, mask, &pattern, false,
+        ).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_succeed(), 1);
+        assert_eq!(results.get(&ShapeSequence::new(vec![I, I])), Some(true));
+        assert_eq!(results.solutions().len(), 1);
+        assert_eq!(results.solutions()[0].shape_counter(), ShapeCounter::from(vec![I, I]));
+    }
+
+    #[test]
+    fn hold() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let clipped_board = ClippedBoard::try_new(Board64::blank(), 4).unwrap();
+        let mask = Board64::from_str("
+            ##........
+            ##........
+        ").unwrap();
+        let pattern = Pattern::try_from(vec![
+            One(T),
+            One(O),
+        ]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let executor = SetupBulkExecutor::try_new(
+            &move_rules, clipped_board, mask, &pattern, true,
+        ).unwrap();
+        assert_eq!(executor.execute().count_succeed(), 1);
+
+        let executor = SetupBulkExecutor::try_new(
+            &move_rules, clipped_board, mask, &pattern, false,
+        ).unwrap();
+        assert_eq!(executor.execute().count_succeed(), 0);
+    }
+
+    #[test]
+    fn errors() {
+        use PatternElement::*;
+        use SetupExecutorBulkCreationError::*;
+
+        let board = Board64::from_str("
+            ######....
+            ######....
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let pattern = Pattern::try_from(vec![Wildcard]).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        let error = |mask: &str| {
+            let mask = Board64::from_str(mask).unwrap();
+            SetupBulkExecutor::try_new(&move_rules, clipped_board, mask, &pattern, true).unwrap_err()
+        };
+
+        assert_eq!(error("
+            ..........
+        "), EmptyMask);
+        assert_eq!(error("
+            .......###
+        "), UnexpectedMaskSpaces);
+        assert_eq!(error("
+            .....####.
+        "), MaskOverlapsBoard);
+        assert_eq!(error("
+            ####......
+            ..........
+            ..........
+            ..........
+            ..........
+        "), MaskExceedsHeight);
+        assert_eq!(error("
+            ........##
+            ........##
+            ........##
+            ........##
+        "), ShortPatternDimension);
+    }
+}
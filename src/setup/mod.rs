@@ -0,0 +1,9 @@
+pub use binder::*;
+pub use bulk_binder::*;
+pub use bulk_executor::*;
+pub use setup_results::*;
+
+mod binder;
+mod bulk_binder;
+mod bulk_executor;
+mod setup_results;
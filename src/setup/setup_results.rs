@@ -0,0 +1,126 @@
+use bitris::prelude::*;
+use bitvec::vec::BitVec;
+use itertools::Itertools;
+
+use crate::{ShapeCounter, ShapeSequence};
+use crate::internals::SequenceKeys;
+
+/// Represents a set of placements to build the setup.
+/// The placements are sorted, so the same set is equal regardless of the order in which they were placed.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct SetupSolution {
+    placements: Vec<BlPlacement>,
+}
+
+impl SetupSolution {
+    #[inline]
+    pub(crate) fn new(mut placements: Vec<BlPlacement>) -> Self {
+        placements.sort();
+        Self { placements }
+    }
+
+    #[inline]
+    pub fn placements(&self) -> &[BlPlacement] {
+        self.placements.as_slice()
+    }
+
+    /// Returns the count of each shape used in the solution.
+    #[inline]
+    pub fn shape_counter(&self) -> ShapeCounter {
+        ShapeCounter::from(self.placements.iter().map(|placement| placement.piece.shape).collect_vec())
+    }
+}
+
+/// Holds the results of setups.
+///
+/// It contains all distinct solutions found, and whether each shape sequence could build the setup.
+/// The shape sequences are kept in the order in which they were searched, and whether they succeed is stored as bits.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct SetupResults {
+    solutions: Vec<SetupSolution>,
+    keys: SequenceKeys,
+    succeed: BitVec,
+}
+
+impl SetupResults {
+    #[inline]
+    pub(crate) fn new(solutions: Vec<SetupSolution>, sequences: Vec<(ShapeSequence, bool)>) -> Self {
+        let keys = SequenceKeys::new(sequences.iter().map(|(sequence, _)| sequence));
+        let mut succeed: BitVec = BitVec::repeat(false, keys.len());
+        for (sequence, value) in &sequences {
+            succeed.set(keys.position(sequence).unwrap(), *value);
+        }
+        Self { solutions, keys, succeed }
+    }
+
+    /// Returns all distinct solutions. They are sorted.
+    #[inline]
+    pub fn solutions(&self) -> &[SetupSolution] {
+        self.solutions.as_slice()
+    }
+
+    /// Returns `Some(true)` if the shape sequence can build the setup.
+    /// If the shape sequence was not searched, returns `None`.
+    #[inline]
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<bool> {
+        self.keys.position(sequence).map(|index| self.succeed[index])
+    }
+
+    /// Returns the pair of shape sequence and result in the searched order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(ShapeSequence, bool)> + '_ {
+        (0..self.keys.len()).map(|index| (self.keys.sequence_at(index), self.succeed[index]))
+    }
+
+    /// Returns the count of shape sequences that can build the setup.
+    #[inline]
+    pub fn count_succeed(&self) -> u64 {
+        self.succeed.count_ones() as u64
+    }
+
+    /// Returns the count of shape sequences that cannot build the setup.
+    #[inline]
+    pub fn count_failed(&self) -> u64 {
+        self.succeed.count_zeros() as u64
+    }
+
+    /// Return the count of all shape sequences independent of the result.
+    #[inline]
+    pub fn count_keys(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+
+    use crate::setup::{SetupResults, SetupSolution};
+    use crate::ShapeSequence;
+
+    #[test]
+    fn setup_results() {
+        use Shape::*;
+        let solution = SetupSolution::new(vec![
+            O.with(Orientation::North).with(bl(2, 0)),
+            O.with(Orientation::North).with(bl(0, 0)),
+        ]);
+        assert_eq!(solution.placements()[0], O.with(Orientation::North).with(bl(0, 0)));
+        assert_eq!(solution.shape_counter()[O], 2);
+
+        let results = SetupResults::new(vec![solution], vec![
+            (ShapeSequence::new(vec![O, O]), true),
+            (ShapeSequence::new(vec![O, T]), false),
+            (ShapeSequence::new(vec![T, O]), false),
+        ]);
+        assert_eq!(results.solutions().len(), 1);
+        assert_eq!(results.count_succeed(), 1);
+        assert_eq!(results.count_failed(), 2);
+        assert_eq!(results.count_keys(), 3);
+        assert_eq!(results.get(&ShapeSequence::new(vec![O, O])), Some(true));
+        assert_eq!(results.get(&ShapeSequence::new(vec![T, O])), Some(false));
+        assert_eq!(results.get(&ShapeSequence::new(vec![T, T])), None);
+        assert_eq!(results.iter().next(), Some((ShapeSequence::new(vec![O, O]), true)));
+    }
+}
@@ -1,7 +1,12 @@
-use std::ops;
+use std::{fmt, ops};
+use std::str::FromStr;
 
 use bitris::Shape;
 use derive_more::Constructor;
+use thiserror::Error;
+
+use crate::ShapesParseError;
+use crate::internals::{parse_shapes, write_shapes};
 
 /// Holds the count of each shape. Each shape can hold up to 255 items.
 /// ```
@@ -46,6 +51,11 @@ impl ShapeCounter {
             .fold(0, |sum, it| sum + it)
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counters.iter().all(|&it| it == 0)
+    }
+
     /// Returns a pair of each shape and its count.
     /// ```
     /// use bitris_commands::prelude::*;
@@ -63,15 +73,216 @@ impl ShapeCounter {
         }
         vec
     }
+
+    /// Returns all shapes, repeated by their count. They are in the order of `Shape`.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    /// let counter = ShapeCounter::from(vec![O, T, O]);
+    /// assert_eq!(counter.iter().collect::<Vec<_>>(), vec![T, O, O]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item=Shape> {
+        let counters = self.counters;
+        Shape::all_into_iter()
+            .flat_map(move |shape| (0..counters[shape as usize]).map(move |_| shape))
+    }
+
+    /// Returns true if all shapes of the other are contained, with at least the same count (sub-multiset).
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    /// let counter = ShapeCounter::from(vec![T, T, I]);
+    /// assert!(counter.contains(&ShapeCounter::from(vec![T, I])));
+    /// assert!(counter.contains(&ShapeCounter::empty()));
+    /// assert!(!counter.contains(&ShapeCounter::from(vec![I, I])));
+    /// ```
+    pub fn contains(&self, other: &ShapeCounter) -> bool {
+        self.counters.iter()
+            .zip(other.counters)
+            .all(|(&left, right)| right <= left)
+    }
+
+    /// Returns the sum of the counts of each shape.
+    /// Returns `None` if a count exceeds 255.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    /// let counter = ShapeCounter::from(vec![T, I]);
+    /// assert_eq!(counter.checked_add(ShapeCounter::from(vec![T])), Some(ShapeCounter::from(vec![T, T, I])));
+    /// assert_eq!(ShapeCounter::new([255; 7]).checked_add(counter), None);
+    /// ```
+    pub fn checked_add(self, other: ShapeCounter) -> Option<ShapeCounter> {
+        self.zip_checked(other, u8::checked_add)
+    }
+
+    /// Returns the difference of the counts of each shape. It's useful to get the remainder of a bag.
+    /// Returns `None` if the other is not contained.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    /// let counter = ShapeCounter::from(vec![T, T, I]);
+    /// assert_eq!(counter.checked_sub(ShapeCounter::from(vec![T])), Some(ShapeCounter::from(vec![T, I])));
+    /// assert_eq!(counter.checked_sub(ShapeCounter::from(vec![O])), None);
+    /// ```
+    pub fn checked_sub(self, other: ShapeCounter) -> Option<ShapeCounter> {
+        self.zip_checked(other, u8::checked_sub)
+    }
+
+    /// Returns the larger count of each shape.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    /// let counter = ShapeCounter::from(vec![T, T, I]);
+    /// assert_eq!(counter.union(ShapeCounter::from(vec![T, O])), ShapeCounter::from(vec![T, T, I, O]));
+    /// ```
+    pub fn union(self, other: ShapeCounter) -> ShapeCounter {
+        self.zip_checked(other, |left, right| Some(left.max(right))).unwrap()
+    }
+
+    /// Returns the smaller count of each shape.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    /// let counter = ShapeCounter::from(vec![T, T, I]);
+    /// assert_eq!(counter.intersection(ShapeCounter::from(vec![T, O])), ShapeCounter::from(vec![T]));
+    /// ```
+    pub fn intersection(self, other: ShapeCounter) -> ShapeCounter {
+        self.zip_checked(other, |left, right| Some(left.min(right))).unwrap()
+    }
+
+    /// Decrements the count of the shape.
+    /// Returns false and does nothing if the shape is not contained.
+    /// ```
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    /// let mut counter = ShapeCounter::from(vec![T, I]);
+    /// assert!(counter.remove(T));
+    /// assert!(!counter.remove(T));
+    /// assert_eq!(counter, ShapeCounter::from(vec![I]));
+    /// ```
+    pub fn remove(&mut self, shape: Shape) -> bool {
+        let counter = &mut self.counters[shape as usize];
+        if *counter == 0 {
+            return false;
+        }
+        *counter -= 1;
+        true
+    }
+
+    fn zip_checked(self, other: ShapeCounter, op: impl Fn(u8, u8) -> Option<u8>) -> Option<ShapeCounter> {
+        let mut counters: [u8; 7] = [0; 7];
+        for (index, counter) in counters.iter_mut().enumerate() {
+            *counter = op(self.counters[index], other.counters[index])?;
+        }
+        Some(ShapeCounter::new(counters))
+    }
+}
+
+/// A collection of errors that occur when making `ShapeCounter`.
+#[derive(Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ShapeCounterCreationError {
+    #[error("The count of {0:?} exceeds the upper limit. Up to 255 are supported.")]
+    TooManyShapes(Shape),
+}
+
+impl TryFrom<&[Shape]> for ShapeCounter {
+    type Error = ShapeCounterCreationError;
+
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let counter = ShapeCounter::try_from([Shape::T, Shape::T].as_slice()).unwrap();
+    /// assert_eq!(counter[Shape::T], 2);
+    ///
+    /// let result = ShapeCounter::try_from(vec![Shape::I; 256].as_slice());
+    /// assert_eq!(result, Err(ShapeCounterCreationError::TooManyShapes(Shape::I)));
+    /// ```
+    fn try_from(shapes: &[Shape]) -> Result<Self, Self::Error> {
+        let mut counters: [u8; 7] = [0; 7];
+        for &shape in shapes {
+            let counter = &mut counters[shape as usize];
+            *counter = counter.checked_add(1).ok_or(ShapeCounterCreationError::TooManyShapes(shape))?;
+        }
+        Ok(ShapeCounter::new(counters))
+    }
 }
 
 impl From<Vec<Shape>> for ShapeCounter {
+    /// Panics if the count of a shape exceeds 255. Use `ShapeCounter::try_from()` to check it.
     fn from(shapes: Vec<Shape>) -> Self {
-        let mut counters: [u8; 7] = [0; 7];
-        for shape in shapes {
-            counters[shape as usize] += 1;
+        ShapeCounter::try_from(shapes.as_slice()).unwrap()
+    }
+}
+
+impl ops::Add for ShapeCounter {
+    type Output = ShapeCounter;
+
+    /// Panics if a count exceeds 255. Use `ShapeCounter::checked_add()` to check it.
+    fn add(self, other: ShapeCounter) -> Self::Output {
+        self.checked_add(other).expect("The count of a shape overflows.")
+    }
+}
+
+impl ops::Sub for ShapeCounter {
+    type Output = ShapeCounter;
+
+    /// Panics if the other is not contained. Use `ShapeCounter::checked_sub()` to check it.
+    fn sub(self, other: ShapeCounter) -> Self::Output {
+        self.checked_sub(other).expect("The count of a shape underflows.")
+    }
+}
+
+impl fmt::Display for ShapeCounter {
+    /// Formats the shapes in brackets (like `[TTI]`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (shape, count) in self.to_pairs() {
+            write_shapes(f, (0..count).map(|_| shape))?;
         }
-        ShapeCounter::new(counters)
+        write!(f, "]")
+    }
+}
+
+impl FromStr for ShapeCounter {
+    type Err = ShapesParseError;
+
+    /// Parses the shapes in brackets (like `[TTI]`). The order of the shapes does not matter.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    ///
+    /// let counter = ShapeCounter::from_str("[ITT]").unwrap();
+    /// assert_eq!(counter, ShapeCounter::from(vec![T, T, I]));
+    /// assert_eq!(counter.to_string(), "[TTI]");
+    ///
+    /// assert_eq!(ShapeCounter::from_str("TTI"), Err(ShapesParseError::UnexpectedCharacter('T', 0)));
+    /// assert_eq!(ShapeCounter::from_str("[TTI"), Err(ShapesParseError::UnexpectedEnd));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ShapesParseError::*;
+
+        let trimmed = s.trim_start();
+        let offset = s.len() - trimmed.len();
+        let trimmed = trimmed.trim_end();
+
+        let Some(inner) = trimmed.strip_prefix('[') else {
+            return match trimmed.chars().next() {
+                Some(ch) => Err(UnexpectedCharacter(ch, offset)),
+                None => Err(UnexpectedEnd),
+            };
+        };
+        let Some(inner) = inner.strip_suffix(']') else {
+            return Err(UnexpectedEnd);
+        };
+
+        let shapes = parse_shapes(inner).map_err(|error| match error {
+            UnexpectedCharacter(ch, index) => UnexpectedCharacter(ch, offset + 1 + index),
+            error => error,
+        })?;
+
+        ShapeCounter::try_from(shapes.as_slice())
+            .map_err(|_| TooManyShapes(shapes.len()))
     }
 }
 
@@ -86,9 +297,11 @@ impl ops::Index<Shape> for ShapeCounter {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use bitris::Shape;
 
-    use crate::ShapeCounter;
+    use crate::{ShapeCounter, ShapesParseError};
 
     #[test]
     fn one_of_each() {
@@ -97,4 +310,33 @@ mod tests {
         assert!(Shape::all_into_iter().any(|shape| counter[shape] == 1));
         assert!(counter.to_pairs().into_iter().all(|(_, count)| count == 1));
     }
+
+    #[test]
+    fn operations() {
+        use Shape::*;
+
+        let bag = ShapeCounter::one_of_each();
+        let used = ShapeCounter::from(vec![T, I, O]);
+        let remainder = bag - used;
+        assert_eq!(remainder, ShapeCounter::from(vec![L, J, S, Z]));
+        assert_eq!(remainder + used, bag);
+        assert!(bag.contains(&remainder));
+        assert!(!remainder.contains(&used));
+        assert_eq!(remainder.union(used), bag);
+        assert_eq!(remainder.intersection(used), ShapeCounter::empty());
+        assert_eq!(remainder.iter().collect::<Vec<_>>(), vec![L, J, S, Z]);
+        assert_eq!(used.checked_sub(bag), None);
+    }
+
+    #[test]
+    fn parse() {
+        use ShapesParseError::*;
+
+        assert_eq!(ShapeCounter::from_str(" [] "), Ok(ShapeCounter::empty()));
+        assert_eq!(ShapeCounter::from_str("[ZSJLOIT]"), Ok(ShapeCounter::one_of_each()));
+        assert_eq!(ShapeCounter::one_of_each().to_string(), "[TIOLJSZ]");
+        assert_eq!(ShapeCounter::from_str(""), Err(UnexpectedEnd));
+        assert_eq!(ShapeCounter::from_str(" [TX]"), Err(UnexpectedCharacter('X', 3)));
+        assert_eq!(ShapeCounter::from_str(&format!("[{}]", "T".repeat(256))), Err(TooManyShapes(256)));
+    }
 }
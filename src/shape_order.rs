@@ -1,6 +1,13 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
 use bitris::Shape;
 use itertools::Itertools;
 
+use crate::{ShapeSequence, ShapesParseError};
+use crate::internals::{parse_shapes, write_shapes};
+
 /// A collection of operations to take one from a shape order.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
 pub enum PopOp {
@@ -36,6 +43,28 @@ impl<'a> OrderCursor<'a> {
         self.sequence.shapes.len() - self.tails + self.head.and(Some(1)).unwrap_or(0)
     }
 
+    /// Returns the index of the second in the order. If the second doesn't exist, returns the length of the order.
+    #[inline]
+    pub(crate) fn index_of_second(&self) -> usize {
+        self.tails
+    }
+
+    /// Returns the cursor at the same position on another order.
+    /// The order may be shorter if it has the same shapes up to the position. Then, the position is moved back to its end.
+    #[inline]
+    pub(crate) fn rebind<'b>(&self, sequence: &'b ShapeOrder) -> OrderCursor<'b> {
+        let len = sequence.shapes.len();
+        debug_assert!(self.tails <= len || !matches!(self.head, Some(head) if head < len));
+        OrderCursor { sequence, head: self.head.filter(|&head| head < len), tails: self.tails.min(len) }
+    }
+
+    /// Returns `true` if the shape is in the shapes that have not been used. It does not allocate.
+    #[inline]
+    pub(crate) fn contains_unused(&self, shape: Shape) -> bool {
+        self.head.is_some_and(|head| self.sequence.shapes[head] == shape)
+            || self.sequence.shapes[self.tails.min(self.sequence.shapes.len())..].contains(&shape)
+    }
+
     /// Returns shapes that have not been used as an order.
     #[inline]
     pub fn unused_shapes(&self) -> ShapeOrder {
@@ -63,7 +92,7 @@ impl<'a> OrderCursor<'a> {
     ///   In other words, equivalent to not using a hold.
     ///   Note, however, this means that "The second is not always the hold because the last one is assigned to the first, regardless of the hold".
     #[inline]
-    pub fn pop(&self, op: PopOp) -> (Option<Shape>, OrderCursor) {
+    pub fn pop(&self, op: PopOp) -> (Option<Shape>, OrderCursor<'a>) {
         return match op {
             PopOp::First => {
                 return if let Some(head) = self.head {
@@ -151,14 +180,85 @@ impl ShapeOrder {
     pub fn shapes(&self) -> &[Shape] {
         self.shapes.as_slice()
     }
+
+    /// Returns all sequences of shapes that can be consumed from `self` with one hold.
+    /// Let `consume_size` be the count of shapes to consume.
+    /// They are sorted, and there are no duplicates.
+    /// If panics, `len < consume_size`.
+    ///
+    /// See `ShapeSequence::infer_input()` for the inverse.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let order = ShapeOrder::from_str("TIO").unwrap();
+    /// let sequences: Vec<String> = order.infer_output(3).iter().map(|sequence| sequence.to_string()).collect();
+    /// assert_eq!(sequences, vec!["TIO", "TOI", "ITO", "IOT"]);
+    ///
+    /// let sequences: Vec<String> = order.infer_output(2).iter().map(|sequence| sequence.to_string()).collect();
+    /// assert_eq!(sequences, vec!["TI", "TO", "IT", "IO"]);
+    /// ```
+    pub fn infer_output(&self, consume_size: usize) -> Vec<ShapeSequence> {
+        fn build(cursor: OrderCursor, consume_size: usize, buffer: &mut Vec<Shape>, out: &mut BTreeSet<ShapeSequence>) {
+            if buffer.len() == consume_size {
+                out.insert(ShapeSequence::new(buffer.clone()));
+                return;
+            }
+
+            for op in [PopOp::First, PopOp::Second] {
+                if let (Some(shape), next_cursor) = cursor.pop(op) {
+                    buffer.push(shape);
+                    build(next_cursor, consume_size, buffer, out);
+                    buffer.pop();
+                }
+            }
+        }
+
+        assert!(consume_size <= self.shapes.len());
+
+        if consume_size == 0 {
+            return vec![ShapeSequence::new(vec![])];
+        }
+
+        let mut out = BTreeSet::new();
+        let mut buffer = Vec::<Shape>::with_capacity(consume_size);
+        build(self.new_cursor(), consume_size, &mut buffer, &mut out);
+        out.into_iter().collect()
+    }
+}
+
+impl FromStr for ShapeOrder {
+    type Err = ShapesParseError;
+
+    /// Parses the letters of the shapes (like `ITOLJ`). Lowercase letters are also accepted, and whitespaces are ignored.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    ///
+    /// let order = ShapeOrder::from_str("ITOLJ").unwrap();
+    /// assert_eq!(order, ShapeOrder::new(vec![I, T, O, L, J]));
+    /// assert_eq!(order.to_string(), "ITOLJ");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_shapes(s).map(ShapeOrder::new)
+    }
+}
+
+impl fmt::Display for ShapeOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_shapes(f, self.shapes.iter().copied())
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use bitris::*;
 
-    use crate::{PopOp, ShapeOrder};
+    use crate::{PopOp, ShapeOrder, ShapeSequence};
 
     #[test]
     #[should_panic]
@@ -298,4 +398,24 @@ mod tests {
         assert_eq!(cursor.peek(PopOp::First), None);
         assert_eq!(cursor.peek(PopOp::Second), None);
     }
+
+    #[test]
+    fn infer_output_is_inverse_of_infer_input() {
+        let sequence = ShapeSequence::from_str("TSZ").unwrap();
+        for infer_size in 3..=5 {
+            for fuzzy_order in sequence.infer_input(infer_size) {
+                for order in fuzzy_order.expand_as_wildcard() {
+                    assert!(order.infer_output(3).contains(&sequence), "{} -> {}", order, sequence);
+                }
+            }
+        }
+
+        let order = ShapeOrder::from_str("TSZI").unwrap();
+        for sequence in order.infer_output(3) {
+            let found = sequence.infer_input(4).into_iter()
+                .any(|fuzzy_order| fuzzy_order.expand_as_wildcard().contains(&order));
+            assert!(found, "{} -> {}", order, sequence);
+        }
+        assert_eq!(order.infer_output(0), vec![ShapeSequence::new(vec![])]);
+    }
 }
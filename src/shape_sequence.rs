@@ -1,8 +1,11 @@
+use std::fmt;
+use std::str::FromStr;
+
 use bitris::Shape;
 
-use crate::{BitShapes, ForEachVisitor, ShapeOrder};
+use crate::{BitShapes, ForEachVisitor, FuzzyShape, FuzzyShapeOrder, ShapeOrder, ShapesParseError, WideBitShapes};
 use crate::internal_macros::forward_impl_from;
-use crate::internals::{FuzzyShape, FuzzyShapeOrder};
+use crate::internals::{parse_shapes, write_shapes};
 
 /// Represents a sequence of shapes.
 /// "Sequence" means that it is not affected by the hold operation.
@@ -31,10 +34,21 @@ impl ShapeSequence {
 
 impl ShapeSequence {
     /// If `self` is the resulting sequence of shapes, infer the order that could be the input.
+    /// That is, it returns the orders that can consume the shapes of `self` in sequence with one hold.
     /// Let `infer_size` be the length of the order you wish to infer.
+    /// The shapes that are not consumed are unknown, since they can be anything.
     /// If panics, `infer_size < sequence_length`.
-    #[allow(dead_code)]
-    fn infer_input(&self, infer_size: usize) -> Vec<FuzzyShapeOrder> {
+    ///
+    /// See `ShapeOrder::infer_output()` for the inverse.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let sequence = ShapeSequence::from_str("TS").unwrap();
+    /// let orders: Vec<String> = sequence.infer_input(3).iter().map(|order| order.to_string()).collect();
+    /// assert_eq!(orders, vec!["?TS", "ST?", "T?S", "TS?"]);
+    /// ```
+    pub fn infer_input(&self, infer_size: usize) -> Vec<FuzzyShapeOrder> {
         assert!(self.shapes.len() <= infer_size);
 
         if self.shapes.is_empty() {
@@ -104,13 +118,46 @@ impl From<&BitShapes> for ShapeSequence {
 
 forward_impl_from!(ShapeSequence, from BitShapes);
 
+impl From<&WideBitShapes> for ShapeSequence {
+    fn from(bit_shapes: &WideBitShapes) -> Self {
+        Self { shapes: bit_shapes.to_vec() }
+    }
+}
+
+forward_impl_from!(ShapeSequence, from WideBitShapes);
+
+impl FromStr for ShapeSequence {
+    type Err = ShapesParseError;
+
+    /// Parses the letters of the shapes (like `ITOLJ`). Lowercase letters are also accepted, and whitespaces are ignored.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris_commands::prelude::*;
+    /// use Shape::*;
+    ///
+    /// let sequence = ShapeSequence::from_str("ITOLJ").unwrap();
+    /// assert_eq!(sequence, ShapeSequence::new(vec![I, T, O, L, J]));
+    /// assert_eq!(sequence.to_string(), "ITOLJ");
+    ///
+    /// assert_eq!(ShapeSequence::from_str("IX"), Err(ShapesParseError::UnexpectedCharacter('X', 1)));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_shapes(s).map(ShapeSequence::new)
+    }
+}
+
+impl fmt::Display for ShapeSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_shapes(f, self.shapes.iter().copied())
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
     use bitris::*;
 
-    use crate::internals::{FuzzyShape, FuzzyShapeOrder};
-    use crate::ShapeSequence;
+    use crate::{FuzzyShape, FuzzyShapeOrder, ShapeSequence};
 
     #[test]
     fn infer_input() {
@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+/// A collection of errors that occur when parsing shapes from letters (like `TIO`).
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ShapesParseError {
+    #[error("Unexpected character '{0}' at {1}.")]
+    UnexpectedCharacter(char, usize),
+    #[error("The text ends unexpectedly.")]
+    UnexpectedEnd,
+    #[error("Too many shapes ({0}) to hold.")]
+    TooManyShapes(usize),
+}
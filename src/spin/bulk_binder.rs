@@ -0,0 +1,101 @@
+use std::rc::Rc;
+
+use bitris::prelude::*;
+use bitris::srs::SrsKickTable;
+
+use crate::{ClippedBoard, Pattern, PatternElement, ShapeCounter};
+use crate::spin::{SpinBulkExecutor, SpinExecutorBulkCreationError, SpinResults};
+
+/// The binder to hold and tie settings for `SpinBulkExecutor`.
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct SpinBulkExecutorBinder<T: RotationSystem> {
+    pub rotation_system: Rc<T>,
+    pub allow_move: AllowMove,
+    pub clipped_board: ClippedBoard,
+    pub pattern: Rc<Pattern>,
+    pub allows_hold: bool,
+}
+
+impl SpinBulkExecutorBinder<SrsKickTable> {
+    /// Making the executor with SRS. See `SpinBulkExecutorBinder::default()` for more details.
+    pub fn srs() -> Self {
+        SpinBulkExecutorBinder::default(Rc::from(SrsKickTable))
+    }
+}
+
+impl<T: RotationSystem> SpinBulkExecutorBinder<T> {
+    /// Making the executor with default.
+    ///
+    /// The default values are as follows:
+    ///   + [required] rotation_system: set an argument (wrapped by Rc)
+    ///   + allow move: softdrop
+    ///   + board: blank
+    ///   + height: 4 lines
+    ///   + pattern: factorial of all shapes (like `*p7`)
+    ///   + allows hold: yes
+    pub fn default(rotation_system: Rc<T>) -> Self {
+        Self {
+            rotation_system,
+            allow_move: AllowMove::Softdrop,
+            clipped_board: ClippedBoard::try_new(Board64::blank(), 4).unwrap(),
+            pattern: Rc::from(Pattern::try_from(vec![
+                PatternElement::Factorial(ShapeCounter::one_of_each()),
+            ]).unwrap()),
+            allows_hold: true,
+        }
+    }
+
+    // See `SpinBulkExecutor::{try_new, execute}` for more details.
+    pub fn try_execute(&self) -> Result<SpinResults, SpinExecutorBulkCreationError> {
+        let move_rules = MoveRules::new(self.rotation_system.as_ref(), self.allow_move);
+        let executor = self.try_bind(&move_rules)?;
+        Ok(executor.execute())
+    }
+
+    fn try_bind<'a>(&'a self, move_rules: &'a MoveRules<T>) -> Result<SpinBulkExecutor<'a, T>, SpinExecutorBulkCreationError> {
+        SpinBulkExecutor::try_new(
+            move_rules,
+            self.clipped_board,
+            self.pattern.as_ref(),
+            self.allows_hold,
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeSequence};
+    use crate::spin::SpinBulkExecutorBinder;
+
+    #[test]
+    fn reuse() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let mut binder = SpinBulkExecutorBinder::srs();
+        binder.clipped_board = ClippedBoard::try_new(Board64::from_str("
+            XXXX......
+            XXX...XXXX
+            XXXX.XXXXX
+        ").unwrap(), 4).unwrap();
+        binder.pattern = Rc::from(Pattern::try_from(vec![
+            Wildcard,
+            One(T),
+        ]).unwrap());
+
+        let results = binder.try_execute().unwrap();
+        assert_eq!(results.count_succeed(), 7);
+        assert_eq!(results.count_keys(), 7);
+
+        binder.allows_hold = false;
+        let results = binder.try_execute().unwrap();
+        assert_eq!(results.get(&ShapeSequence::new(vec![T, T])), Some(true));
+        assert_eq!(results.count_succeed(), 7); // The slot remains open after placing any support
+    }
+}
@@ -0,0 +1,286 @@
+use bitris::prelude::*;
+use fxhash::FxHashSet;
+use thiserror::Error;
+
+use crate::{ClippedBoard, OrderCursor, Pattern, PopOp, ShapeSequence};
+use crate::internals::{can_reach_by_rotation, judge_spin_type};
+use crate::spin::{SpinCandidate, SpinResults};
+
+/// Dataset for detecting the same state during T-spin search.
+/// Supports don't clear lines, so the placed supports and the head of shapes determine the state.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+struct SearchingState {
+    // Sorted to ignore the order in which they were placed.
+    supports: Vec<BlPlacement>,
+
+    first: Option<Shape>,
+}
+
+
+/// A collection of errors that occur when making the executor.
+#[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum SpinExecutorBulkCreationError {
+    #[error("Board height exceeds the upper limit. Up to 56 are supported.")]
+    BoardIsTooHigh,
+}
+
+/// The executor to find T-spins.
+///
+/// The pieces before the T are placed as supports within the height, and they must not clear lines.
+/// Then, a T-spin is accepted if it clears at least one line and satisfies the following:
+///
+/// * The T rotates into the placement from a placement reachable with the move rules, with the kicks of the rotation system.
+///   With harddrop only, the T cannot rotate after landing, so no T-spins are found.
+/// * Three or more corners around the center of the T are occupied (3-corner rule).
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct SpinBulkExecutor<'a, T: RotationSystem> {
+    move_rules: &'a MoveRules<'a, T>,
+    clipped_board: ClippedBoard,
+    pattern: &'a Pattern,
+    allows_hold: bool,
+    spawn_position: BlPosition,
+}
+
+impl<'a, T: RotationSystem> SpinBulkExecutor<'a, T> {
+    /// Make SpinBulkExecutor.
+    ///
+    /// Returns `Err()` if the setting is incorrect or restricted.
+    /// See `SpinExecutorBulkCreationError` for error patterns.
+    /// ```
+    /// use std::str::FromStr;
+    /// use bitris::{Shape, Board64, MoveRules, AllowMove};
+    /// use bitris_commands::{ClippedBoard, Pattern, PatternElement};
+    /// use bitris_commands::spin::{SpinBulkExecutor, TSpinType};
+    ///
+    /// let move_rules = MoveRules::srs(AllowMove::Softdrop);
+    ///
+    /// let board = Board64::from_str("
+    ///     XXXX......
+    ///     XXX...XXXX
+    ///     XXXX.XXXXX
+    /// ").expect("Failed to create a board");
+    /// let clipped_board = ClippedBoard::try_new(board, 4).expect("Failed to clip");
+    ///
+    /// let pattern = Pattern::try_from(vec![
+    ///     PatternElement::One(Shape::T),
+    /// ]).expect("Failed to create a pattern");
+    ///
+    /// let executor = SpinBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true)
+    ///     .expect("Failed to create an executor");
+    ///
+    /// let results = executor.execute();
+    /// assert!(results.candidates().iter().any(|candidate| {
+    ///     candidate.spin_type() == TSpinType::Regular && candidate.lines_cleared() == 2
+    /// }));
+    /// ```
+    pub fn try_new(
+        move_rules: &'a MoveRules<T>,
+        clipped_board: ClippedBoard,
+        pattern: &'a Pattern,
+        allows_hold: bool,
+    ) -> Result<Self, SpinExecutorBulkCreationError> {
+        use SpinExecutorBulkCreationError::*;
+
+        if 56 < clipped_board.height() {
+            return Err(BoardIsTooHigh);
+        }
+
+        // Spawn above the height so that it is not stuck when harddrop only.
+        let spawn_position = bl(5, clipped_board.height() as i32 + 4);
+
+        Ok(Self { move_rules, clipped_board, pattern, allows_hold, spawn_position })
+    }
+
+    /// Start the search for T-spins in bulk.
+    /// All sequences are searched until no T remains to collect every candidate.
+    /// The sequences without T fail without searching.
+    pub fn execute(&self) -> SpinResults {
+        let mut candidates = FxHashSet::<SpinCandidate>::default();
+        let mut results = Vec::<(ShapeSequence, bool)>::new();
+        let mut searched = FxHashSet::<ShapeSequence>::default();
+
+        let mut visited_states = FxHashSet::<SearchingState>::default();
+        let mut supports = Vec::<BlPlacement>::with_capacity(self.pattern.dim_shapes());
+
+        for sequence in self.pattern.to_sequences() {
+            if !searched.insert(sequence.clone()) {
+                continue;
+            }
+
+            visited_states.clear();
+
+            let order = sequence.to_shape_order();
+            let succeed = self.pop_shape(
+                order.new_cursor(),
+                self.clipped_board.board(),
+                &mut supports,
+                &mut visited_states,
+                &mut candidates,
+            );
+
+            results.push((sequence, succeed));
+        }
+
+        let mut candidates: Vec<SpinCandidate> = candidates.into_iter().collect();
+        candidates.sort();
+
+        SpinResults::new(candidates, results)
+    }
+
+    fn pop_shape(
+        &self,
+        cursor: OrderCursor,
+        board: Board64,
+        supports: &mut Vec<BlPlacement>,
+        visited_states: &mut FxHashSet<SearchingState>,
+        candidates: &mut FxHashSet<SpinCandidate>,
+    ) -> bool {
+        // No T-spins can follow once neither the unused shapes nor the hold have a T.
+        if !cursor.contains_unused(Shape::T) {
+            return false;
+        }
+
+        let mut found = false;
+
+        let (popped, next_cursor) = cursor.pop(PopOp::First);
+        if let Some(shape) = popped {
+            found |= self.increment(shape, board, next_cursor, supports, visited_states, candidates);
+        } else {
+            return false;
+        }
+
+        if self.allows_hold {
+            let (popped, next_cursor) = cursor.pop(PopOp::Second);
+            if let Some(shape) = popped {
+                found |= self.increment(shape, board, next_cursor, supports, visited_states, candidates);
+            }
+        }
+
+        found
+    }
+
+    fn increment(
+        &self,
+        shape: Shape,
+        board: Board64,
+        next_cursor: OrderCursor,
+        supports: &mut Vec<BlPlacement>,
+        visited_states: &mut FxHashSet<SearchingState>,
+        candidates: &mut FxHashSet<SpinCandidate>,
+    ) -> bool {
+        let mut found = false;
+
+        let spawn = shape.with(Orientation::North).with(self.spawn_position);
+        let moves = self.move_rules.generate_minimized_moves(board, spawn);
+
+        for placement in moves {
+            if self.clipped_board.height() as i32 <= placement.tr_placement().position.ty {
+                continue;
+            }
+
+            if shape == Shape::T {
+                if let Some(spin_type) = judge_spin_type(&board, placement) {
+                    let mut next_board = board;
+                    let lines_cleared = placement.place_on_and_clear_lines(&mut next_board).unwrap().count();
+                    if 0 < lines_cleared && can_reach_by_rotation(self.move_rules, &board, spawn, placement) {
+                        candidates.insert(SpinCandidate::new(supports.clone(), placement, spin_type, lines_cleared));
+                        found = true;
+                    }
+                }
+            }
+
+            // Place it as a support.
+            let mut next_board = board;
+            let lines_cleared = placement.place_on_and_clear_lines(&mut next_board).unwrap();
+            if 0 < lines_cleared.count() {
+                continue;
+            }
+
+            supports.push(placement);
+
+            let state = SearchingState {
+                supports: {
+                    let mut sorted = supports.clone();
+                    sorted.sort();
+                    sorted
+                },
+                first: next_cursor.first(),
+            };
+
+            if visited_states.insert(state) {
+                found |= self.pop_shape(next_cursor, next_board, supports, visited_states, candidates);
+            }
+
+            supports.pop();
+        }
+
+        found
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitris::prelude::*;
+
+    use crate::{ClippedBoard, Pattern, PatternElement, ShapeSequence};
+    use crate::spin::{SpinBulkExecutor, TSpinType};
+
+    #[test]
+    fn tsd_with_support() {
+        use PatternElement::*;
+        use Shape::*;
+
+        let board = Board64::from_str("
+            XXX...XXXX
+            XXXX.XXXXX
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        // The overhang is needed.
+        let pattern = Pattern::try_from(vec![One(T)]).unwrap();
+        let executor = SpinBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_succeed(), 0);
+        assert!(results.candidates().is_empty());
+
+        // The I makes the overhang.
+        let pattern = Pattern::try_from(vec![One(I), One(T)]).unwrap();
+        let executor = SpinBulkExecutor::try_new(&move_rules, clipped_board, &pattern, false).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_succeed(), 1);
+
+        let tsd = results.candidates().iter()
+            .find(|candidate| candidate.spin_type() == TSpinType::Regular && candidate.lines_cleared() == 2)
+            .unwrap();
+        assert_eq!(tsd.t_placement().piece, T.with(Orientation::South));
+        assert_eq!(tsd.supports().len(), 1);
+        assert_eq!(tsd.supports()[0].piece.shape, I);
+    }
+
+    #[test]
+    fn seven_shapes() {
+        let board = Board64::from_str("
+            XXXX...XXX
+            XXXX......
+            XXX...XXXX
+            XXXX.XXXXX
+        ").unwrap();
+        let clipped_board = ClippedBoard::try_new(board, 4).unwrap();
+        let move_rules = MoveRules::srs(AllowMove::Softdrop);
+
+        // The search stops when no T remains, so it finishes even for all the orders of seven shapes.
+        let pattern = Pattern::from_str("*p7").unwrap();
+        let executor = SpinBulkExecutor::try_new(&move_rules, clipped_board, &pattern, true).unwrap();
+        let results = executor.execute();
+        assert_eq!(results.count_keys(), 5040);
+        assert_eq!(results.get(&ShapeSequence::from_str("TIOLJSZ").unwrap()), Some(true));
+        assert_eq!(results.get(&ShapeSequence::from_str("ITOLJSZ").unwrap()), Some(true));
+        assert!(results.candidates().iter().any(|candidate| {
+            candidate.spin_type() == TSpinType::Regular && candidate.lines_cleared() == 2 && candidate.supports().is_empty()
+        }));
+    }
+}
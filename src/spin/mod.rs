@@ -0,0 +1,7 @@
+pub use bulk_binder::*;
+pub use bulk_executor::*;
+pub use spin_results::*;
+
+mod bulk_binder;
+mod bulk_executor;
+mod spin_results;
@@ -0,0 +1,155 @@
+use bitris::prelude::*;
+use bitvec::vec::BitVec;
+
+use crate::ShapeSequence;
+use crate::internals::SequenceKeys;
+
+/// A collection of T-spin types by the 3-corner rule.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum TSpinType {
+    /// Three or more corners are occupied, but only one of the corners the T points to is.
+    Mini,
+
+    /// Three or more corners are occupied, including both corners the T points to.
+    Regular,
+}
+
+/// Represents a T-spin found by the search.
+/// The supports are the placements to be placed before the T. They are sorted.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct SpinCandidate {
+    supports: Vec<BlPlacement>,
+    t_placement: BlPlacement,
+    spin_type: TSpinType,
+    lines_cleared: u32,
+}
+
+impl SpinCandidate {
+    #[inline]
+    pub(crate) fn new(mut supports: Vec<BlPlacement>, t_placement: BlPlacement, spin_type: TSpinType, lines_cleared: u32) -> Self {
+        supports.sort();
+        Self { supports, t_placement, spin_type, lines_cleared }
+    }
+
+    /// Returns the placements to be placed before the T.
+    #[inline]
+    pub fn supports(&self) -> &[BlPlacement] {
+        self.supports.as_slice()
+    }
+
+    #[inline]
+    pub fn t_placement(&self) -> BlPlacement {
+        self.t_placement
+    }
+
+    #[inline]
+    pub fn spin_type(&self) -> TSpinType {
+        self.spin_type
+    }
+
+    /// Returns the count of lines cleared by the T (1: single, 2: double, 3: triple).
+    #[inline]
+    pub fn lines_cleared(&self) -> u32 {
+        self.lines_cleared
+    }
+}
+
+/// Holds the results of T-spin search.
+///
+/// It contains all distinct candidates found, and whether each shape sequence could make a T-spin.
+/// The shape sequences are kept in the order in which they were searched, and whether they succeed is stored as bits.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct SpinResults {
+    candidates: Vec<SpinCandidate>,
+    keys: SequenceKeys,
+    succeed: BitVec,
+}
+
+impl SpinResults {
+    #[inline]
+    pub(crate) fn new(candidates: Vec<SpinCandidate>, sequences: Vec<(ShapeSequence, bool)>) -> Self {
+        let keys = SequenceKeys::new(sequences.iter().map(|(sequence, _)| sequence));
+        let mut succeed: BitVec = BitVec::repeat(false, keys.len());
+        for (sequence, value) in &sequences {
+            succeed.set(keys.position(sequence).unwrap(), *value);
+        }
+        Self { candidates, keys, succeed }
+    }
+
+    /// Returns all distinct candidates. They are sorted.
+    #[inline]
+    pub fn candidates(&self) -> &[SpinCandidate] {
+        self.candidates.as_slice()
+    }
+
+    /// Returns `Some(true)` if the shape sequence can make a T-spin.
+    /// If the shape sequence was not searched, returns `None`.
+    #[inline]
+    pub fn get(&self, sequence: &ShapeSequence) -> Option<bool> {
+        self.keys.position(sequence).map(|index| self.succeed[index])
+    }
+
+    /// Returns the pair of shape sequence and result in the searched order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=(ShapeSequence, bool)> + '_ {
+        (0..self.keys.len()).map(|index| (self.keys.sequence_at(index), self.succeed[index]))
+    }
+
+    /// Returns the count of shape sequences that can make a T-spin.
+    #[inline]
+    pub fn count_succeed(&self) -> u64 {
+        self.succeed.count_ones() as u64
+    }
+
+    /// Returns the count of shape sequences that cannot make a T-spin.
+    #[inline]
+    pub fn count_failed(&self) -> u64 {
+        self.succeed.count_zeros() as u64
+    }
+
+    /// Return the count of all shape sequences independent of the result.
+    #[inline]
+    pub fn count_keys(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::prelude::*;
+
+    use crate::spin::{SpinCandidate, SpinResults, TSpinType};
+    use crate::ShapeSequence;
+
+    #[test]
+    fn spin_results() {
+        use Shape::*;
+        let candidate = SpinCandidate::new(
+            vec![
+                O.with(Orientation::North).with(bl(8, 0)),
+                O.with(Orientation::North).with(bl(0, 0)),
+            ],
+            T.with(Orientation::South).with(bl(3, 0)),
+            TSpinType::Regular,
+            2,
+        );
+        assert_eq!(candidate.supports()[0], O.with(Orientation::North).with(bl(0, 0)));
+        assert_eq!(candidate.t_placement(), T.with(Orientation::South).with(bl(3, 0)));
+        assert_eq!(candidate.spin_type(), TSpinType::Regular);
+        assert_eq!(candidate.lines_cleared(), 2);
+
+        let results = SpinResults::new(vec![candidate], vec![
+            (ShapeSequence::new(vec![O, O, T]), true),
+            (ShapeSequence::new(vec![O, T, O]), false),
+        ]);
+        assert_eq!(results.candidates().len(), 1);
+        assert_eq!(results.count_succeed(), 1);
+        assert_eq!(results.count_failed(), 1);
+        assert_eq!(results.count_keys(), 2);
+        assert_eq!(results.get(&ShapeSequence::new(vec![O, O, T])), Some(true));
+        assert_eq!(results.get(&ShapeSequence::new(vec![O, T, O])), Some(false));
+        assert_eq!(results.get(&ShapeSequence::new(vec![T, O, O])), None);
+        assert_eq!(results.iter().next(), Some((ShapeSequence::new(vec![O, O, T]), true)));
+    }
+}
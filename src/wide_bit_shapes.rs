@@ -0,0 +1,175 @@
+use std::fmt;
+use std::str::FromStr;
+
+use bitris::Shape;
+
+use crate::{BitShapes, BitShapesCreationError, ShapeSequence, ShapesParseError};
+use crate::internal_macros::{forward_impl_from, forward_impl_try_from};
+use crate::internals::{parse_shapes, write_shapes};
+
+/// The number of shapes packed into one word.
+const SHAPES_PER_WORD: usize = 22;
+
+/// A wider version of `BitShapes` that preserves the order of up to 66 shapes.
+/// Like `BitShapes`, it's represented in bits, making copying and comparing lightweight.
+/// ```
+/// use bitris_commands::prelude::*;
+///
+/// let shapes = WideBitShapes::try_from(vec![Shape::T, Shape::I].repeat(20)).unwrap();
+/// assert_eq!(shapes.len(), 40);
+/// assert_eq!(shapes.to_vec(), vec![Shape::T, Shape::I].repeat(20));
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub struct WideBitShapes {
+    values: [u64; 3],
+    len: u8,
+}
+
+impl WideBitShapes {
+    /// The maximum number of shapes.
+    pub const MAX_LEN: usize = SHAPES_PER_WORD * 3;
+
+    /// ```
+    /// use bitris_commands::WideBitShapes;
+    /// let shapes = WideBitShapes::empty();
+    /// assert_eq!(shapes.len(), 0);
+    /// assert_eq!(shapes.to_vec(), Vec::new());
+    /// ```
+    #[inline]
+    pub fn empty() -> Self {
+        Self { values: [0; 3], len: 0 }
+    }
+
+    #[inline]
+    pub fn len(self) -> usize {
+        self.len as usize
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.len == 0
+    }
+
+    pub fn to_vec(self) -> Vec<Shape> {
+        let len = self.len();
+        let mut vec = Vec::with_capacity(len);
+        for (word, mut value) in self.values.into_iter().enumerate() {
+            let start = word * SHAPES_PER_WORD;
+            for _ in start..len.min(start + SHAPES_PER_WORD) {
+                vec.push(Shape::try_from((value % 7) as usize).ok().unwrap());
+                value /= 7;
+            }
+        }
+        vec
+    }
+}
+
+impl TryFrom<&[Shape]> for WideBitShapes {
+    type Error = BitShapesCreationError;
+
+    fn try_from(shapes: &[Shape]) -> Result<Self, Self::Error> {
+        if WideBitShapes::MAX_LEN < shapes.len() {
+            return Err(BitShapesCreationError::TooManyShapes(shapes.len()));
+        }
+
+        let mut values = [0u64; 3];
+        for (word, chunk) in shapes.chunks(SHAPES_PER_WORD).enumerate() {
+            let mut scale = 1u64;
+            for shape in chunk {
+                values[word] += *shape as u64 * scale;
+                scale *= 7;
+            }
+        }
+        Ok(Self { values, len: shapes.len() as u8 })
+    }
+}
+
+impl TryFrom<&Vec<Shape>> for WideBitShapes {
+    type Error = BitShapesCreationError;
+
+    /// ```
+    /// use bitris_commands::prelude::*;
+    ///
+    /// let result = WideBitShapes::try_from(vec![Shape::T].repeat(67));
+    /// assert_eq!(result, Err(BitShapesCreationError::TooManyShapes(67)));
+    /// ```
+    fn try_from(shapes: &Vec<Shape>) -> Result<Self, Self::Error> {
+        WideBitShapes::try_from(shapes.as_slice())
+    }
+}
+
+forward_impl_try_from!(WideBitShapes, BitShapesCreationError, from Vec<Shape>);
+
+impl TryFrom<&ShapeSequence> for WideBitShapes {
+    type Error = BitShapesCreationError;
+
+    fn try_from(sequence: &ShapeSequence) -> Result<Self, Self::Error> {
+        WideBitShapes::try_from(sequence.shapes())
+    }
+}
+
+forward_impl_try_from!(WideBitShapes, BitShapesCreationError, from ShapeSequence);
+
+impl From<&BitShapes> for WideBitShapes {
+    /// It does not allocate, since `BitShapes` fits in the first word.
+    fn from(shapes: &BitShapes) -> Self {
+        let mut value = 0u64;
+        let mut scale = 1u64;
+        for shape in shapes.iter() {
+            value += shape as u64 * scale;
+            scale *= 7;
+        }
+        Self { values: [value, 0, 0], len: shapes.len() as u8 }
+    }
+}
+
+forward_impl_from!(WideBitShapes, from BitShapes);
+
+impl fmt::Display for WideBitShapes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_shapes(f, self.to_vec())
+    }
+}
+
+impl FromStr for WideBitShapes {
+    type Err = ShapesParseError;
+
+    /// Parses the letters of the shapes (like `TIO`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let shapes = parse_shapes(s)?;
+        WideBitShapes::try_from(shapes.as_slice())
+            .map_err(|_| ShapesParseError::TooManyShapes(shapes.len()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bitris::Shape;
+    use itertools::Itertools;
+
+    use crate::{BitShapes, ShapeSequence, WideBitShapes};
+
+    #[test]
+    fn len66() {
+        let vec = (0..66).map(|index| Shape::try_from(index % 7).unwrap()).collect_vec();
+        let shapes = WideBitShapes::try_from(&vec).unwrap();
+        assert_eq!(shapes.len(), 66);
+        assert_eq!(shapes.to_vec(), vec);
+        assert_eq!(ShapeSequence::from(shapes).shapes(), vec.as_slice());
+    }
+
+    #[test]
+    fn boundary() {
+        for len in [21, 22, 23, 44, 45] {
+            let vec = vec![Shape::Z].repeat(len);
+            assert_eq!(WideBitShapes::try_from(&vec).unwrap().to_vec(), vec);
+        }
+
+        let bit_shapes = BitShapes::try_from(vec![Shape::S, Shape::O]).unwrap();
+        assert_eq!(WideBitShapes::from(bit_shapes).to_vec(), vec![Shape::S, Shape::O]);
+
+        let vec = vec![Shape::Z].repeat(22);
+        assert_eq!(WideBitShapes::from(BitShapes::try_from(&vec).unwrap()), WideBitShapes::try_from(&vec).unwrap());
+    }
+}